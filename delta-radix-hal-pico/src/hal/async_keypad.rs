@@ -1,4 +1,4 @@
-use delta_radix_hal::{Key, Keypad, Display};
+use delta_radix_hal::{AutoRepeat, Key, Keypad, Display};
 use embedded_hal::digital::v2::OutputPin;
 use embedded_time::duration::{Extensions, Duration, Seconds, Microseconds};
 use rp_pico::{pac::{self, interrupt}, hal::{Sio, multicore::Stack, sio::SioFifo, timer::Alarm0, Timer}, Pins};
@@ -76,6 +76,7 @@ pub fn async_keypad_core1() -> ! {
         row5: pins.gpio28.into_push_pull_output(),
 
         currently_pressed: None,
+        auto_repeat: AutoRepeat::default(),
     };
 
     // Set up timer stuff
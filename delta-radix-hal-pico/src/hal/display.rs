@@ -15,6 +15,11 @@ type LcdBacklight = Gpio5;
 pub struct LcdDisplay<'d> {
     pub delay: &'d mut Delay,
     pub backlight: Pin<LcdBacklight, Output<PushPull>>,
+
+    /// The LCD's current DDRAM address, which is where the next character will be written. The
+    /// LCD can't be read from, so this is tracked as characters are written.
+    pub address: u8,
+
     pub lcd: HD44780<
         FourBitBus<
             Pin<LcdRs, Output<PushPull>>,
@@ -110,6 +115,81 @@ mod chars {
         0b00000000,
         0b00000000,
     ]);
+
+    pub const XOR: CustomChar = CustomChar::new(6, [
+        0b00000000,
+        0b00001110,
+        0b00010101,
+        0b00011111,
+        0b00010101,
+        0b00001110,
+        0b00000000,
+        0b00000000,
+    ]);
+
+    /// Redefined with the inverted bitmap of a character whenever one is printed inverse, so only
+    /// one inverse character can be shown at a time.
+    pub const INVERSE_INDEX: u8 = 7;
+}
+
+/// Bitmaps of the characters in the LCD's character ROM which the OS can print inverse, so that
+/// they can be inverted in a custom character.
+mod font {
+    use super::chars;
+
+    pub fn bitmap(c: char) -> Option<[u8; 8]> {
+        let rows = match c {
+            '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+            '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+            '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+            '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+            '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+            '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+            '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+            '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+            '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+            '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+            'A' => [0b01110, 0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001],
+            'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+            'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+            'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+            'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+            'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+
+            '+' => [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000],
+            '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+            '÷' => [0b00000, 0b00100, 0b00000, 0b11111, 0b00000, 0b00100, 0b00000],
+            '%' => [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011],
+            '^' => [0b00100, 0b01010, 0b10001, 0b00000, 0b00000, 0b00000, 0b00000],
+            '«' => [0b00000, 0b00100, 0b01000, 0b11111, 0b01000, 0b00100, 0b00000],
+            '»' => [0b00000, 0b00100, 0b00010, 0b11111, 0b00010, 0b00100, 0b00000],
+            '&' => [0b01100, 0b10010, 0b10100, 0b01000, 0b10101, 0b10010, 0b01101],
+            '|' => [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+            '>' => [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000],
+            '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+            ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+
+            'x' => [0b00000, 0b00000, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001],
+            'b' => [0b10000, 0b10000, 0b10110, 0b11001, 0b10001, 0b10001, 0b11110],
+            'd' => [0b00001, 0b00001, 0b01101, 0b10011, 0b10001, 0b10001, 0b01111],
+            'o' => [0b00000, 0b00000, 0b01110, 0b10001, 0b10001, 0b10001, 0b01110],
+            'e' => [0b00000, 0b00000, 0b01110, 0b10001, 0b11111, 0b10000, 0b01110],
+            '?' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100],
+            '@' => [0b01110, 0b10001, 0b00001, 0b01101, 0b10101, 0b10101, 0b01110],
+            ';' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000],
+
+            // These are drawn with custom characters anyway
+            '*' => return Some(chars::MULTIPLY.data),
+            '⊕' => return Some(chars::XOR.data),
+
+            _ => return None,
+        };
+
+        // The bottom row is left empty for the cursor
+        let mut bitmap = [0; 8];
+        bitmap[..7].copy_from_slice(&rows);
+        Some(bitmap)
+    }
 }
 
 impl<'d> LcdDisplay<'d> {
@@ -123,6 +203,16 @@ impl<'d> LcdDisplay<'d> {
         0x14,
         0x54,
     ];
+
+    /// Moves [Self::address] on by one written character. The DDRAM holds two 40-character lines,
+    /// each split across two rows of the display, and the address skips from one to the other.
+    fn advance(&mut self) {
+        self.address = match self.address {
+            0x27 => 0x40,
+            0x67 => 0x00,
+            a => a + 1,
+        };
+    }
 }
 
 impl<'d> delta_radix_hal::Display for LcdDisplay<'d> {
@@ -133,6 +223,7 @@ impl<'d> delta_radix_hal::Display for LcdDisplay<'d> {
         chars::CURSOR_LEFT_WITH_WARNING.register(self);
         chars::CURSOR_RIGHT_WITH_WARNING.register(self);
         chars::MULTIPLY.register(self);
+        chars::XOR.register(self);
         
         self.clear();
 
@@ -144,6 +235,7 @@ impl<'d> delta_radix_hal::Display for LcdDisplay<'d> {
 
     fn clear(&mut self) {
         self.lcd.clear(self.delay).unwrap();
+        self.address = 0;
 
         // This command seems to take a while - prevent garbage
         self.delay.delay_ms(10);
@@ -151,14 +243,35 @@ impl<'d> delta_radix_hal::Display for LcdDisplay<'d> {
 
     fn print_char(&mut self, c: char) {
         self.lcd.write_char(c, self.delay).unwrap();
+        self.advance();
     }
 
     fn print_string(&mut self, s: &str) {
         self.lcd.write_str(s, self.delay).unwrap();
+        for _ in s.chars() {
+            self.advance();
+        }
+    }
+
+    fn print_char_inverse(&mut self, c: char) {
+        // The HD44780 can't invert characters, so draw an inverted copy of the character into a
+        // custom character. If we don't know what it looks like, a solid block at least marks
+        // the position
+        let mut bitmap = font::bitmap(c).unwrap_or([0; 8]);
+        for row in &mut bitmap {
+            *row ^= 0b11111;
+        }
+        CustomChar::new(chars::INVERSE_INDEX, bitmap).register(self);
+
+        // Defining the character leaves the LCD writing to CGRAM, so go back to where we were
+        self.lcd.set_cursor_pos(self.address, self.delay).unwrap();
+        self.lcd.write_byte(chars::INVERSE_INDEX, self.delay).unwrap();
+        self.advance();
     }
 
     fn set_position(&mut self, x: u8, y: u8) {
-        self.lcd.set_cursor_pos(Self::CURSOR_LINE_OFFSETS[y as usize] + x, self.delay).unwrap();
+        self.address = Self::CURSOR_LINE_OFFSETS[y as usize] + x;
+        self.lcd.set_cursor_pos(self.address, self.delay).unwrap();
     }
 
     fn get_position(&mut self) -> (u8, u8) {
@@ -175,12 +288,17 @@ impl<'d> delta_radix_hal::Display for LcdDisplay<'d> {
             DisplaySpecialCharacter::CursorRightWithWarning => chars::CURSOR_RIGHT_WITH_WARNING.index,
         };
         self.lcd.write_byte(byte, self.delay).unwrap();
+        self.advance();
     }
 
     fn print_glyph(&mut self, glyph: Glyph) {
         self.print_char(
             match glyph {
                 Glyph::Multiply => chars::MULTIPLY.index as char,
+                Glyph::BitXor => chars::XOR.index as char,
+                // The character ROM has arrows where ASCII has `~` and DEL
+                Glyph::ShiftLeft => 0b0111_1111 as char,
+                Glyph::ShiftRight => 0b0111_1110 as char,
                 // Not aligned with baseline of other operators, but it'll do!
                 Glyph::Divide => 0b1111_1101 as char,
                 _ => glyph.char(),
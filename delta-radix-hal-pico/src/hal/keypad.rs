@@ -1,7 +1,7 @@
-use core::convert::Infallible;
+use core::{convert::Infallible, time::Duration};
 
 use cortex_m::delay::Delay;
-use delta_radix_hal::Key;
+use delta_radix_hal::{AutoRepeat, Key};
 use embedded_hal::digital::v2::{InputPin, OutputPin};
 use rp_pico::hal::gpio::{bank0::{Gpio15, Gpio16, Gpio17, Gpio18, Gpio19, Gpio20, Gpio21, Gpio22, Gpio26, Gpio27, Gpio28}, Pin, Input, PullUp, Output, PushPull};
 
@@ -38,12 +38,27 @@ pub struct ButtonMatrix<'d> {
     pub row5: RowPin<Row5>,
 
     pub currently_pressed: Option<(u8, u8)>,
+    pub auto_repeat: AutoRepeat,
 }
 
 impl<'d> ButtonMatrix<'d> {
     const COLS: usize = 5;
     const ROWS: usize = 6;
 
+    /// The key at each position of the matrix, indexed by `[row][col]`. This is laid out the same
+    /// way as the keys are on the device, so row 0 is the top row, and column 0 is the left column.
+    ///
+    /// Every position has a key. Anything without a dedicated key, like the bitwise operators, is
+    /// reached through Shift instead, which the OS handles.
+    pub const KEY_MAP: [[Key; Self::COLS]; Self::ROWS] = [
+        [Key::Shift,    Key::Menu,         Key::Variable, Key::Left,       Key::Right],
+        [Key::Add,      Key::Subtract,     Key::Multiply, Key::Divide,     Key::Delete],
+        [Key::Digit(7), Key::Digit(8),     Key::Digit(9), Key::Digit(0xE), Key::Digit(0xF)],
+        [Key::Digit(4), Key::Digit(5),     Key::Digit(6), Key::Digit(0xC), Key::Digit(0xD)],
+        [Key::Digit(1), Key::Digit(2),     Key::Digit(3), Key::Digit(0xA), Key::Digit(0xB)],
+        [Key::Digit(0), Key::FormatSelect, Key::HexBase,  Key::BinaryBase, Key::Exe],
+    ];
+
     const DEBOUNCE_MS: u32 = 1;
 
     fn rows_and_cols(&mut self) ->
@@ -84,7 +99,9 @@ impl<'d> ButtonMatrix<'d> {
         None
     }
 
-    pub fn wait_press(&mut self) -> (u8, u8) {
+    /// Waits for a key to be pressed and returns its position, or returns `None` if the key which is
+    /// already pressed has been held long enough to repeat.
+    pub fn wait_press(&mut self) -> Option<(u8, u8)> {
         // If we're currently pressing, wait for a release, or a different press
         if let Some(current_press) = self.currently_pressed {
             loop {
@@ -92,11 +109,18 @@ impl<'d> ButtonMatrix<'d> {
                     // Wait the debounce time, and check that there's still no press
                     self.delay.delay_ms(Self::DEBOUNCE_MS);
                     if self.scan_matrix() != Some(current_press) {
+                        self.auto_repeat.release();
                         break;
                     }
                 }
     
                 self.delay.delay_ms(Self::DEBOUNCE_MS);
+
+                // Still held - if it's been long enough, report the same press again
+                // (This ignores the time spent scanning, but that's tiny in comparison)
+                if self.auto_repeat.hold(Duration::from_millis(Self::DEBOUNCE_MS as u64)).is_some() {
+                    return None;
+                }
             }
         }
 
@@ -109,7 +133,11 @@ impl<'d> ButtonMatrix<'d> {
                     if initial_press == debounce_press {
                         // Yep, that's a press! Store it and return
                         self.currently_pressed = Some(initial_press);
-                        return initial_press;
+                        match self.map_key(initial_press.0, initial_press.1) {
+                            Some(key) => self.auto_repeat.press(key),
+                            None => self.auto_repeat.release(),
+                        }
+                        return Some(initial_press);
                     }
                 }
             }
@@ -119,55 +147,14 @@ impl<'d> ButtonMatrix<'d> {
     }
 
     pub fn map_key(&self, row: u8, col: u8) -> Option<Key> {
-        match (col, row) {
-            (4, 5) => Some(Key::Exe),
-
-            (0, 1) => Some(Key::Add),
-            (1, 1) => Some(Key::Subtract),
-            (2, 1) => Some(Key::Multiply),
-            (3, 1) => Some(Key::Divide),
-
-            (4, 1) => Some(Key::Delete),
-
-            (3, 0) => Some(Key::Left),
-            (4, 0) => Some(Key::Right),
-
-            (2, 0) => Some(Key::Variable),
-            
-            (0, 5) => Some(Key::Digit(0)),
-            (0, 4) => Some(Key::Digit(1)),
-            (1, 4) => Some(Key::Digit(2)),
-            (2, 4) => Some(Key::Digit(3)),
-            (0, 3) => Some(Key::Digit(4)),
-            (1, 3) => Some(Key::Digit(5)),
-            (2, 3) => Some(Key::Digit(6)),
-            (0, 2) => Some(Key::Digit(7)),
-            (1, 2) => Some(Key::Digit(8)),
-            (2, 2) => Some(Key::Digit(9)),
-
-            (3, 4) => Some(Key::Digit(0xA)),
-            (4, 4) => Some(Key::Digit(0xB)),
-            (3, 3) => Some(Key::Digit(0xC)),
-            (4, 3) => Some(Key::Digit(0xD)),
-            (3, 2) => Some(Key::Digit(0xE)),
-            (4, 2) => Some(Key::Digit(0xF)),
-
-            (1, 5) => Some(Key::FormatSelect),
-            (2, 5) => Some(Key::HexBase),
-            (3, 5) => Some(Key::BinaryBase),
-
-            (0, 0) => Some(Key::Shift),
-            (1, 0) => Some(Key::Menu),
-            
-            _ => None,
-        }
+        Self::KEY_MAP.get(row as usize)?.get(col as usize).copied()
     }
 }
 
 impl<'d> delta_radix_hal::Keypad for ButtonMatrix<'d> {
     async fn wait_key(&mut self) -> Key {
         loop {
-            let (r, c) = self.wait_press();
+            let Some((r, c)) = self.wait_press() else { return Key::Repeat };
             if let Some(key) = self.map_key(r, c) {
                 return key
             }
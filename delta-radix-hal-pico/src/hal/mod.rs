@@ -44,6 +44,14 @@ impl<'d> delta_radix_hal::Hal for PicoHal<'d> {
         }
         panic!("failed to access bootloader")
     }
+
+    fn free_memory(&self) -> Option<usize> {
+        Some(crate::ALLOCATOR.free())
+    }
+
+    // `load` and `store` aren't implemented, so nothing persists across restarts. Writing to flash
+    // needs both cores to stop executing from it while a sector is erased, and the keypad core
+    // isn't set up to pause for that.
 }
 
 pub unsafe fn enter_bootloader() {
@@ -25,7 +25,7 @@ fn lives_forever<T: ?Sized>(t: &mut T) -> &'static mut T {
 }
 
 #[global_allocator]
-static ALLOCATOR: CortexMHeap = CortexMHeap::empty();
+pub static ALLOCATOR: CortexMHeap = CortexMHeap::empty();
 const HEAP_SIZE: usize = 230_000;
 
 static mut CORE1_STACK: Stack<4096> = Stack::new();
@@ -89,7 +89,7 @@ fn main() -> ! {
     let lcd = HD44780::new_4bit(rs, en, d4, d5, d6, d7, &mut delay).unwrap();
 
     let mut hal = PicoHal {
-        display: hal::LcdDisplay { lcd, delay: lives_forever(&mut delay), backlight },
+        display: hal::LcdDisplay { lcd, delay: lives_forever(&mut delay), backlight, address: 0 },
         keypad: AsyncKeypadReceiver {
             fifo: lives_forever(&mut sio.fifo),
         },
@@ -4,18 +4,24 @@ use delta_radix_hal::{Display, Keypad, Key, Hal, Time};
 use termion::{raw::{IntoRawMode, RawTerminal}, input::{TermRead, Keys}};
 use termion::event::Key as TermKey;
 
-pub struct SimDisplay {
+pub struct SimDisplay<W: Write = RawTerminal<Stdout>> {
     x: u8,
     y: u8,
-    stdout: RawTerminal<Stdout>,
+    stdout: W,
 }
 
 impl SimDisplay {
+    fn new() -> Self {
+        let stdout = stdout().into_raw_mode().unwrap();
+        Self::with_output(stdout)
+    }
+}
+
+impl<W: Write> SimDisplay<W> {
     const ROWS: u8 = 4;
     const COLS: u8 = 20;
 
-    fn new() -> Self {
-        let stdout = stdout().into_raw_mode().unwrap();
+    fn with_output(stdout: W) -> Self {
         Self { stdout, x: 0, y: 0 }
     }
 
@@ -29,7 +35,7 @@ impl SimDisplay {
     }
 }
 
-impl Display for SimDisplay {
+impl<W: Write> Display for SimDisplay<W> {
     fn init(&mut self) {
         self.clear();
     }
@@ -51,6 +57,13 @@ impl Display for SimDisplay {
         self.stdout.flush().unwrap();
     }
 
+    fn print_char_inverse(&mut self, c: char) {
+        write!(self.stdout, "{}", termion::style::Invert).unwrap();
+        self.print_char(c);
+        write!(self.stdout, "{}", termion::style::NoInvert).unwrap();
+        self.stdout.flush().unwrap();
+    }
+
     fn set_position(&mut self, x: u8, y: u8) {
         self.x = x;
         self.y = y;
@@ -72,6 +85,14 @@ impl SimKeypad {
         let keys = RefCell::new(stdin().keys());
         Self { keys }
     }
+
+    /// Blanks the terminal and shows a sleep indicator, like the hardware turning off its display.
+    /// The OS redraws everything on the next key.
+    fn show_sleep(&self) {
+        let mut stdout = stdout();
+        write!(stdout, "{}{}(asleep - press any key to wake)", termion::clear::All, termion::cursor::Goto(1, 1)).unwrap();
+        stdout.flush().unwrap();
+    }
 }
 
 pub struct SimTime;
@@ -90,26 +111,20 @@ impl Keypad for SimKeypad {
     async fn wait_key(&mut self) -> Key {
         loop {
             match self.keys.borrow_mut().next().unwrap().unwrap() {                                
-                TermKey::Char(c) if c.is_ascii_digit()
-                    => return Key::Digit(c.to_digit(10).unwrap() as u8),
-                TermKey::Char('x') => return Key::HexBase,
-                TermKey::Char('b') => return Key::BinaryBase,
-
-                TermKey::Char('+') => return Key::Add,
-                TermKey::Char('-') => return Key::Subtract,
-                TermKey::Char('*') => return Key::Multiply,
-                TermKey::Char('/') => return Key::Divide,
+                TermKey::Char('q') => panic!("exit"),
+                TermKey::Char('z') => {
+                    self.show_sleep();
+                    return Key::Sleep
+                },
+                TermKey::Char(c) => if let Some(key) = Key::from_char(c) {
+                    return key
+                },
 
                 TermKey::Left => return Key::Left,
                 TermKey::Right => return Key::Right,
                 TermKey::Backspace => return Key::Delete,
-                TermKey::Char('\n') => return Key::Exe,
                 TermKey::Esc => return Key::Menu,
 
-                TermKey::Char(' ') => return Key::Menu,
-                TermKey::Char('s') => return Key::Shift,
-                TermKey::Char('q') => panic!("exit"),
-
                 _ => (),
             };
         }
@@ -157,4 +172,36 @@ impl Hal for SimHal {
         display.print_string("No bootloader");
         time.sleep(Duration::from_secs(2)).await;
     }   
+
+    fn load(&self, key: &str) -> Option<Vec<u8>> {
+        std::fs::read(storage_path(key)).ok()
+    }
+
+    fn store(&mut self, key: &str, value: &[u8]) {
+        // Losing a stored value isn't worth crashing the simulator over
+        let _ = std::fs::write(storage_path(key), value);
+    }
+}
+
+/// The file used to store the value with the given key, in the system's temporary directory.
+fn storage_path(key: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("delta-radix-{}", key))
+}
+
+#[cfg(test)]
+mod tests {
+    use delta_radix_hal::Display;
+
+    use super::SimDisplay;
+
+    #[test]
+    fn test_print_char_inverse() {
+        let mut display = SimDisplay::with_output(vec![]);
+        display.print_char_inverse('A');
+
+        assert_eq!(display.get_position(), (1, 0));
+
+        let output = String::from_utf8(display.stdout).unwrap();
+        assert_eq!(output, format!("{}A{}", termion::style::Invert, termion::style::NoInvert));
+    }
 }
@@ -30,8 +30,27 @@ extern "C" {
 pub struct WebKeypad;
 impl Keypad for WebKeypad {
     async fn wait_key(&mut self) -> Key {
-        let value = radix_keypad_wait_key().await;
-        match value.as_string().expect("non-string returned from `radix_keypad_wait_key`").as_str() {
+        loop {
+            let value = radix_keypad_wait_key().await;
+            let value = value.as_string().expect("non-string returned from `radix_keypad_wait_key`");
+
+            // Characters typed on a physical keyboard are prefixed, to distinguish them from the
+            // names of on-screen buttons
+            if let Some(c) = value.strip_prefix("char:") {
+                match c.chars().next().and_then(Key::from_char) {
+                    Some(key) => return key,
+                    None => continue,
+                }
+            }
+
+            return Self::key_from_button(&value);
+        }
+    }
+}
+
+impl WebKeypad {
+    fn key_from_button(name: &str) -> Key {
+        match name {
             x if x.len() == 1 && x.chars().next().unwrap().is_ascii_hexdigit() => {
                 Key::Digit(char::to_digit(x.chars().next().unwrap(), 16).unwrap() as u8)
             },
@@ -39,6 +58,7 @@ impl Keypad for WebKeypad {
             "shift" => Key::Shift,
             "menu" => Key::Menu,
             "var" => todo!(),
+            "ans" => Key::Answer,
             "left" => Key::Left,
             "right" => Key::Right,
 
@@ -46,11 +66,19 @@ impl Keypad for WebKeypad {
             "subtract" => Key::Subtract,
             "multiply" => Key::Multiply,
             "divide" => Key::Divide,
+            "modulo" => Key::Modulo,
+            "power" => Key::Power,
+            "and" => Key::BitAnd,
+            "or" => Key::BitOr,
+            "xor" => Key::BitXor,
+            "shift-left" => Key::ShiftLeft,
+            "shift-right" => Key::ShiftRight,
             "delete" => Key::Delete,
 
             "format" => Key::FormatSelect,
             "hex" => Key::HexBase,
             "bin" => Key::BinaryBase,
+            "oct" => Key::OctalBase,
             "exe" => Key::Exe,
 
             _ => panic!("unknown keypad key"),
@@ -69,6 +97,13 @@ impl Time for WebTime {
     }
 }
 
+#[wasm_bindgen]
+extern "C" {
+    fn radix_copy_to_clipboard(text: &str);
+    fn radix_storage_load(key: &str) -> Option<Box<[u8]>>;
+    fn radix_storage_store(key: &str, value: &[u8]);
+}
+
 pub struct WebHal {
     display: WebDisplay,
     keypad: WebKeypad,
@@ -110,4 +145,16 @@ impl Hal for WebHal {
         display.print_string("No bootloader");
         time.sleep(Duration::from_secs(2)).await;
     }   
+
+    fn copy_to_clipboard(&mut self, text: &str) {
+        radix_copy_to_clipboard(text)
+    }
+
+    fn load(&self, key: &str) -> Option<Vec<u8>> {
+        radix_storage_load(key).map(|value| value.into_vec())
+    }
+
+    fn store(&mut self, key: &str, value: &[u8]) {
+        radix_storage_store(key, value)
+    }
 }
@@ -8,6 +8,14 @@ pub enum Glyph {
     Subtract,
     Multiply,
     Divide,
+    Modulo,
+    Power,
+    ShiftLeft,
+    ShiftRight,
+
+    BitAnd,
+    BitOr,
+    BitXor,
 
     Align,
 
@@ -17,11 +25,26 @@ pub enum Glyph {
     HexBase,
     BinaryBase,
     DecimalBase,
+    OctalBase,
+
+    Exponent,
 
     Variable,
+    Answer,
+
+    Separator,
 }
 
 impl Glyph {
+    /// Whether this glyph is a binary operator. [Glyph::Subtract] is included, even though it can
+    /// also be a unary negation.
+    pub fn is_operator(&self) -> bool {
+        matches!(self,
+            Self::Add | Self::Subtract | Self::Multiply | Self::Divide | Self::Modulo | Self::Power
+            | Self::ShiftLeft | Self::ShiftRight
+            | Self::BitAnd | Self::BitOr | Self::BitXor | Self::Align)
+    }
+
     pub fn describe(&self) -> &'static str {
         match self {
             Self::Digit(_) => "digit",
@@ -30,6 +53,14 @@ impl Glyph {
             Self::Subtract => "subtract",
             Self::Multiply => "multiply",
             Self::Divide => "divide",
+            Self::Modulo => "modulo",
+            Self::Power => "power",
+            Self::ShiftLeft => "left shift",
+            Self::ShiftRight => "right shift",
+
+            Self::BitAnd => "and",
+            Self::BitOr => "or",
+            Self::BitXor => "xor",
 
             Self::Align => "align",
 
@@ -39,8 +70,14 @@ impl Glyph {
             Self::HexBase => "hex base",
             Self::BinaryBase => "bin base",
             Self::DecimalBase => "dec base",
+            Self::OctalBase => "oct base",
+
+            Self::Exponent => "exponent",
 
             Self::Variable => "variable",
+            Self::Answer => "answer",
+
+            Self::Separator => "separator",
         }
     }
 
@@ -52,6 +89,14 @@ impl Glyph {
             Glyph::Subtract => '-',
             Glyph::Multiply => '*',
             Glyph::Divide => '÷',
+            Glyph::Modulo => '%',
+            Glyph::Power => '^',
+            Glyph::ShiftLeft => '«',
+            Glyph::ShiftRight => '»',
+
+            Glyph::BitAnd => '&',
+            Glyph::BitOr => '|',
+            Glyph::BitXor => '⊕',
 
             Glyph::Align => '>',
 
@@ -61,8 +106,14 @@ impl Glyph {
             Glyph::HexBase => 'x',
             Glyph::BinaryBase => 'b',
             Glyph::DecimalBase => 'd',
+            Glyph::OctalBase => 'o',
+
+            Glyph::Exponent => 'e',
 
             Glyph::Variable => '?',
+            Glyph::Answer => '@',
+
+            Glyph::Separator => ';',
         }
     }
 
@@ -71,6 +122,8 @@ impl Glyph {
             'x' => Glyph::HexBase,
             'b' => Glyph::BinaryBase,
             'd' => Glyph::DecimalBase,
+            'o' => Glyph::OctalBase,
+            'e' => Glyph::Exponent,
 
             _ if char::to_digit(c, 16).is_some()
                 => Glyph::Digit(char::to_digit(c, 16).unwrap() as u8),
@@ -79,11 +132,22 @@ impl Glyph {
             '-' => Glyph::Subtract,
             '*' => Glyph::Multiply,
             '÷' => Glyph::Divide,
+            '%' => Glyph::Modulo,
+            '^' => Glyph::Power,
+            '«' => Glyph::ShiftLeft,
+            '»' => Glyph::ShiftRight,
+
+            '&' => Glyph::BitAnd,
+            '|' => Glyph::BitOr,
+            '⊕' => Glyph::BitXor,
 
             '(' => Glyph::LeftParen,
             ')' => Glyph::RightParen,
 
             '?' => Glyph::Variable,
+            '@' => Glyph::Answer,
+
+            ';' => Glyph::Separator,
 
             _ => return None,
         })
@@ -109,6 +173,14 @@ pub trait Display {
 
     fn print_char(&mut self, c: char);
 
+    /// Prints a character with inverted colours, to highlight it.
+    /// 
+    /// Displays which can't invert characters can use some other marker instead. By default, this
+    /// is just [Display::print_char].
+    fn print_char_inverse(&mut self, c: char) {
+        self.print_char(c)
+    }
+
     fn set_position(&mut self, x: u8, y: u8);
     fn get_position(&mut self) -> (u8, u8);
 
@@ -1,3 +1,5 @@
+use core::time::Duration;
+
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum Key {
     Digit(u8),
@@ -9,8 +11,20 @@ pub enum Key {
     Subtract,
     Multiply,
     Divide,
+    Modulo,
+    Power,
+    ShiftLeft,
+    ShiftRight,
+
+    BitAnd,
+    BitOr,
+    BitXor,
 
     Variable,
+    Answer,
+
+    LeftParen,
+    RightParen,
 
     Left,
     Right,
@@ -18,12 +32,15 @@ pub enum Key {
 
     HexBase,
     BinaryBase,
+    OctalBase,
 
     FormatSelect,
 
-    // Neither are actual keys, just markers to communicate things to OS
+    // None are actual keys, just markers to communicate things to OS
     DebugTerminate,
     Sleep,
+    /// The last key is still held down, so may be entered again. See [AutoRepeat].
+    Repeat,
 }
 
 impl Key {
@@ -45,7 +62,19 @@ impl Key {
             Key::FormatSelect => 0x10D,
             Key::DebugTerminate => 0x10E,
             Key::Sleep => 0x10F,
+            Key::Repeat => 0x11C,
             Key::Variable => 0x110,
+            Key::LeftParen => 0x111,
+            Key::RightParen => 0x112,
+            Key::Modulo => 0x113,
+            Key::Power => 0x114,
+            Key::BitAnd => 0x115,
+            Key::BitOr => 0x116,
+            Key::BitXor => 0x117,
+            Key::ShiftLeft => 0x118,
+            Key::ShiftRight => 0x119,
+            Key::Answer => 0x11A,
+            Key::OctalBase => 0x11B,
         }
     }
 
@@ -67,11 +96,151 @@ impl Key {
             0x10D => Key::FormatSelect,
             0x10E => Key::DebugTerminate,
             0x10F => Key::Sleep,
+            0x11C => Key::Repeat,
             0x110 => Key::Variable,
+            0x111 => Key::LeftParen,
+            0x112 => Key::RightParen,
+            0x113 => Key::Modulo,
+            0x114 => Key::Power,
+            0x115 => Key::BitAnd,
+            0x116 => Key::BitOr,
+            0x117 => Key::BitXor,
+            0x118 => Key::ShiftLeft,
+            0x119 => Key::ShiftRight,
+            0x11A => Key::Answer,
+            0x11B => Key::OctalBase,
 
             _ => return None,
         })
     }
+
+    /// Whether holding this key down should enter it repeatedly, like typing a run of the same digit.
+    /// Repeating other keys, like Exe or Shift, would rarely be useful and easily be accidental.
+    pub fn auto_repeats(&self) -> bool {
+        matches!(self, Key::Digit(_))
+    }
+
+    /// Gets the key which a character typed on a physical keyboard corresponds to, for HALs which
+    /// accept input from one. Keys which don't have an obvious character, like the arrow keys, must
+    /// be handled separately.
+    /// 
+    /// Since `b` is used for the binary base, hex digits must be typed in uppercase.
+    /// 
+    /// ```rust
+    /// # use delta_radix_hal::Key;
+    /// assert_eq!(Key::from_char('7'), Some(Key::Digit(7)));
+    /// assert_eq!(Key::from_char('B'), Some(Key::Digit(0xB)));
+    /// assert_eq!(Key::from_char('b'), Some(Key::BinaryBase));
+    /// assert_eq!(Key::from_char('+'), Some(Key::Add));
+    /// assert_eq!(Key::from_char('\n'), Some(Key::Exe));
+    /// assert_eq!(Key::from_char('z'), None);
+    /// ```
+    pub fn from_char(c: char) -> Option<Key> {
+        Some(match c {
+            '0'..='9' | 'A'..='F' => Key::Digit(c.to_digit(16).unwrap() as u8),
+            'x' => Key::HexBase,
+            'b' => Key::BinaryBase,
+            'o' => Key::OctalBase,
+
+            '+' => Key::Add,
+            '-' => Key::Subtract,
+            '*' => Key::Multiply,
+            '/' => Key::Divide,
+            '%' => Key::Modulo,
+            '^' => Key::Power,
+            '&' => Key::BitAnd,
+            '|' => Key::BitOr,
+            '<' => Key::ShiftLeft,
+            '>' => Key::ShiftRight,
+            '(' => Key::LeftParen,
+            ')' => Key::RightParen,
+            '?' => Key::Variable,
+            '@' => Key::Answer,
+
+            '\n' | '=' => Key::Exe,
+            ' ' => Key::Menu,
+            's' => Key::Shift,
+
+            _ => return None,
+        })
+    }
+}
+
+/// Decides when a key which is being held down should be reported again, as though it was pressed
+/// repeatedly. Only keys where [Key::auto_repeats] is true are repeated.
+/// 
+/// Repeats are reported as [Key::Repeat] rather than as the held key, so that the OS can tell them
+/// apart from real presses, and ignore them where they wouldn't make sense, like in a menu.
+/// 
+/// This is for HALs which scan their own keys - others, like those reading from a keyboard, usually
+/// get auto-repeat for free.
+/// 
+/// ```rust
+/// # use core::time::Duration;
+/// # use delta_radix_hal::{AutoRepeat, Key};
+/// let mut repeat = AutoRepeat::new(Duration::from_millis(500), Duration::from_millis(100));
+/// repeat.press(Key::Digit(1));
+/// 
+/// // Nothing happens until the key has been held for the delay...
+/// assert_eq!(repeat.hold(Duration::from_millis(400)), None);
+/// assert_eq!(repeat.hold(Duration::from_millis(100)), Some(Key::Repeat));
+/// 
+/// // ...and then it repeats once per interval
+/// assert_eq!(repeat.hold(Duration::from_millis(50)), None);
+/// assert_eq!(repeat.hold(Duration::from_millis(50)), Some(Key::Repeat));
+/// 
+/// repeat.release();
+/// assert_eq!(repeat.hold(Duration::from_millis(1000)), None);
+/// ```
+pub struct AutoRepeat {
+    delay: Duration,
+    interval: Duration,
+
+    /// If a key which repeats is being held, how much longer it must be held until it next repeats.
+    held: Option<Duration>,
+}
+
+impl AutoRepeat {
+    pub const DEFAULT_DELAY: Duration = Duration::from_millis(500);
+    pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+    /// Creates a new auto-repeater, which repeats a key once it has been held for `delay`, and
+    /// then again after every `interval`.
+    pub fn new(delay: Duration, interval: Duration) -> Self {
+        Self { delay, interval, held: None }
+    }
+
+    /// Records that a key has just been pressed.
+    pub fn press(&mut self, key: Key) {
+        self.held = if key.auto_repeats() { Some(self.delay) } else { None };
+    }
+
+    /// Records that the held key was released.
+    pub fn release(&mut self) {
+        self.held = None;
+    }
+
+    /// Records that the held key has been held for a further `elapsed` time, and returns
+    /// [Key::Repeat] if it should now be repeated.
+    pub fn hold(&mut self, elapsed: Duration) -> Option<Key> {
+        let remaining = self.held.as_mut()?;
+        match remaining.checked_sub(elapsed) {
+            Some(r) if !r.is_zero() => {
+                *remaining = r;
+                None
+            }
+            _ => {
+                *remaining = self.interval;
+                Some(Key::Repeat)
+            }
+        }
+    }
+}
+
+impl Default for AutoRepeat {
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_DELAY, Self::DEFAULT_INTERVAL)
+    }
 }
 
 pub trait Keypad {
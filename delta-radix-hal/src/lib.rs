@@ -3,6 +3,8 @@
 
 extern crate alloc;
 
+use alloc::vec::Vec;
+
 mod display;
 pub use display::*;
 
@@ -29,4 +31,28 @@ pub trait Hal {
     fn common_mut(&mut self) -> (&mut Self::D, &mut Self::K, &mut Self::T);
 
     async fn enter_bootloader(&mut self);
+
+    /// Copies text to the clipboard of the device running the HAL, if it has one.
+    /// 
+    /// Most platforms have no clipboard, so by default this does nothing.
+    fn copy_to_clipboard(&mut self, _text: &str) {}
+
+    /// Gets the number of bytes which can currently be allocated on the heap, if this is limited
+    /// and known.
+    /// 
+    /// This is used to refuse operations which would probably exhaust the heap, rather than
+    /// crashing partway through. By default, memory is assumed to be unlimited.
+    fn free_memory(&self) -> Option<usize> { None }
+
+    /// Loads a value previously saved with [Hal::store] under the same key, or `None` if there
+    /// isn't one.
+    /// 
+    /// By default there is no persistent storage, so nothing is ever loaded.
+    fn load(&self, _key: &str) -> Option<Vec<u8>> { None }
+
+    /// Saves a small value so that it is available through [Hal::load], ideally even after the
+    /// device restarts.
+    /// 
+    /// By default there is no persistent storage, so this does nothing.
+    fn store(&mut self, _key: &str, _value: &[u8]) {}
 }
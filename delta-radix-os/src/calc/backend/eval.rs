@@ -1,11 +1,140 @@
-use alloc::{format, string::String};
+use alloc::{format, string::String, vec, vec::Vec};
 
-use super::parse::{Node, NodeKind};
+use delta_radix_hal::Glyph;
+
+use super::parse::{Node, NodeKind, GlyphSpan, ParserError, ParserErrorKind};
 use flex_int::FlexInt;
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Configuration {
     pub data_type: DataType,
+    pub overflow_behaviour: OverflowBehaviour,
+    pub divide_by_zero: DivideByZero,
+    pub operators: OperatorGroups,
+
+    /// Whether hexadecimal and binary numbers are taken as the raw two's complement bit pattern of
+    /// a signed data type, so that `xFF` is -1 for S8, rather than an overflowing 255.
+    pub raw_based_literals: bool,
+}
+
+/// A group of operators which can be disabled, so that they are rejected by the parser.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OperatorGroup {
+    Align,
+    Modulo,
+    Power,
+    Bitwise,
+    Shift,
+}
+
+impl OperatorGroup {
+    /// The group which an operator belongs to, or `None` if it can't be disabled.
+    pub fn of(op: Glyph) -> Option<OperatorGroup> {
+        match op {
+            Glyph::Align => Some(OperatorGroup::Align),
+            Glyph::Modulo => Some(OperatorGroup::Modulo),
+            Glyph::Power => Some(OperatorGroup::Power),
+            Glyph::BitAnd | Glyph::BitOr | Glyph::BitXor => Some(OperatorGroup::Bitwise),
+            Glyph::ShiftLeft | Glyph::ShiftRight => Some(OperatorGroup::Shift),
+            _ => None,
+        }
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self {
+            OperatorGroup::Align => "align",
+            OperatorGroup::Modulo => "modulo",
+            OperatorGroup::Power => "power",
+            OperatorGroup::Bitwise => "bitwise",
+            OperatorGroup::Shift => "shift",
+        }
+    }
+
+    fn mask(&self) -> u8 {
+        1 << (*self as u8)
+    }
+}
+
+/// A set of [OperatorGroup]s which are enabled.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct OperatorGroups(u8);
+
+impl OperatorGroups {
+    pub const ALL: OperatorGroups = OperatorGroups(u8::MAX);
+
+    pub fn is_enabled(&self, group: OperatorGroup) -> bool {
+        self.0 & group.mask() != 0
+    }
+
+    pub fn set_enabled(&mut self, group: OperatorGroup, enabled: bool) {
+        if enabled {
+            self.0 |= group.mask();
+        } else {
+            self.0 &= !group.mask();
+        }
+    }
+}
+
+/// How the result of an operation is presented when it overflows.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OverflowBehaviour {
+    /// Keep the wrapped two's complement result.
+    Wrap,
+
+    /// Keep the wrapped result, but display it as unsigned, even for a signed data type.
+    Reinterpret,
+
+    /// Clamp the result of each overflowing operation to the largest or smallest value.
+    Saturate,
+}
+
+impl OverflowBehaviour {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            OverflowBehaviour::Wrap => "Wrap",
+            OverflowBehaviour::Reinterpret => "Unsigned",
+            OverflowBehaviour::Saturate => "Saturate",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            OverflowBehaviour::Wrap => OverflowBehaviour::Reinterpret,
+            OverflowBehaviour::Reinterpret => OverflowBehaviour::Saturate,
+            OverflowBehaviour::Saturate => OverflowBehaviour::Wrap,
+        }
+    }
+}
+
+/// What happens when dividing by zero.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum DivideByZero {
+    /// Fail evaluation with an error.
+    Error,
+
+    /// Give zero, marked as an overflow.
+    Zero,
+
+    /// Give the largest value of the data type, marked as an overflow.
+    Saturate,
+}
+
+impl DivideByZero {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            DivideByZero::Error => "Error",
+            DivideByZero::Zero => "Zero",
+            DivideByZero::Saturate => "Max",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            DivideByZero::Error => DivideByZero::Zero,
+            DivideByZero::Zero => DivideByZero::Saturate,
+            DivideByZero::Saturate => DivideByZero::Error,
+        }
+    }
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
@@ -20,40 +149,226 @@ impl DataType {
     }
 }
 
+/// An operation performed on numbers during evaluation, or on a result afterwards.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Operation {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Power,
+    ShiftLeft,
+    ShiftRight,
+    BitAnd,
+    BitOr,
+    BitXor,
+    Align,
+    Negate,
+
+    /// Conversion of a value into a different data type, which couldn't represent it.
+    Assign,
+}
+
+/// An operation which overflowed during evaluation.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Overflow {
+    pub operation: Operation,
+
+    /// The glyphs making up the operation, including its operands.
+    pub span: GlyphSpan,
+}
+
 #[derive(Clone, PartialEq, Eq, Debug)]
 pub struct EvaluationResult {
     pub result: FlexInt,
+
+    /// Whether any operation overflowed.
     pub overflow: bool,
+
+    /// Each operation which overflowed, in the order they were evaluated.
+    pub overflows: Vec<Overflow>,
 }
 
 impl EvaluationResult {
-    pub fn new(result: FlexInt, overflow: bool) -> Self {
-        Self { result, overflow }
+    pub fn new(result: FlexInt, overflows: Vec<Overflow>) -> Self {
+        Self { result, overflow: !overflows.is_empty(), overflows }
     }
 }
 
-pub fn evaluate(node: &Node, config: &Configuration) -> EvaluationResult {
+pub fn evaluate(node: &Node, config: &Configuration) -> Result<EvaluationResult, ParserError> {
     match &node.kind {
-        NodeKind::Number(num) => EvaluationResult::new(num.clone(), false),
+        NodeKind::Number(num) => Ok(EvaluationResult::new(num.clone(), vec![])),
         
         NodeKind::Add(a, b)
         | NodeKind::Subtract(a, b)
-        | NodeKind::Divide(a, b)
+        | NodeKind::Divide(a, b, _)
         | NodeKind::Multiply(a, b)
+        | NodeKind::Modulo(a, b, _)
+        | NodeKind::Power(a, b)
+        | NodeKind::ShiftLeft(a, b)
+        | NodeKind::ShiftRight(a, b)
+        | NodeKind::BitAnd(a, b)
+        | NodeKind::BitOr(a, b)
+        | NodeKind::BitXor(a, b)
         | NodeKind::Align(a, b) => {
-            let a: EvaluationResult = evaluate(a, config);
-            let b = evaluate(b, config);
-
-            let (result, overflow) = match &node.kind {
-                NodeKind::Add(_, _) => a.result.add(&b.result, config.data_type.signed),
-                NodeKind::Subtract(_, _) => a.result.subtract(&b.result, config.data_type.signed),
-                NodeKind::Multiply(_, _) => a.result.multiply(&b.result, config.data_type.signed),
-                NodeKind::Divide(_, _) => a.result.divide(&b.result, config.data_type.signed),
-                NodeKind::Align(_, _) => a.result.align(&b.result, config.data_type.signed),
+            let a: EvaluationResult = evaluate(a, config)?;
+            let b = evaluate(b, config)?;
+
+            let bits = config.data_type.bits;
+            let signed = config.data_type.signed;
+            let divide_by_zero = matches!(node.kind, NodeKind::Divide(_, _, _) | NodeKind::Modulo(_, _, _))
+                && b.result.is_zero();
+            let (operation, (mut result, overflow)) = match node.kind {
+                NodeKind::Divide(_, _, operator) | NodeKind::Modulo(_, _, operator) if divide_by_zero => (match node.kind {
+                    NodeKind::Modulo(_, _, _) => Operation::Modulo,
+                    _ => Operation::Divide,
+                }, match config.divide_by_zero {
+                    DivideByZero::Error =>
+                        return Err(ParserError::new(operator, ParserErrorKind::DivisionByZero)),
+                    DivideByZero::Zero => (FlexInt::new(bits), true),
+                    DivideByZero::Saturate => (FlexInt::max_value(bits, signed), true),
+                }),
+
+                NodeKind::Add(_, _) => (Operation::Add, a.result.add(&b.result, signed)),
+                NodeKind::Subtract(_, _) => (Operation::Subtract, a.result.subtract(&b.result, signed)),
+                NodeKind::Multiply(_, _) => (Operation::Multiply, a.result.multiply(&b.result, signed)),
+                NodeKind::Divide(_, _, _) => (Operation::Divide, a.result.divide(&b.result, signed)),
+                NodeKind::Modulo(_, _, _) => (Operation::Modulo, a.result.remainder(&b.result, signed)),
+                NodeKind::Power(_, _) => (Operation::Power, a.result.pow(&b.result, signed)),
+                NodeKind::ShiftLeft(_, _) => (Operation::ShiftLeft, shift_left(&a.result, &b.result, signed)),
+                NodeKind::ShiftRight(_, _) => (Operation::ShiftRight, (shift_right(&a.result, &b.result, signed), false)),
+                NodeKind::BitAnd(_, _) => (Operation::BitAnd, (a.result.bitwise_and(&b.result), false)),
+                NodeKind::BitOr(_, _) => (Operation::BitOr, (a.result.bitwise_or(&b.result), false)),
+                NodeKind::BitXor(_, _) => (Operation::BitXor, (a.result.bitwise_xor(&b.result), false)),
+                NodeKind::Align(_, _) => (Operation::Align, a.result.align(&b.result, signed)),
                 _ => unreachable!()
             };
 
-            EvaluationResult::new(result, a.overflow || b.overflow || overflow)
+            // Division by zero has already been given the result the user asked for
+            if overflow && !divide_by_zero && config.overflow_behaviour == OverflowBehaviour::Saturate {
+                result = if overflowed_upwards(&node.kind, &a.result, &b.result, signed) {
+                    FlexInt::max_value(bits, signed)
+                } else {
+                    FlexInt::min_value(bits, signed)
+                };
+            }
+
+            let mut overflows = a.overflows;
+            overflows.extend(b.overflows);
+            if overflow {
+                overflows.push(Overflow { operation, span: node.span });
+            }
+
+            Ok(EvaluationResult::new(result, overflows))
         },
+
+        NodeKind::Negate(a) => {
+            let a = evaluate(a, config)?;
+            let bits = config.data_type.bits;
+            let signed = config.data_type.signed;
+
+            // Only the smallest signed value can't be negated, and only zero can be negated when
+            // unsigned - otherwise, negation wraps
+            let (mut result, overflow) = match a.result.negate() {
+                Some(negated) => (negated, !signed && !a.result.is_zero()),
+                None => (a.result.clone(), true),
+            };
+            if overflow && config.overflow_behaviour == OverflowBehaviour::Saturate {
+                result = if signed { FlexInt::max_value(bits, signed) } else { FlexInt::new(bits) };
+            }
+
+            let mut overflows = a.overflows;
+            if overflow {
+                overflows.push(Overflow { operation: Operation::Negate, span: node.span });
+            }
+
+            Ok(EvaluationResult::new(result, overflows))
+        }
+    }
+}
+
+/// Converts a value of any width, interpreted with the given signedness, to a data type. This is
+/// needed when storing a value computed with one data type somewhere which uses another.
+/// 
+/// Returns the converted value, and whether it overflowed because the value can't be represented
+/// in the data type - for example, because it's too narrow.
+pub fn assign_checked(value: &FlexInt, signed: bool, data_type: DataType) -> (FlexInt, bool) {
+    // Extend both ways to a width which can represent either exactly, then compare
+    let exact_size = value.size().max(data_type.bits) + 1;
+    let exact = value.extend(exact_size, signed);
+    let (converted, _, _) = exact.shrink(data_type.bits);
+    let overflow = converted.extend(exact_size, data_type.signed) != exact;
+
+    (converted, overflow)
+}
+
+/// Estimates the peak number of bytes of heap needed to parse and evaluate an expression of the
+/// given length, with the given data type size.
+/// 
+/// This is deliberately pessimistic, since underestimating crashes the device.
+pub fn estimate_memory_usage(bits: usize, glyphs: usize) -> usize {
+    // Every glyph could become a node holding a number, which stores one byte per bit
+    const NODE_OVERHEAD: usize = 64;
+    let nodes = glyphs * (bits + NODE_OVERHEAD);
+
+    // Multiplication and division create several temporary numbers, some of double width
+    let temporaries = bits * 8;
+
+    // Tokens, the result string, and so on
+    const BASELINE: usize = 1024;
+
+    nodes + temporaries + BASELINE
+}
+
+/// The number of places to shift by, for the right-hand side of a shift operator. This is always
+/// treated as unsigned, and a value which doesn't fit in a `usize` shifts every bit out anyway.
+fn shift_amount(amount: &FlexInt) -> usize {
+    amount.to_u64()
+        .and_then(|a| usize::try_from(a).ok())
+        .unwrap_or(usize::MAX)
+}
+
+/// Shifts a value left, and returns whether it overflowed because the result is not the value
+/// multiplied by a power of two. For signed values, this includes changing the sign bit.
+fn shift_left(value: &FlexInt, amount: &FlexInt, signed: bool) -> (FlexInt, bool) {
+    let amount = shift_amount(amount);
+    let (result, overflow) = value.shift_left(amount);
+    if signed {
+        // Shifting back only gives the original value if every bit shifted out, and the new sign
+        // bit, matched the original sign
+        let overflow = result.shift_right_arithmetic(amount) != *value;
+        (result, overflow)
+    } else {
+        (result, overflow)
+    }
+}
+
+/// Shifts a value right - arithmetically if it's signed, so that negative values stay negative.
+fn shift_right(value: &FlexInt, amount: &FlexInt, signed: bool) -> FlexInt {
+    let amount = shift_amount(amount);
+    if signed {
+        value.shift_right_arithmetic(amount)
+    } else {
+        value.shift_right_logical(amount)
+    }
+}
+
+/// Given an operation which overflowed, determines whether its true result was too large to be
+/// represented, rather than too small.
+fn overflowed_upwards(kind: &NodeKind, a: &FlexInt, b: &FlexInt, signed: bool) -> bool {
+    match kind {
+        // Signed addition can only overflow if both operands have the same sign
+        NodeKind::Add(_, _) => !signed || !a.is_negative(),
+        NodeKind::Subtract(_, _) => signed && b.is_negative(),
+        NodeKind::Multiply(_, _) | NodeKind::Divide(_, _, _) | NodeKind::Modulo(_, _, _) =>
+            !signed || a.is_negative() == b.is_negative(),
+        // The result is only negative for an odd power of a negative number
+        NodeKind::Power(_, _) => !signed || !a.is_negative() || !b.bit(0),
+        NodeKind::Align(_, _) => true,
+        NodeKind::ShiftLeft(_, _) => !signed || !a.is_negative(),
+        // Right shifts and bitwise operations can't overflow
+        NodeKind::ShiftRight(_, _) | NodeKind::BitAnd(_, _) | NodeKind::BitOr(_, _) | NodeKind::BitXor(_, _)
+        | NodeKind::Number(_) | NodeKind::Negate(_) => unreachable!(),
     }
 }
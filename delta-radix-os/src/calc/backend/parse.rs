@@ -1,9 +1,9 @@
 use core::{ops::Range, marker::PhantomData};
 
-use alloc::{vec, vec::Vec, string::{String, ToString}, boxed::Box, format};
+use alloc::{vec, vec::Vec, string::{String, ToString}, boxed::Box, format, borrow::Cow};
 use delta_radix_hal::Glyph;
 
-use super::eval;
+use super::{eval::{self, OperatorGroup}, tokenize::{Tokenizer, Token, TokenKind, IncrementalTokenizer, TokenEdit}};
 use crate::calc::frontend::{Base, VariableArray};
 use flex_int::FlexInt;
 
@@ -14,6 +14,18 @@ pub struct GlyphSpan {
 }
 
 impl GlyphSpan {
+    pub fn new(start: usize, length: usize) -> Self {
+        GlyphSpan { start, length }
+    }
+
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
     pub fn indices(&self) -> Range<usize> {
         self.start..(self.start + self.length)
     }
@@ -32,7 +44,7 @@ impl GlyphSpan {
 }
 
 pub struct Node {
-    span: GlyphSpan,
+    pub span: GlyphSpan,
     pub kind: NodeKind,
 }
 
@@ -41,10 +53,58 @@ pub enum NodeKind {
 
     Add(Box<Node>, Box<Node>),
     Subtract(Box<Node>, Box<Node>),
-    Divide(Box<Node>, Box<Node>),
+    /// A division, with the index of its operator glyph, which is where division by zero is
+    /// reported.
+    Divide(Box<Node>, Box<Node>, usize),
     Multiply(Box<Node>, Box<Node>),
+    /// A modulo, with the index of its operator glyph, like [NodeKind::Divide].
+    Modulo(Box<Node>, Box<Node>, usize),
+    Power(Box<Node>, Box<Node>),
+    ShiftLeft(Box<Node>, Box<Node>),
+    ShiftRight(Box<Node>, Box<Node>),
+
+    BitAnd(Box<Node>, Box<Node>),
+    BitOr(Box<Node>, Box<Node>),
+    BitXor(Box<Node>, Box<Node>),
 
     Align(Box<Node>, Box<Node>),
+
+    /// A unary negation of anything other than a number literal. Negations of literals are
+    /// instead applied while parsing the number, so that values like `-128` in S8 fit.
+    Negate(Box<Node>),
+}
+
+impl Node {
+    /// Moves this node and everything within it to the given span, so that overflows and errors
+    /// from any part of it are reported there.
+    fn move_to(&mut self, span: GlyphSpan) {
+        self.span = span;
+        match &mut self.kind {
+            NodeKind::Number(_) => (),
+
+            NodeKind::Divide(a, b, operator) | NodeKind::Modulo(a, b, operator) => {
+                *operator = span.start();
+                a.move_to(span);
+                b.move_to(span);
+            }
+
+            NodeKind::Add(a, b)
+            | NodeKind::Subtract(a, b)
+            | NodeKind::Multiply(a, b)
+            | NodeKind::Power(a, b)
+            | NodeKind::ShiftLeft(a, b)
+            | NodeKind::ShiftRight(a, b)
+            | NodeKind::BitAnd(a, b)
+            | NodeKind::BitOr(a, b)
+            | NodeKind::BitXor(a, b)
+            | NodeKind::Align(a, b) => {
+                a.move_to(span);
+                b.move_to(span);
+            }
+
+            NodeKind::Negate(a) => a.move_to(span),
+        }
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -54,9 +114,22 @@ pub struct ParserError {
 }
 
 impl ParserError {
+    pub(crate) fn new(ptr: usize, kind: ParserErrorKind) -> Self {
+        ParserError { ptr, kind }
+    }
+
     pub fn describe(&self) -> String {
         self.kind.describe()
     }
+
+    /// The index of the glyph where the error was found.
+    pub fn ptr(&self) -> usize {
+        self.ptr
+    }
+
+    pub fn kind(&self) -> &ParserErrorKind {
+        &self.kind
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Debug)]
@@ -67,6 +140,11 @@ pub enum ParserErrorKind {
     ExpectedParen,
     UnexpectedEnd,
     InvalidVariable,
+    OperatorDisabled(OperatorGroup),
+    NegativeExponent,
+    OutOfMemory,
+    DivisionByZero,
+    RecursiveVariable,
 }
 
 impl ParserErrorKind {
@@ -78,18 +156,72 @@ impl ParserErrorKind {
             ParserErrorKind::ExpectedParen => "expected paren".to_string(),
             ParserErrorKind::UnexpectedEnd => "unexpected end".to_string(),
             ParserErrorKind::InvalidVariable => "invalid variable".to_string(),
+            ParserErrorKind::OperatorDisabled(g) => format!("{} disabled", g.describe()),
+            ParserErrorKind::NegativeExponent => "negative exponent".to_string(),
+            ParserErrorKind::OutOfMemory => "out of memory".to_string(),
+            ParserErrorKind::DivisionByZero => "division by zero".to_string(),
+            ParserErrorKind::RecursiveVariable => "recursive variable".to_string(),
         }
     }
 }
 
+/// The left-associative binary operators, grouped by precedence, from the loosest binding to the
+/// tightest. [Glyph::Power] binds more tightly than all of these, but is right-associative.
+/// 
+/// The bitwise operators have the same precedence as in C - AND binds most tightly, then XOR, then
+/// OR, and all of them bind less tightly than arithmetic. Shifts bind more tightly than addition,
+/// unlike in C, since a shift is really multiplication or division by a power of two.
+const PRECEDENCE: &[&[Glyph]] = &[
+    &[Glyph::Align],
+    &[Glyph::BitOr],
+    &[Glyph::BitXor],
+    &[Glyph::BitAnd],
+    &[Glyph::Add, Glyph::Subtract],
+    &[Glyph::ShiftLeft, Glyph::ShiftRight],
+    &[Glyph::Multiply, Glyph::Divide, Glyph::Modulo],
+];
+
+/// Whether the parser accepts a glyph as a binary operator.
+fn is_binary_operator(op: Glyph) -> bool {
+    op == Glyph::Power || PRECEDENCE.iter().any(|level| level.contains(&op))
+}
+
+/// Creates the node for a binary operator from [PRECEDENCE], given the index of its glyph.
+fn binary_node_kind(op: Glyph, lhs: Node, rhs: Node, operator: usize) -> NodeKind {
+    let (lhs, rhs) = (Box::new(lhs), Box::new(rhs));
+    match op {
+        Glyph::Align => NodeKind::Align(lhs, rhs),
+        Glyph::BitOr => NodeKind::BitOr(lhs, rhs),
+        Glyph::BitXor => NodeKind::BitXor(lhs, rhs),
+        Glyph::BitAnd => NodeKind::BitAnd(lhs, rhs),
+        Glyph::Add => NodeKind::Add(lhs, rhs),
+        Glyph::Subtract => NodeKind::Subtract(lhs, rhs),
+        Glyph::ShiftLeft => NodeKind::ShiftLeft(lhs, rhs),
+        Glyph::ShiftRight => NodeKind::ShiftRight(lhs, rhs),
+        Glyph::Multiply => NodeKind::Multiply(lhs, rhs),
+        Glyph::Divide => NodeKind::Divide(lhs, rhs, operator),
+        Glyph::Modulo => NodeKind::Modulo(lhs, rhs, operator),
+        _ => unreachable!(),
+    }
+}
+
 pub struct Parser<'g, 'v, N: NumberParser> {
     pub glyphs: &'g [Glyph],
+    pub tokens: Cow<'g, [Token]>,
     pub variables: &'v VariableArray,
     pub ptr: usize,
     pub eval_config: eval::Configuration,
     pub constant_overflow_spans: Vec<GlyphSpan>,
     pub next_number_unary_negations: usize,
 
+    /// The value which [Glyph::Answer] refers to, or `None` to treat it as zero. This must have
+    /// the width of the data type.
+    pub answer: Option<&'v FlexInt>,
+
+    /// The variables whose contents are being parsed by the parsers enclosing this one. A variable
+    /// which refers back to one of these would be expanded forever.
+    expanding_variables: Vec<u8>,
+
     _phantom: PhantomData<N>,
 }
 
@@ -97,11 +229,14 @@ impl<'g, 'v, N: NumberParser> Parser<'g, 'v, N> {
     pub fn new(glyphs: &'g [Glyph], variables: &'v VariableArray, eval_config: eval::Configuration) -> Self {
         Parser {
             glyphs,
+            tokens: Cow::Borrowed(&[]),
             variables,
             ptr: 0,
             eval_config,
             constant_overflow_spans: vec![],
             next_number_unary_negations: 0,
+            answer: None,
+            expanding_variables: vec![],
 
             _phantom: PhantomData,
         }
@@ -110,28 +245,57 @@ impl<'g, 'v, N: NumberParser> Parser<'g, 'v, N> {
     pub fn parse(&mut self) -> Result<Node, ParserError> {
         // Special case - if there are no tokens, parse to 0
         if self.glyphs.is_empty() {
-            return Ok(Node {
-                span: GlyphSpan { start: 0, length: 0 },
-                kind: NodeKind::Number(FlexInt::new(self.eval_config.data_type.bits)),
-            })
+            return Ok(self.empty_node())
         }
 
+        self.tokens = Cow::Owned(Tokenizer::new(self.glyphs).tokenize()?);
         let result = self.parse_top_level()?;
+        self.expect_end()?;
 
-        // Check we reached the end
-        if let Some(glyph) = self.here() {
-            Err(self.create_error(ParserErrorKind::UnexpectedGlyph(glyph)))
-        } else {
-            Ok(result)
+        Ok(result)
+    }
+
+    /// Parses a list of expressions separated by [Glyph::Separator], returning one node for each.
+    /// 
+    /// If there is no separator, this returns a single node, equivalent to [Parser::parse].
+    pub fn parse_list(&mut self) -> Result<Vec<Node>, ParserError> {
+        // Special case - if there are no tokens, parse to 0
+        if self.glyphs.is_empty() {
+            return Ok(vec![self.empty_node()])
+        }
+
+        self.tokens = Cow::Owned(Tokenizer::new(self.glyphs).tokenize()?);
+        self.parse_tokens_as_list()
+    }
+
+    fn parse_tokens_as_list(&mut self) -> Result<Vec<Node>, ParserError> {
+        let mut nodes = vec![self.parse_top_level()?];
+        while let Some(TokenKind::Separator) = self.here() {
+            self.advance();
+            nodes.push(self.parse_top_level()?);
+        }
+        self.expect_end()?;
+
+        Ok(nodes)
+    }
+
+    fn empty_node(&self) -> Node {
+        Node {
+            span: GlyphSpan { start: 0, length: 0 },
+            kind: NodeKind::Number(FlexInt::new(self.eval_config.data_type.bits)),
         }
     }
 
-    fn here(&self) -> Option<Glyph> {
-        self.glyphs.get(self.ptr).copied()
+    fn expect_end(&self) -> Result<(), ParserError> {
+        if self.here().is_some() {
+            Err(self.create_unexpected_error())
+        } else {
+            Ok(())
+        }
     }
 
-    fn peek(&self) -> Option<Glyph> {
-        self.glyphs.get(self.ptr + 1).copied()
+    fn here(&self) -> Option<&TokenKind> {
+        self.tokens.get(self.ptr).map(|t| &t.kind)
     }
 
     fn advance(&mut self) {
@@ -139,212 +303,270 @@ impl<'g, 'v, N: NumberParser> Parser<'g, 'v, N> {
     }
 
     fn parse_top_level(&mut self) -> Result<Node, ParserError> {
-        self.parse_align()
+        self.parse_binary(0)
     }
 
-    fn parse_align(&mut self) -> Result<Node, ParserError> {
-        let mut current = self.parse_add_sub()?;
+    /// Parses a left-associative chain of the operators at one level of [PRECEDENCE], whose
+    /// operands are parsed with the next level.
+    fn parse_binary(&mut self, level: usize) -> Result<Node, ParserError> {
+        let Some(operators) = PRECEDENCE.get(level) else { return self.parse_power() };
+        let mut current = self.parse_binary(level + 1)?;
 
-        while let Some(Glyph::Align) = self.here() {
+        while let Some(&TokenKind::Operator(op)) = self.here() {
+            if !operators.contains(&op) {
+                break;
+            }
+            self.check_enabled(op)?;
+            let operator = self.glyph_ptr();
             self.advance();
-            let rhs = self.parse_add_sub()?;
-            let span = current.span.merge(rhs.span);
+            let rhs = self.parse_binary(level + 1)?;
             current = Node {
-                span,
-                kind: NodeKind::Align(Box::new(current), Box::new(rhs))
+                span: current.span.merge(rhs.span),
+                kind: binary_node_kind(op, current, rhs, operator),
             };
         }
 
         Ok(current)
     }
 
-    fn parse_add_sub(&mut self) -> Result<Node, ParserError> {
-        let mut current = self.parse_mul_div()?;
+    fn parse_power(&mut self) -> Result<Node, ParserError> {
+        // Like in maths, a power binds more tightly than negation, so `-2^2` is `-(2^2)`. If no
+        // power follows, leave the negations for `parse_bottom` to apply to the number as usual
+        let start = self.glyph_ptr();
+        let negations = self.tokens[self.ptr..].iter()
+            .take_while(|t| t.kind == TokenKind::Operator(Glyph::Subtract))
+            .count();
+        if negations > 0 && !self.power_follows_operand(self.ptr + negations) {
+            return self.parse_bottom();
+        }
+        self.ptr += negations;
+
+        let base = self.parse_bottom()?;
+        let Some(TokenKind::Operator(Glyph::Power)) = self.here() else { return Ok(base) };
+        self.check_enabled(Glyph::Power)?;
+        self.advance();
+
+        // Right-associative, so `2^3^2` is `2^(3^2)`
+        let exponent = self.parse_power()?;
+        let mut node = Node {
+            span: base.span.merge(exponent.span),
+            kind: NodeKind::Power(Box::new(base), Box::new(exponent)),
+        };
 
-        while let Some(op @ (Glyph::Add | Glyph::Subtract)) = self.here() {
-            self.advance();
-            let rhs = self.parse_mul_div()?;
-            let span = current.span.merge(rhs.span);
-            let kind = match op {
-                Glyph::Add => NodeKind::Add(Box::new(current), Box::new(rhs)),
-                Glyph::Subtract => NodeKind::Subtract(Box::new(current), Box::new(rhs)),
-                _ => unreachable!(),
+        if negations % 2 == 1 {
+            node = Node {
+                span: node.span.merge(GlyphSpan::new(start, 1)),
+                kind: NodeKind::Negate(Box::new(node)),
             };
-            current = Node { span, kind };
         }
 
-        Ok(current)
+        Ok(node)
     }
 
-    fn parse_mul_div(&mut self) -> Result<Node, ParserError> {
-        let mut current = self.parse_bottom()?;
+    /// Whether the operand starting at the given token index is followed by a power operator. This
+    /// only looks at the tokens, so doesn't report errors in the operand itself.
+    fn power_follows_operand(&self, mut ptr: usize) -> bool {
+        let mut depth = 0usize;
+        loop {
+            match self.tokens.get(ptr).map(|t| &t.kind) {
+                Some(TokenKind::LeftParen) => depth += 1,
+                Some(TokenKind::RightParen) => depth = depth.saturating_sub(1),
+                Some(TokenKind::Number { .. } | TokenKind::Variable(_) | TokenKind::Answer) => (),
+                Some(_) if depth > 0 => (),
+                _ => return false,
+            }
+            ptr += 1;
 
-        while let Some(op @ (Glyph::Multiply | Glyph::Divide)) = self.here() {
-            self.advance();
-            let rhs = self.parse_bottom()?;
-            let span = current.span.merge(rhs.span);
-            let kind = match op {
-                Glyph::Multiply => NodeKind::Multiply(Box::new(current), Box::new(rhs)),
-                Glyph::Divide => NodeKind::Divide(Box::new(current), Box::new(rhs)),
-                _ => unreachable!(),
-            };
-            current = Node { span, kind };
+            if depth == 0 {
+                return matches!(self.tokens.get(ptr).map(|t| &t.kind), Some(TokenKind::Operator(Glyph::Power)));
+            }
         }
-
-        Ok(current)
     }
 
     fn parse_bottom(&mut self) -> Result<Node, ParserError> {
-        // Subtract as negation
-        if let Some(Glyph::Subtract) = self.here() {
-            self.next_number_unary_negations += 1;
-            self.advance();
-            return self.parse_bottom();
-        }
+        let Some(token) = self.tokens.get(self.ptr).cloned() else {
+            return Err(self.create_error(ParserErrorKind::UnexpectedEnd))
+        };
 
-        // Check for parentheses
-        if let Some(Glyph::LeftParen) = self.here() {
-            self.advance();
-            let node = self.parse_top_level()?;
-            let Some(Glyph::RightParen) = self.here() else {
-                return Err(self.create_error(ParserErrorKind::ExpectedParen.into()))
-            };
-            self.advance();
+        match token.kind {
+            // Subtract as negation
+            TokenKind::Operator(Glyph::Subtract) => {
+                self.next_number_unary_negations += 1;
+                self.advance();
+                if let Some(TokenKind::Number { .. } | TokenKind::Operator(Glyph::Subtract)) = self.here() {
+                    return self.parse_bottom()
+                }
 
-            return Ok(node);
-        }
+                // Anything other than a number literal is negated once it's been evaluated
+                let negations = core::mem::take(&mut self.next_number_unary_negations);
+                let operand = self.parse_bottom()?;
+                if negations % 2 == 1 {
+                    // Include any closing parenthesis, which isn't part of the operand's own span
+                    let start = token.span.start + 1 - negations;
+                    Ok(Node {
+                        span: GlyphSpan::new(start, 1).merge(self.tokens[self.ptr - 1].span),
+                        kind: NodeKind::Negate(Box::new(operand)),
+                    })
+                } else {
+                    Ok(operand)
+                }
+            }
 
-        // Check for variable
-        if let Some(Glyph::Variable) = self.here() {
-            // Figure out which variable we're using
-            self.advance();
-            let Some(Glyph::Digit(d)) = self.here() else {
-                return Err(self.create_error(ParserErrorKind::InvalidVariable.into()))
-            };
-            if d as usize >= self.variables.len() {
-                return Err(self.create_error(ParserErrorKind::InvalidVariable.into()))
-            };
-            self.advance();
+            // Parentheses
+            TokenKind::LeftParen => {
+                self.advance();
+                let node = self.parse_top_level()?;
+                let Some(TokenKind::RightParen) = self.here() else {
+                    return Err(self.create_error(ParserErrorKind::ExpectedParen))
+                };
+                self.advance();
 
-            // Parse its contents
-            let variable_glyphs = &self.variables[d as usize];
-            let mut variable_parser = Parser::<N>::new(
-                &variable_glyphs,
-                self.variables,
-                self.eval_config,
-            );
-            let variable_node = variable_parser.parse()?;
-
-            if !variable_parser.constant_overflow_spans.is_empty() {
-                self.constant_overflow_spans.push(GlyphSpan {
-                    start: self.ptr - 2, length: 2,
-                })
+                Ok(node)
             }
 
-            return Ok(variable_node);
-        }
+            // Variable
+            TokenKind::Variable(d) => {
+                let (variable_node, overflow) = self.parse_variable(d, token.span)?;
+                self.advance();
 
-        // Number
-        if let Some(g @ (Glyph::Digit(_) | Glyph::HexBase | Glyph::BinaryBase | Glyph::DecimalBase)) = self.here() {
-            let mut start = self.ptr;
-            let mut digits = vec![];
-            let mut base = None;
+                if overflow {
+                    self.constant_overflow_spans.push(token.span)
+                }
 
-            // Check for base at start
-            if let Some(b) = Base::from_glyph(g) {
-                self.advance();
-                base = Some(b);
-            };
+                Ok(variable_node)
+            }
 
-            // Gather digits
-            while let Some(Glyph::Digit(d)) = self.here() {
-                digits.push(char::from_digit(d as u32, 16).unwrap());
+            TokenKind::Answer => {
                 self.advance();
+                let value = match self.answer {
+                    Some(answer) => answer.clone(),
+                    None => FlexInt::new(self.eval_config.data_type.bits),
+                };
+                Ok(Node { span: token.span, kind: NodeKind::Number(value) })
             }
 
-            // Check for base at end
-            if let Some(b) = self.here().map(Base::from_glyph).flatten() {
-                if base.is_some() {
-                    return Err(self.create_error(ParserErrorKind::DuplicateBase));
-                }
+            // Number
+            TokenKind::Number { digits, base, exponent } => {
                 self.advance();
-                base = Some(b);
-            };
 
-            // Construct string of digits, considering negation
-            // (Specifically we want an odd number of unary negations; -2 is negative, --2 isn't)
-            let mut str: String = digits.into_iter().collect();
-            let mut force_parse_signed = false;
-            if self.next_number_unary_negations % 2 == 1 {
-                start -= self.next_number_unary_negations;
-                str.insert(0, '-');
-                self.next_number_unary_negations = 0;
-
-                // We'll need to parse this number as signed, even though the underlying data type
-                // is unsigned
-                if !self.eval_config.data_type.signed {
-                    force_parse_signed = true;
+                // Consider negation
+                // (Specifically we want an odd number of unary negations; -2 is negative, --2 isn't)
+                let negations = core::mem::take(&mut self.next_number_unary_negations);
+                let negative = negations % 2 == 1;
+                let start = if negative { token.span.start - negations } else { token.span.start };
+
+                let (num, overflow) = self.parse_number(digits, base, exponent, negative)
+                    .ok_or(self.create_error(ParserErrorKind::InvalidNumber))?;
+
+                // Add warning region of number parsing overflowed
+                let length = token.span.start + token.span.length - start;
+                let span = GlyphSpan { start, length };
+                if overflow {
+                    self.constant_overflow_spans.push(span);
                 }
+
+                Ok(Node { span, kind: NodeKind::Number(num) })
             }
 
-            // Parse number
-            let parse_signed = self.eval_config.data_type.signed || force_parse_signed;
-            let (num, mut overflow) =
-                N::parse(&str, base.unwrap_or(Base::Decimal), parse_signed, self.eval_config.data_type.bits)
-                .ok_or(self.create_error(ParserErrorKind::InvalidNumber))?;
+            _ => Err(self.create_unexpected_error()),
+        }
+    }
 
-            // Force-parsing a negative number will always result in overflow (because the data type
-            // can't represent the parsed number)
-            if force_parse_signed {
-                overflow = true;
-            }
+    /// Parses the contents of the variable referred to by a token, returning it as a node at the
+    /// token's span, and whether it contains a constant which overflows.
+    fn parse_variable(&self, d: u8, span: GlyphSpan) -> Result<(Node, bool), ParserError> {
+        if d as usize >= self.variables.len() {
+            return Err(ParserError::new(span.start, ParserErrorKind::InvalidVariable))
+        };
+        if self.expanding_variables.contains(&d) {
+            return Err(ParserError::new(span.start, ParserErrorKind::RecursiveVariable))
+        }
 
-            // Add warning region of number parsing overflowed
-            let length = self.ptr - start;
-            let span = GlyphSpan { start, length };
-            if overflow {
-                self.constant_overflow_spans.push(span);
-            }
+        let variable_glyphs = &self.variables[d as usize];
+        let mut variable_parser = Parser::<N>::new(
+            variable_glyphs,
+            self.variables,
+            self.eval_config,
+        );
+        variable_parser.answer = self.answer;
+        variable_parser.expanding_variables = self.expanding_variables.clone();
+        variable_parser.expanding_variables.push(d);
+
+        // Positions within the variable's glyphs don't mean anything in this expression, so
+        // report any error at the reference to the variable instead. The same goes for
+        // anything found while evaluating it
+        let mut variable_node = variable_parser.parse()
+            .map_err(|e| ParserError::new(span.start, e.kind))?;
+        variable_node.move_to(span);
+
+        Ok((variable_node, !variable_parser.constant_overflow_spans.is_empty()))
+    }
 
-            Ok(Node { span, kind: NodeKind::Number(num) })
-        } else if let Some(glyph) = self.here() {
-            Err(self.create_error(ParserErrorKind::UnexpectedGlyph(glyph)))
-        } else {
-            Err(self.create_error(ParserErrorKind::UnexpectedEnd))
+    /// Parses the digits of a number literal, which are the magnitude of a negative number if
+    /// `negative` is set. Returns the number and whether it overflowed, or `None` if it's invalid.
+    fn parse_number(&self, mut digits: Vec<u8>, base: Option<Base>, exponent: usize, negative: bool) -> Option<(FlexInt, bool)> {
+        // Once the exponent reaches the number of bits, the multiplier is a multiple of
+        // 2^bits, so the wrapped result is 0 (overflowing unless the mantissa is 0) no
+        // matter how much bigger the exponent gets - so don't bother adding lots of digits
+        let exponent = exponent.min(self.eval_config.data_type.bits);
+        digits.resize(digits.len() + exponent, 0);
+
+        // We'll need to parse a negative number as signed, even though the underlying data type
+        // is unsigned
+        let force_parse_signed = negative && !self.eval_config.data_type.signed;
+
+        // A raw bit pattern is parsed as unsigned, which gives the same bits as the signed
+        // number it represents, and only overflows if it doesn't fit
+        let base = base.unwrap_or(Base::Decimal);
+        let raw_pattern = self.eval_config.raw_based_literals
+            && base != Base::Decimal
+            && !negative;
+        let parse_signed = (self.eval_config.data_type.signed && !raw_pattern) || force_parse_signed;
+        let (num, overflow) = N::parse(&digits, base, parse_signed, negative, self.eval_config.data_type.bits)?;
+
+        // Force-parsing a negative number will always result in overflow (because the data type
+        // can't represent the parsed number)
+        Some((num, overflow || force_parse_signed))
+    }
+
+    /// Checks that an operator isn't in a group which has been disabled.
+    fn check_enabled(&self, op: Glyph) -> Result<(), ParserError> {
+        match OperatorGroup::of(op) {
+            Some(group) if !self.eval_config.operators.is_enabled(group) =>
+                Err(self.create_error(ParserErrorKind::OperatorDisabled(group))),
+            _ => Ok(()),
         }
     }
 
+    /// The index of the glyph which the current token starts at, or the end of the glyphs if there
+    /// are no tokens left.
+    fn glyph_ptr(&self) -> usize {
+        self.tokens.get(self.ptr)
+            .map(|t| t.span.start)
+            .unwrap_or(self.glyphs.len())
+    }
+
     fn create_error(&self, kind: ParserErrorKind) -> ParserError {
-        ParserError { ptr: self.ptr, kind }
+        ParserError { ptr: self.glyph_ptr(), kind }
+    }
+
+    /// Creates an error for a token which isn't valid in its position, described by the first glyph
+    /// of the token.
+    fn create_unexpected_error(&self) -> ParserError {
+        let ptr = self.glyph_ptr();
+        self.create_error(ParserErrorKind::UnexpectedGlyph(self.glyphs[ptr]))
     }
 }
 
 pub trait NumberParser {
-    fn parse(chars: &str, base: Base, signed: bool, bits: usize) -> Option<(FlexInt, bool)>;
+    /// Parses a list of digit values into a number. If `negative` is true, these digits are the
+    /// magnitude of a negative number.
+    fn parse(digits: &[u8], base: Base, signed: bool, negative: bool, bits: usize) -> Option<(FlexInt, bool)>;
 }
 
 impl NumberParser for FlexInt {
-    fn parse(chars: &str, base: Base, signed: bool, bits: usize) -> Option<(FlexInt, bool)> {
-        match base {
-            Base::Decimal => 
-                if signed {
-                    FlexInt::from_signed_decimal_string(chars, bits)
-                } else {
-                    FlexInt::from_unsigned_decimal_string(chars, bits)
-                }
-            Base::Hexadecimal =>
-                if signed {
-                    FlexInt::from_signed_hex_string(chars, bits)
-                } else {
-                    FlexInt::from_unsigned_hex_string(chars, bits)
-                }
-            Base::Binary => 
-                if signed {
-                    FlexInt::from_signed_binary_string(chars, bits)
-                } else {
-                    FlexInt::from_unsigned_binary_string(chars, bits)
-                }
-        }
+    fn parse(digits: &[u8], base: Base, signed: bool, negative: bool, bits: usize) -> Option<(FlexInt, bool)> {
+        FlexInt::from_ascii_digits(digits, base.radix(), signed, negative, bits)
     }
 }
 
@@ -353,17 +575,203 @@ impl NumberParser for FlexInt {
 /// suitable for per-keypress constant overflow checking.
 pub struct ConstantOverflowChecker;
 impl NumberParser for ConstantOverflowChecker {
-    fn parse(chars: &str, base: Base, signed: bool, bits: usize) -> Option<(FlexInt, bool)> {
-        let Ok(num) = i128::from_str_radix(chars, base.radix()) else {
+    fn parse(digits: &[u8], base: Base, signed: bool, negative: bool, bits: usize) -> Option<(FlexInt, bool)> {
+        let radix = base.radix() as i128;
+        let num = digits.iter().try_fold(0_i128, |acc, d| {
+            if (*d as i128) < radix {
+                acc.checked_mul(radix)?.checked_add(*d as i128)
+            } else {
+                None
+            }
+        });
+        let Some(num) = num else {
             // To play it safe, treat parse errors as constant overflow
             // (otherwise, ludicrously large numbers may overflow)
             return Some((FlexInt::new(1), true));
         };
+
+        // If the limit doesn't fit in an i128, then nothing which was parsed can reach it
         let overflow = if signed {
-            num >= 2_i128.pow(bits as u32 - 1) || num < -1 * 2_i128.pow(bits as u32 - 1)
+            match 2_i128.checked_pow(bits as u32 - 1) {
+                Some(limit) => if negative { num > limit } else { num >= limit },
+                None => false,
+            }
         } else {
-            num >= 2_i128.pow(bits as u32)
+            matches!(2_i128.checked_pow(bits as u32), Some(limit) if num >= limit)
         };
         Some((FlexInt::new(1), overflow))
     }
 }
+
+/// How far a scan through the tokens of an expression has got, which is everything that determines
+/// how the rest of the tokens are parsed.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+struct ScanState {
+    /// Whether the next token should be an operator, rather than an operand.
+    expect_operator: bool,
+
+    /// How many parentheses are open.
+    depth: usize,
+
+    /// How many unary negations immediately precede the next token.
+    negations: usize,
+}
+
+#[derive(Clone, Copy)]
+struct ScannedToken {
+    /// The state before this token.
+    before: ScanState,
+
+    /// If this token is a constant which overflows, how many glyphs before it (its negations) are
+    /// also part of the warning.
+    overflow: Option<usize>,
+}
+
+/// Finds the constants which overflow in successive versions of an expression, such as after each
+/// keypress. This always gives the same spans as [Parser::parse_list] with a
+/// [ConstantOverflowChecker], but doesn't parse the whole expression each time.
+/// 
+/// The parser reports each literal or variable which overflows until it reaches an error. Whether
+/// a token does either only depends on the token itself, the one after it, and the [ScanState]
+/// before it, so the state before each token is kept. After an edit, scanning starts again just
+/// before the tokens which changed, and stops once the state before an unchanged token is the
+/// same as last time - from there on, the previous results still apply.
+#[derive(Default)]
+pub struct IncrementalOverflowChecker {
+    tokenizer: IncrementalTokenizer,
+
+    /// The configuration and variables which the tokens were scanned with. If either changes, the
+    /// whole expression must be scanned again.
+    config: Option<eval::Configuration>,
+    variables: VariableArray,
+
+    /// Each token which has been scanned, up to and including the first one which is an error.
+    scanned: Vec<ScannedToken>,
+
+    /// Whether the last of `scanned` is an error.
+    error: bool,
+}
+
+impl IncrementalOverflowChecker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the spans of the constants in the glyphs which overflow.
+    pub fn check(&mut self, glyphs: &[Glyph], variables: &VariableArray, config: eval::Configuration) -> Vec<GlyphSpan> {
+        if self.tokenizer.tokenize(glyphs).is_err() {
+            // The tokenizer starts again from nothing after an error, so this must too
+            self.scanned.clear();
+            self.error = false;
+            return vec![]
+        }
+        let tokens = self.tokenizer.tokens();
+        let mut edit = self.tokenizer.last_edit();
+
+        if self.config != Some(config) || self.variables != *variables {
+            self.config = Some(config);
+            self.variables = variables.clone();
+            self.scanned.clear();
+            self.error = false;
+            edit = TokenEdit { start: 0, removed: 0, inserted: tokens.len() };
+        } else if edit.removed == 0 && edit.inserted == 0 {
+            return Self::spans(&self.scanned, tokens);
+        }
+
+        // Whether a literal is negated depends on whether a power follows it, so the token before
+        // the edit must be scanned again too. But if the scan previously stopped at an error
+        // before that, it still does
+        let from = edit.start.saturating_sub(1);
+        if self.error && from >= self.scanned.len() {
+            return Self::spans(&self.scanned, tokens);
+        }
+        let previous = self.scanned.split_off(from);
+        let previous_error = core::mem::take(&mut self.error);
+
+        let parser = Parser::<ConstantOverflowChecker>::new(glyphs, variables, config);
+        let mut state = previous.first().map(|t| t.before).unwrap_or_default();
+        for index in from..tokens.len() {
+            // Once back at an unchanged token, which is in the same state as before, the rest of
+            // the tokens will be scanned exactly the same
+            if index >= edit.start + edit.inserted {
+                let previous_index = index + edit.removed - edit.inserted - from;
+                if previous.get(previous_index).is_some_and(|t| t.before == state) {
+                    self.scanned.extend_from_slice(&previous[previous_index..]);
+                    self.error = previous_error;
+                    break;
+                }
+            }
+
+            let Some((after, overflow)) = parser.scan_token(tokens, index, state) else {
+                self.scanned.push(ScannedToken { before: state, overflow: None });
+                self.error = true;
+                break;
+            };
+            self.scanned.push(ScannedToken { before: state, overflow });
+            state = after;
+        }
+
+        Self::spans(&self.scanned, tokens)
+    }
+
+    fn spans(scanned: &[ScannedToken], tokens: &[Token]) -> Vec<GlyphSpan> {
+        scanned.iter().zip(tokens)
+            .filter_map(|(scanned, token)| {
+                let negations = scanned.overflow?;
+                Some(GlyphSpan::new(token.span.start - negations, token.span.length + negations))
+            })
+            .collect()
+    }
+}
+
+impl<N: NumberParser> Parser<'_, '_, N> {
+    /// Checks a single token against the state of the expression before it, mirroring the
+    /// recursive parser. Returns the state after the token, and whether it's a constant which
+    /// overflows (like in [ScannedToken]), or `None` if the parser would stop with an error here.
+    fn scan_token(&self, tokens: &[Token], index: usize, state: ScanState) -> Option<(ScanState, Option<usize>)> {
+        let token = &tokens[index];
+        let after_operand = ScanState { expect_operator: true, depth: state.depth, negations: 0 };
+
+        if state.expect_operator {
+            return match token.kind {
+                TokenKind::Operator(op) if is_binary_operator(op) => {
+                    self.check_enabled(op).ok()?;
+                    Some((ScanState { expect_operator: false, depth: state.depth, negations: 0 }, None))
+                }
+                TokenKind::RightParen if state.depth > 0 =>
+                    Some((ScanState { depth: state.depth - 1, ..state }, None)),
+                TokenKind::Separator if state.depth == 0 =>
+                    Some((ScanState::default(), None)),
+                _ => None,
+            }
+        }
+
+        match &token.kind {
+            TokenKind::Operator(Glyph::Subtract) =>
+                Some((ScanState { negations: state.negations + 1, ..state }, None)),
+            TokenKind::LeftParen =>
+                Some((ScanState { expect_operator: false, depth: state.depth + 1, negations: 0 }, None)),
+            TokenKind::Answer => Some((after_operand, None)),
+
+            TokenKind::Variable(d) => {
+                let (_, overflow) = self.parse_variable(*d, token.span).ok()?;
+                Some((after_operand, overflow.then_some(0)))
+            }
+
+            TokenKind::Number { digits, base, exponent } => {
+                // A power binds more tightly than negation, so negations aren't applied to the
+                // literal if one follows it
+                let power_follows = matches!(
+                    tokens.get(index + 1).map(|t| &t.kind),
+                    Some(TokenKind::Operator(Glyph::Power)),
+                );
+                let negations = if power_follows { 0 } else { state.negations };
+                let negative = negations % 2 == 1;
+                let (_, overflow) = self.parse_number(digits.clone(), *base, *exponent, negative)?;
+                Some((after_operand, overflow.then_some(if negative { negations } else { 0 })))
+            }
+
+            _ => None,
+        }
+    }
+}
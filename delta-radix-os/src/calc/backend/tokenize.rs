@@ -0,0 +1,329 @@
+use alloc::{vec, vec::Vec};
+use delta_radix_hal::Glyph;
+
+use super::parse::{GlyphSpan, ParserError, ParserErrorKind};
+use crate::calc::frontend::Base;
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub struct Token {
+    pub span: GlyphSpan,
+    pub kind: TokenKind,
+}
+
+#[derive(PartialEq, Eq, Clone, Debug)]
+pub enum TokenKind {
+    /// A number literal, as a list of digit values. If there is no explicit base, then the number
+    /// is decimal.
+    ///
+    /// The number is not yet converted into a value, since this depends on the data type, and on
+    /// any unary negations found by the parser.
+    Number {
+        digits: Vec<u8>,
+        base: Option<Base>,
+        exponent: usize,
+    },
+
+    /// A binary operator, or [Glyph::Subtract] as unary negation.
+    Operator(Glyph),
+
+    LeftParen,
+    RightParen,
+
+    /// A reference to a variable with the given index.
+    Variable(u8),
+
+    /// A reference to the result of the previous evaluation.
+    Answer,
+
+    Separator,
+}
+
+/// Converts a sequence of glyphs into [Token]s, for consumption by the parser.
+pub struct Tokenizer<'g> {
+    glyphs: &'g [Glyph],
+    ptr: usize,
+}
+
+impl<'g> Tokenizer<'g> {
+    pub fn new(glyphs: &'g [Glyph]) -> Self {
+        Tokenizer { glyphs, ptr: 0 }
+    }
+
+    /// Creates a tokenizer which starts at a particular glyph, which must be the start of a token.
+    pub fn starting_at(glyphs: &'g [Glyph], ptr: usize) -> Self {
+        Tokenizer { glyphs, ptr }
+    }
+
+    pub fn tokenize(mut self) -> Result<Vec<Token>, ParserError> {
+        let mut tokens = vec![];
+        while let Some(token) = self.next_token()? {
+            tokens.push(token);
+        }
+
+        Ok(tokens)
+    }
+
+    /// Reads one token, or returns `None` if there are no glyphs left.
+    pub fn next_token(&mut self) -> Result<Option<Token>, ParserError> {
+        let Some(glyph) = self.here() else { return Ok(None) };
+        let start = self.ptr;
+        let kind = match glyph {
+            Glyph::Add | Glyph::Subtract | Glyph::Multiply | Glyph::Divide | Glyph::Modulo | Glyph::Power
+            | Glyph::ShiftLeft | Glyph::ShiftRight
+            | Glyph::BitAnd | Glyph::BitOr | Glyph::BitXor | Glyph::Align => {
+                self.advance();
+                TokenKind::Operator(glyph)
+            }
+
+            Glyph::LeftParen => { self.advance(); TokenKind::LeftParen }
+            Glyph::RightParen => { self.advance(); TokenKind::RightParen }
+            Glyph::Separator => { self.advance(); TokenKind::Separator }
+            Glyph::Answer => { self.advance(); TokenKind::Answer }
+
+            Glyph::Variable => {
+                self.advance();
+                let Some(Glyph::Digit(d)) = self.here() else {
+                    return Err(self.create_error(ParserErrorKind::InvalidVariable))
+                };
+                self.advance();
+                TokenKind::Variable(d)
+            }
+
+            Glyph::Digit(_) | Glyph::HexBase | Glyph::BinaryBase | Glyph::DecimalBase | Glyph::OctalBase =>
+                self.tokenize_number()?,
+
+            Glyph::Exponent =>
+                return Err(self.create_error(ParserErrorKind::UnexpectedGlyph(glyph))),
+        };
+
+        Ok(Some(Token { span: GlyphSpan::new(start, self.ptr - start), kind }))
+    }
+
+    /// Reads a number, with an optional base either before or after its digits.
+    /// 
+    /// A base directly after digits is always their suffix, never the prefix of another number, so
+    /// `110b` and `b110` are both binary 6. Since there's no implicit multiplication, anything
+    /// which would start another number straight after a suffix base is an error, rather than
+    /// being read as `110b*1` or `110*b1`.
+    fn tokenize_number(&mut self) -> Result<TokenKind, ParserError> {
+        let mut digits = vec![];
+        let mut base = None;
+
+        // Check for base at start
+        if let Some(b) = self.here().and_then(Base::from_glyph) {
+            self.advance();
+            base = Some(b);
+        };
+
+        // Gather digits
+        while let Some(Glyph::Digit(d)) = self.here() {
+            digits.push(d);
+            self.advance();
+        }
+
+        // Check for base at end
+        if let Some(b) = self.here().and_then(Base::from_glyph) {
+            if base.is_some() {
+                return Err(self.create_error(ParserErrorKind::DuplicateBase));
+            }
+            self.advance();
+            base = Some(b);
+
+            match self.here() {
+                Some(Glyph::Digit(_)) =>
+                    return Err(self.create_error(ParserErrorKind::InvalidNumber)),
+                Some(Glyph::HexBase | Glyph::BinaryBase | Glyph::DecimalBase | Glyph::OctalBase) =>
+                    return Err(self.create_error(ParserErrorKind::DuplicateBase)),
+                _ => (),
+            }
+        };
+
+        // Check for exponent, which is only valid for decimal numbers
+        let mut exponent = 0usize;
+        if let Some(Glyph::Exponent) = self.here() {
+            if base.unwrap_or(Base::Decimal) != Base::Decimal {
+                return Err(self.create_error(ParserErrorKind::InvalidNumber));
+            }
+            self.advance();
+
+            if let Some(Glyph::Subtract) = self.here() {
+                return Err(self.create_error(ParserErrorKind::NegativeExponent));
+            }
+
+            let mut exponent_digits = 0;
+            while let Some(Glyph::Digit(d)) = self.here() {
+                if d >= 10 {
+                    return Err(self.create_error(ParserErrorKind::InvalidNumber));
+                }
+                exponent = exponent.saturating_mul(10).saturating_add(d as usize);
+                exponent_digits += 1;
+                self.advance();
+            }
+            if exponent_digits == 0 {
+                return Err(self.create_error(ParserErrorKind::InvalidNumber));
+            }
+        }
+
+        Ok(TokenKind::Number { digits, base, exponent })
+    }
+
+    fn here(&self) -> Option<Glyph> {
+        self.glyphs.get(self.ptr).copied()
+    }
+
+    fn advance(&mut self) {
+        self.ptr += 1;
+    }
+
+    fn create_error(&self, kind: ParserErrorKind) -> ParserError {
+        ParserError::new(self.ptr, kind)
+    }
+}
+
+/// Finds unary negations which cancel each other out, like in `--5` or `2*--5`, returning the
+/// indices of their glyphs. Removing these glyphs doesn't change the value of the expression.
+/// 
+/// Within a run of consecutive negations, all are redundant if there are an even number, or all but
+/// one if there are an odd number.
+pub fn redundant_negations(tokens: &[Token]) -> Vec<usize> {
+    let mut redundant = vec![];
+    let mut run: Vec<usize> = vec![];
+    let mut previous: Option<&TokenKind> = None;
+
+    for token in tokens {
+        // A subtraction is a negation if there's no operand before it
+        let unary = matches!(token.kind, TokenKind::Operator(Glyph::Subtract))
+            && matches!(previous,
+                None | Some(TokenKind::Operator(_) | TokenKind::LeftParen | TokenKind::Separator));
+
+        if unary {
+            run.push(token.span.start());
+        } else {
+            let keep = run.len() % 2;
+            redundant.extend(&run[..(run.len() - keep)]);
+            run.clear();
+        }
+        previous = Some(&token.kind);
+    }
+
+    // A trailing run isn't valid anyway, so leave it for the user to see
+    redundant
+}
+
+/// Tokenizes successive versions of an expression, such as after each keypress, reusing the tokens
+/// of the previous version wherever they couldn't have been affected by the edit.
+/// 
+/// Only the region between the first and last changed glyphs is tokenized again, along with the
+/// tokens either side of it, which the edit may have extended or split. Finding that region
+/// compares the glyphs with the previous version, which is much cheaper than tokenizing them.
+#[derive(Default)]
+pub struct IncrementalTokenizer {
+    /// The glyphs which were last tokenized successfully.
+    glyphs: Vec<Glyph>,
+
+    /// The tokens produced from `glyphs`.
+    tokens: Vec<Token>,
+
+    /// The tokens which were replaced when `tokens` was last updated.
+    last_edit: TokenEdit,
+}
+
+/// A range of tokens which an [IncrementalTokenizer] replaced. Tokens outside of it are the same
+/// as in the previous version, although those after it may have moved.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct TokenEdit {
+    /// The index of the first token which was replaced.
+    pub start: usize,
+
+    /// How many tokens of the previous version were removed.
+    pub removed: usize,
+
+    /// How many tokens were inserted in their place.
+    pub inserted: usize,
+}
+
+impl IncrementalTokenizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tokenizes the glyphs, always producing the same result as [Tokenizer::tokenize].
+    pub fn tokenize(&mut self, glyphs: &[Glyph]) -> Result<&[Token], ParserError> {
+        if let Err(e) = self.retokenize(glyphs) {
+            // The tokens after the error weren't produced, so start afresh next time
+            self.glyphs.clear();
+            self.tokens.clear();
+            return Err(e);
+        }
+        Ok(&self.tokens)
+    }
+
+    /// The tokens produced by the last successful call to [IncrementalTokenizer::tokenize].
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// The tokens which changed during the last successful call to
+    /// [IncrementalTokenizer::tokenize]. If the call before that failed, this covers every token.
+    pub fn last_edit(&self) -> TokenEdit {
+        self.last_edit
+    }
+
+    fn retokenize(&mut self, glyphs: &[Glyph]) -> Result<(), ParserError> {
+        let old_len = self.glyphs.len();
+        let new_len = glyphs.len();
+
+        // Find the region which changed - everything outside it is the same in both versions
+        let prefix = self.glyphs.iter().zip(glyphs)
+            .take_while(|(a, b)| a == b)
+            .count();
+        if prefix == old_len && prefix == new_len {
+            self.last_edit = TokenEdit { start: self.tokens.len(), removed: 0, inserted: 0 };
+            return Ok(());
+        }
+        let suffix = self.glyphs[prefix..].iter().rev().zip(glyphs[prefix..].iter().rev())
+            .take_while(|(a, b)| a == b)
+            .count();
+
+        // Keep tokens which end before the change. One ending right at the change might be
+        // extended by it (like a number gaining a digit), so that must be tokenized again
+        let kept_prefix = self.tokens.iter()
+            .take_while(|t| t.span.indices().end < prefix)
+            .count();
+        let start = self.tokens[..kept_prefix].last().map(|t| t.span.indices().end).unwrap_or(0);
+
+        // Tokenize until reaching a token boundary within the unchanged suffix - from there, the
+        // old tokens are still correct, just shifted
+        let mut tokenizer = Tokenizer::starting_at(glyphs, start);
+        let mut new_tokens = vec![];
+        let kept_suffix = loop {
+            if tokenizer.ptr >= new_len - suffix {
+                let old_ptr = tokenizer.ptr + old_len - new_len;
+                if old_ptr == old_len {
+                    break self.tokens.len();
+                }
+                if let Ok(index) = self.tokens.binary_search_by_key(&old_ptr, |t| t.span.start()) {
+                    break index;
+                }
+            }
+
+            match tokenizer.next_token()? {
+                Some(token) => new_tokens.push(token),
+                None => break self.tokens.len(),
+            }
+        };
+
+        for token in &mut self.tokens[kept_suffix..] {
+            token.span = GlyphSpan::new(token.span.start() + new_len - old_len, token.span.length());
+        }
+        self.last_edit = TokenEdit {
+            start: kept_prefix,
+            removed: kept_suffix - kept_prefix,
+            inserted: new_tokens.len(),
+        };
+        self.tokens.splice(kept_prefix..kept_suffix, new_tokens);
+        self.glyphs.splice(prefix..(old_len - suffix), glyphs[prefix..(new_len - suffix)].iter().copied());
+
+        Ok(())
+    }
+}
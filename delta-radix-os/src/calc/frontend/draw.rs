@@ -1,9 +1,7 @@
-use alloc::{vec::Vec, string::{String, ToString}};
+use alloc::{vec, vec::Vec, string::{String, ToString}, format};
 use delta_radix_hal::{Hal, Display, DisplaySpecialCharacter, Glyph};
 
-use crate::calc::backend::parse::ConstantOverflowChecker;
-
-use super::{CalculatorApplication, ApplicationState};
+use super::{CalculatorApplication, ApplicationState, Base, settings::Setting};
 
 
 impl<'h, H: Hal> CalculatorApplication<'h, H> {
@@ -17,6 +15,8 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
             }
 
             ApplicationState::FormatMenu { ref bits_digits, bits_cursor_pos } => {
+                let preview = self.format_menu_preview(bits_digits);
+                let zero_pad = format!("*)Pad {:>3}", if self.settings.zero_pad { "On" } else { "Off" });
                 let display = self.hal.display_mut();
                 let bits_header = "Bits: ";
 
@@ -24,10 +24,23 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                 display.print_special(DisplaySpecialCharacter::CursorLeft);
                 display.print_special(DisplaySpecialCharacter::CursorRight);
 
+                // Whether hex and binary results are zero-padded, out of the way of the cursor
+                display.set_position((Self::WIDTH - zero_pad.len()) as u8, 0);
+                display.print_string(&zero_pad);
+
                 display.set_position(0, 1);
                 display.print_string(bits_header);
                 display.print_string(bits_digits);
 
+                // Show what the result would be with this format, if there's room after the bits
+                if let Some(preview) = preview {
+                    let used = bits_header.len() + bits_digits.len() + 1;
+                    if used + preview.len() <= Self::WIDTH {
+                        display.set_position((Self::WIDTH - preview.len()) as u8, 1);
+                        display.print_string(&preview);
+                    }
+                }
+
                 display.set_position(0, 2);
                 display.print_string("-) Signed  ");
                 if self.eval_config.data_type.signed {
@@ -64,8 +77,130 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
 
                 display.clear();
                 display.print_string("  1) Variables");
+                display.set_position(0, 1);
+                display.print_string("  2) Settings");
+                display.set_position(0, 2);
+                display.print_string("  3) Copy  4) Expr");
                 display.set_position(0, 3);
-                display.print_string("DEL) Bootloader");            
+                display.print_string("DEL) Boot  5) Hist");
+            }
+
+            ApplicationState::SettingsMenu { settings, page } => {
+                self.hal.display_mut().clear();
+
+                let start = page as usize * Setting::PER_PAGE;
+                for (i, setting) in settings.iter().enumerate().skip(start).take(Setting::PER_PAGE) {
+                    let value = self.setting_value(*setting);
+
+                    let display = self.hal.display_mut();
+                    display.set_position(0, (i - start) as u8);
+                    display.print_glyph(Glyph::Digit(i as u8));
+                    display.print_char(')');
+                    display.print_string(setting.name());
+                    display.set_position((Self::WIDTH - value.len()) as u8, (i - start) as u8);
+                    display.print_string(&value);
+                }
+            }
+
+            ApplicationState::History { index } => {
+                let display = self.hal.display_mut();
+
+                display.clear();
+                display.print_string("History");
+                let Some(entry) = self.history.iter().rev().nth(index) else {
+                    display.set_position(0, 2);
+                    display.print_string("Nothing yet!");
+                    return;
+                };
+
+                // Numbered so that the most recent is the highest, like a line number
+                let position = format!("{}/{}", self.history.len() - index, self.history.len());
+                display.set_position((Self::WIDTH - position.len()) as u8, 0);
+                display.print_string(&position);
+
+                display.set_position(0, 2);
+                for (i, glyph) in entry.glyphs.iter().enumerate().take(Self::WIDTH) {
+                    if i + 1 == Self::WIDTH && entry.glyphs.len() > Self::WIDTH {
+                        display.print_char('>');
+                    } else {
+                        display.print_glyph(*glyph);
+                    }
+                }
+
+                // Keep the least-significant end of a long result, which is usually more useful
+                let result = &entry.result;
+                if result.len() > Self::WIDTH {
+                    display.set_position(0, 3);
+                    display.print_char('<');
+                    display.print_string(&result[(result.len() - Self::WIDTH + 1)..]);
+                } else {
+                    display.set_position((Self::WIDTH - result.len()) as u8, 3);
+                    display.print_string(result);
+                }
+            }
+
+            ApplicationState::BitView { ref result, cursor } => {
+                let signed = self.signed_result.unwrap_or(self.eval_config.data_type.signed);
+                let value = if signed {
+                    result.to_signed_decimal_string()
+                } else {
+                    result.to_unsigned_decimal_string()
+                };
+                let display = self.hal.display_mut();
+
+                display.clear();
+                display.print_string(&format!("Bit {}", cursor));
+                let data_type = format!("{}{}", if signed { 'S' } else { 'U' }, result.size());
+                display.set_position((Self::WIDTH - data_type.len()) as u8, 0);
+                display.print_string(&data_type);
+
+                // Show the page of bits containing the cursor, with the least-significant on the
+                // right, like in a binary result
+                let page_start = cursor - cursor % Self::WIDTH;
+                let page_end = (page_start + Self::WIDTH).min(result.size());
+                for i in page_start..page_end {
+                    display.set_position((Self::WIDTH - 1 - (i - page_start)) as u8, 1);
+                    display.print_char(if result.bit(i) { '1' } else { '0' });
+                }
+                display.set_position((Self::WIDTH - 1 - (cursor - page_start)) as u8, 2);
+                display.print_char('^');
+
+                if value.len() > Self::WIDTH {
+                    display.set_position(0, 3);
+                    display.print_char('<');
+                    display.print_string(&value[(value.len() - Self::WIDTH + 1)..]);
+                } else {
+                    display.set_position((Self::WIDTH - value.len()) as u8, 3);
+                    display.print_string(&value);
+                }
+            }
+
+            ApplicationState::MultiBase => {
+                // Errors would look the same in every base, so only show successful results
+                let strs = if let Some(Ok(_)) = self.eval_result {
+                    [Base::Decimal, Base::Hexadecimal, Base::Binary].map(|base|
+                        self.eval_result_to_string_in_base(base).unwrap())
+                } else {
+                    let display = self.hal.display_mut();
+                    display.print_string("All bases");
+                    display.set_position(0, 2);
+                    display.print_string("No result!");
+                    return;
+                };
+
+                let display = self.hal.display_mut();
+                display.print_string("All bases");
+                for (i, str) in strs.iter().enumerate() {
+                    let row = i as u8 + 1;
+                    if str.len() > Self::WIDTH {
+                        display.set_position(0, row);
+                        display.print_string(&str[..(Self::WIDTH - 1)]);
+                        display.print_char('>');
+                    } else {
+                        display.set_position((Self::WIDTH - str.len()) as u8, row);
+                        display.print_string(str);
+                    }
+                }
             }
 
             ApplicationState::VariableView { page } => {
@@ -92,6 +227,12 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
     }
     
     pub fn draw_header(&mut self) {
+        if self.settings.compact_layout {
+            // There's no header - the status is drawn alongside the result instead
+            self.draw_result();
+            return;
+        }
+
         let has_overflow = self.eval_result_has_overflow();
 
         let disp = self.hal.display_mut();
@@ -130,29 +271,31 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
     pub fn draw_expression(&mut self) {
         self.adjust_scroll();
 
-        // Try to parse and get warning spans
-        let (parser, _) = self.parse::<ConstantOverflowChecker>();
-        let warning_indices = parser.constant_overflow_spans.iter()
-            .flat_map(|s| s.indices().collect::<Vec<_>>())
-            .collect::<Vec<_>>();
-
-        self.constant_overflows = !warning_indices.is_empty();
+        // The glyph which caused an error is highlighted, and marked like a constant overflow
+        let mut warning_indices = self.check_constant_overflows();
+        let error_index = self.eval_error_index();
+        warning_indices.extend(error_index);
+        let expression_row = self.expression_row();
         
         let disp = self.hal.display_mut();
 
         // Draw expression
-        disp.set_position(0, 2);
+        disp.set_position(0, expression_row);
         let mut chars_written = 0;
-        for glyph in self.glyphs.iter().skip(self.scroll_offset).take(Self::WIDTH) {
-            disp.print_glyph(*glyph);
+        for (i, glyph) in self.glyphs.iter().enumerate().skip(self.scroll_offset).take(Self::WIDTH) {
+            if Some(i) == error_index {
+                disp.print_char_inverse(glyph.char());
+            } else {
+                disp.print_glyph(*glyph);
+            }
             chars_written += 1;
         }
         for _ in chars_written..Self::WIDTH {
             disp.print_char(' ');
         }
 
-        // Draw cursor
-        disp.set_position(0, 1);
+        // Draw cursor, on the row above
+        disp.set_position(0, expression_row - 1);
         for i in self.scroll_offset..(self.scroll_offset + Self::WIDTH) {
             let warn = warning_indices.contains(&i);
             if i + 1 == self.cursor_pos {
@@ -179,6 +322,9 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
 
     pub fn draw_result(&mut self) {
         let has_overflow = self.eval_result_has_overflow();
+        let compact_status = if self.settings.compact_layout { Some(self.compact_status()) } else { None };
+        let opposite_signedness = self.opposite_signedness_string();
+        let separator = self.settings.thousands_separator;
 
         let disp = self.hal.display_mut();
 
@@ -194,33 +340,84 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
             return;
         }
 
+        if self.mask_source.is_some() && self.eval_result.is_none() {
+            disp.set_position(0, 3);
+            disp.print_string("MASK? ");
+            return;
+        }
+
+        if let Some(entry) = &self.operator_first {
+            disp.set_position(0, 3);
+            disp.print_glyph(entry.operator);
+            disp.print_string(if entry.first_operand.is_none() { " 1st? " } else { " 2nd? " });
+            return;
+        }
+
         // Briefly drop and re-borrow the display so we can call a method on `&self`
         drop(disp);
-        let mut str = self.eval_result_to_string()
-            .unwrap_or_else(|| str::repeat(" ", Self::WIDTH));
+        let strs = self.eval_result_to_strings()
+            .unwrap_or_else(|| vec![str::repeat(" ", Self::WIDTH)]);
         let disp = self.hal.display_mut();
 
+        // If there are several results, stack them in the rows below the header
+        if strs.len() > 1 {
+            Self::draw_header_marker(disp, compact_status.as_deref(), " MULTI ");
+
+            for y in 1..=3 {
+                disp.set_position(0, y);
+                disp.print_string(&str::repeat(" ", Self::WIDTH));
+            }
+
+            if strs.len() > 3 || strs.iter().any(|s| s.len() > Self::WIDTH) {
+                let message = "results too big :(";
+                disp.set_position((Self::WIDTH - message.len()) as u8, 3);
+                disp.print_string(message);
+                return;
+            }
+
+            for (i, str) in strs.iter().enumerate() {
+                disp.set_position((Self::WIDTH - str.len()) as u8, i as u8 + 1);
+                disp.print_string(str);
+            }
+            return;
+        }
+        let mut str = strs.into_iter().next().unwrap();
+
+        // The compact layout has no header, so has two rows for the result. The status goes in the
+        // top-left corner, and the result is right-aligned after it
+        if let Some(status) = &compact_status {
+            if str.len() <= Self::compact_result_width(status) {
+                let area = format!("{} {:>width$}", status, str, width = Self::compact_result_width(status));
+                for (i, line) in area.chars().collect::<Vec<_>>().chunks(Self::WIDTH).enumerate() {
+                    disp.set_position(0, i as u8 + 2);
+                    disp.print_string(&line.iter().collect::<String>());
+                }
+                Self::draw_opposite_signedness(disp, opposite_signedness.as_deref(), &str);
+                return;
+            }
+        }
+
         // Alright, how long is this result?
         // We can activate ***BIG MODE*** if it's longer than a line
         if str.len() <= Self::WIDTH {
             // Cool, it fits on a line! This should be the average case
-            disp.set_position((Self::WIDTH - str.len()) as u8, 3);
-            disp.print_string(&str);
+            // (Pad it to fill the whole line, in case there's a prompt to overwrite)
+            disp.set_position(0, 3);
+            disp.print_string(&format!("{:>width$}", str, width = Self::WIDTH));
+            Self::draw_opposite_signedness(disp, opposite_signedness.as_deref(), &str);
         } else if str.len() <= Self::WIDTH * 3 {
             // It fits on three lines... we can leave just the header
             // (Add a marker to the header to say we did this, though)
-            disp.set_position(7, 0);
-            disp.print_string(" BIG ");
-            disp.set_position(0, 1);
+            Self::draw_header_marker(disp, compact_status.as_deref(), " BIG ");
 
             for y in 1..=3 {
                 disp.set_position(0, y);
                 disp.print_string(&str::repeat(" ", Self::WIDTH));    
             }
 
-            for (i, line) in str.chars().collect::<Vec<_>>().chunks(20).enumerate() {
+            for (i, line) in Self::wrap_result(&str, separator, 3).iter().enumerate() {
                 disp.set_position(0, i as u8 + 1);
-                disp.print_string(&line.iter().collect::<String>());
+                disp.print_string(line);
             }
         } else if !has_overflow && str.len() <= Self::WIDTH * 4 {
             // If there's no overflow, we can occupy the entire screen with the result
@@ -230,9 +427,9 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
             }
 
             disp.set_position(0, 0);
-            for (i, line) in str.chars().collect::<Vec<_>>().chunks(Self::WIDTH).enumerate() {
+            for (i, line) in Self::wrap_result(&str, separator, 4).iter().enumerate() {
                 disp.set_position(0, i as u8);
-                disp.print_string(&line.iter().collect::<String>());
+                disp.print_string(line);
             }
         } else if has_overflow && str.len() <= Self::WIDTH * 4 - 5 {
             // If there's overflow, we can occupy almost the entire screen but must account for an
@@ -243,9 +440,9 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
             }
 
             str = ["OVER ".to_string(), str.clone()].join("");
-            disp.set_position(0, 0);
-            for line in str.chars().collect::<Vec<_>>().chunks(Self::WIDTH) {
-                disp.print_string(&line.iter().collect::<String>());
+            for (i, line) in Self::wrap_result(&str, separator, 4).iter().enumerate() {
+                disp.set_position(0, i as u8);
+                disp.print_string(line);
             }
         } else {
             // Nothing will fit!
@@ -255,6 +452,105 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
         }
     }
 
+    /// Shows that the result has taken over the rows below the header. In the compact layout,
+    /// where there is no header, the top row is replaced with the status instead.
+    fn draw_header_marker(disp: &mut impl Display, compact_status: Option<&str>, marker: &str) {
+        match compact_status {
+            Some(status) => {
+                Self::clear_row(disp, 0);
+                disp.set_position(0, 0);
+                disp.print_string(status);
+                disp.print_string(marker);
+            }
+            None => {
+                disp.set_position(7, 0);
+                disp.print_string(marker);
+            }
+        }
+    }
+
+    /// Draws the result with the opposite signedness in the bottom-left corner, if there's room next
+    /// to the result.
+    fn draw_opposite_signedness(disp: &mut impl Display, opposite: Option<&str>, result: &str) {
+        let Some(opposite) = opposite else { return };
+        if opposite.len() + 1 + result.len() <= Self::WIDTH {
+            disp.set_position(0, 3);
+            disp.print_string(opposite);
+        }
+    }
+
+    /// Whether the result is too big for the rows below the expression, so is drawn over it.
+    pub(super) fn result_covers_expression(&self) -> bool {
+        let Some(strs) = self.eval_result_to_strings() else { return false };
+        let width = if self.settings.compact_layout {
+            Self::compact_result_width(&self.compact_status())
+        } else {
+            Self::WIDTH
+        };
+        strs.len() > 1 || strs[0].len() > width
+    }
+
+    /// Splits a result into lines of the display's width, to fill at most `rows` rows.
+    /// 
+    /// If the digits are grouped, each line is broken after a separator where possible, so that
+    /// groups aren't split across lines. A separator which would start a line is dropped instead,
+    /// so a group ending right at the edge of the display isn't moved onto the next line. If that
+    /// takes too many rows, the result is just cut into equal lines instead.
+    fn wrap_result(str: &str, separator: Option<char>, rows: usize) -> Vec<String> {
+        let chars = str.chars().collect::<Vec<_>>();
+        let plain = || chars.chunks(Self::WIDTH).map(|line| line.iter().collect()).collect();
+        let Some(separator) = separator else { return plain() };
+
+        let mut lines = vec![];
+        let mut rest = &chars[..];
+        while rest.len() > Self::WIDTH {
+            if rest[Self::WIDTH] == separator {
+                lines.push(rest[..Self::WIDTH].iter().collect());
+                rest = &rest[(Self::WIDTH + 1)..];
+                continue;
+            }
+
+            let split = rest[..Self::WIDTH].iter()
+                .rposition(|c| *c == separator)
+                .map(|i| i + 1)
+                .unwrap_or(Self::WIDTH);
+            lines.push(rest[..split].iter().collect());
+            rest = &rest[split..];
+        }
+        lines.push(rest.iter().collect());
+
+        if lines.len() <= rows { lines } else { plain() }
+    }
+
+    /// The number of characters available for the result in the compact layout, next to the
+    /// status.
+    fn compact_result_width(status: &str) -> usize {
+        Self::WIDTH * 2 - status.len() - 1
+    }
+
+    /// The row which the expression is drawn on. The cursor is drawn on the row above.
+    fn expression_row(&self) -> u8 {
+        if self.settings.compact_layout { 1 } else { 2 }
+    }
+
+    /// A short summary of the header, for the compact layout - the data type, then a `^` if shift
+    /// is active, then whether the result overflowed.
+    fn compact_status(&self) -> String {
+        let mut status = self.eval_config.data_type.concise_name();
+        if let Some(sign) = self.signed_result {
+            status.push('>');
+            status.push(if sign { 'S' } else { 'U' });
+        }
+        // Always take up a space for the shift marker, so that toggling it doesn't change the width
+        status.push(if self.input_shifted { '^' } else { ' ' });
+        if self.eval_result_has_overflow() {
+            status.push_str("OVER");
+        }
+        status
+    }
+
     fn clear_row(disp: &mut impl Display, y: u8) {
+        disp.set_position(0, y);
+        disp.print_string(&str::repeat(" ", Self::WIDTH));
     }
 }
\ No newline at end of file
@@ -1,7 +1,8 @@
-use alloc::string::ToString;
+use alloc::string::{String, ToString};
 use delta_radix_hal::{Hal, Key, Glyph};
+use flex_int::FlexInt;
 
-use super::{CalculatorApplication, ApplicationState, Base};
+use super::{CalculatorApplication, ApplicationState, Base, UndoSnapshot, settings::Setting};
 
 impl<'h, H: Hal> CalculatorApplication<'h, H> {
     pub async fn process_input_and_redraw(&mut self, key: Key) {
@@ -9,6 +10,38 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
             panic!("debug terminate");
         }
 
+        if key == Key::Sleep {
+            // Do not redraw - the HAL is expected to deal with this, by clearing the display.
+            // Instead, redraw everything when woken up by the next key
+            self.state = ApplicationState::Normal;
+            self.clear_all(false);
+            self.asleep = true;
+            self.repeat_key = None;
+            return;
+        }
+
+        // Only keys typed straight into the expression repeat. Repeating the key after Shift would
+        // enter it unshifted, and in menus, repeating a key would rarely be what was wanted
+        let repeatable = self.state == ApplicationState::Normal && !self.input_shifted;
+        let key = if key == Key::Repeat {
+            match self.repeat_key {
+                Some(held) if repeatable => held,
+                _ => return,
+            }
+        } else {
+            self.repeat_key = if repeatable && key.auto_repeats() { Some(key) } else { None };
+            key
+        };
+        if self.asleep {
+            self.asleep = false;
+            self.draw_full();
+        }
+
+        // Remember the expression, so that any edit made by this key can be undone
+        let before = UndoSnapshot { glyphs: self.glyphs.clone(), cursor_pos: self.cursor_pos };
+        let is_undo_or_redo = self.state == ApplicationState::Normal && self.input_shifted
+            && matches!(key, Key::Left | Key::Add);
+
         match self.state {
             ApplicationState::Normal =>
                 if self.input_shifted {
@@ -24,20 +57,109 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
 
                         Key::Digit(0) => {
                             self.input_shifted = false;
-                            
-                            // Insert two characters and move between them
-                            self.glyphs.insert(self.cursor_pos, Glyph::LeftParen);
-                            self.cursor_pos += 1;
-                            self.glyphs.insert(self.cursor_pos, Glyph::RightParen);
-                            self.draw_expression();
-                            self.clear_evaluation(true);
+                            self.insert_parens_and_redraw();
                         }
 
+                        Key::Left => {
+                            self.input_shifted = false;
+                            self.undo_and_redraw();
+                        }
+                        Key::Add => {
+                            self.input_shifted = false;
+                            self.redo_and_redraw();
+                        }
                         Key::Right => {
                             self.input_shifted = false;
                             self.insert_and_redraw(Glyph::Align);
                         }
 
+                        // Like the arrows on a numeric keypad
+                        Key::Digit(4) => {
+                            self.input_shifted = false;
+                            self.jump_to_operator_and_redraw(false);
+                        }
+                        Key::Digit(6) => {
+                            self.input_shifted = false;
+                            self.jump_to_operator_and_redraw(true);
+                        }
+
+                        Key::Exe => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::Separator);
+                        }
+
+                        Key::Multiply => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::Exponent);
+                        }
+
+                        // For keypads without a dedicated modulo key
+                        Key::Divide => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::Modulo);
+                        }
+
+                        // Like `**` for powers in some languages
+                        Key::Digit(8) => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::Power);
+                        }
+
+                        // Either side of Shift > 8
+                        Key::Digit(7) => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::ShiftLeft);
+                        }
+                        Key::Digit(9) => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::ShiftRight);
+                        }
+
+                        Key::Digit(5) => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::Answer);
+                        }
+
+                        // B for bits. Without a result, start from zero to build a number up
+                        Key::Digit(0xB) => {
+                            self.input_shifted = false;
+                            let result = match self.current_result() {
+                                Some(Ok(result)) => result.result.clone(),
+                                _ => FlexInt::new(self.eval_config.data_type.bits),
+                            };
+                            self.state = ApplicationState::BitView { result, cursor: 0 };
+                            self.draw_full();
+                        }
+
+                        // There's no key for octal on the device
+                        Key::Digit(0xA) => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw(Glyph::OctalBase);
+                        }
+
+                        Key::Digit(d @ 1..=3) => {
+                            self.input_shifted = false;
+                            self.insert_and_redraw([Glyph::BitAnd, Glyph::BitOr, Glyph::BitXor][d as usize - 1]);
+                        }
+
+                        Key::Subtract => {
+                            self.input_shifted = false;
+                            self.negate_result();
+                            self.draw_header();
+                            self.draw_result();
+                        }
+
+                        Key::BinaryBase => {
+                            self.input_shifted = false;
+                            self.set_output_format_and_redraw(self.output_format.next());
+                        }
+
+                        Key::HexBase => {
+                            self.input_shifted = false;
+                            self.begin_mask();
+                            self.draw_full();
+                        }
+
                         Key::Variable => {
                             self.input_shifted = false;
                             if let Some(Ok(_)) = self.eval_result {
@@ -65,30 +187,82 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                     }
                 } else {
                     match key {
-                        Key::Digit(d) => self.insert_and_redraw(Glyph::Digit(d)),
-                        Key::HexBase => self.insert_and_redraw(Glyph::HexBase),
+                        Key::Digit(d) => {
+                            // A-F are only meaningful in hexadecimal, so if one of these starts a
+                            // new number, make it hexadecimal for the user
+                            if d >= 10 && self.cursor_at_fresh_number() {
+                                self.glyphs.insert(self.cursor_pos, Glyph::HexBase);
+                                self.cursor_pos += 1;
+                            }
+                            self.insert_and_redraw(Glyph::Digit(d))
+                        }
+                        Key::HexBase => {
+                            // If this is a suffix for a number which already has a hex prefix
+                            // (probably inserted automatically when typing A-F), move the base to
+                            // the end instead
+                            if let Some(prefix_pos) = self.hex_prefix_before_cursor() {
+                                self.glyphs.remove(prefix_pos);
+                                self.cursor_pos -= 1;
+                            }
+                            self.insert_and_redraw(Glyph::HexBase)
+                        }
                         Key::BinaryBase => self.insert_and_redraw(Glyph::BinaryBase),
+                        Key::OctalBase => self.insert_and_redraw(Glyph::OctalBase),
             
+                        Key::Add | Key::Subtract | Key::Multiply | Key::Divide | Key::Modulo | Key::Power
+                            | Key::ShiftLeft | Key::ShiftRight
+                            | Key::BitAnd | Key::BitOr | Key::BitXor if self.can_begin_operator_first() => {
+                            let operator = match key {
+                                Key::Add => Glyph::Add,
+                                Key::Subtract => Glyph::Subtract,
+                                Key::Multiply => Glyph::Multiply,
+                                Key::Divide => Glyph::Divide,
+                                Key::Modulo => Glyph::Modulo,
+                                Key::Power => Glyph::Power,
+                                Key::ShiftLeft => Glyph::ShiftLeft,
+                                Key::ShiftRight => Glyph::ShiftRight,
+                                Key::BitAnd => Glyph::BitAnd,
+                                Key::BitOr => Glyph::BitOr,
+                                Key::BitXor => Glyph::BitXor,
+                                _ => unreachable!(),
+                            };
+                            self.begin_operator_first(operator);
+                        }
                         Key::Add => self.insert_and_redraw(Glyph::Add),
                         Key::Subtract => self.insert_and_redraw(Glyph::Subtract),
                         Key::Multiply => self.insert_and_redraw(Glyph::Multiply),
                         Key::Divide => self.insert_and_redraw(Glyph::Divide),
+                        Key::Modulo => self.insert_and_redraw(Glyph::Modulo),
+                        Key::Power => self.insert_and_redraw(Glyph::Power),
+                        Key::ShiftLeft => self.insert_and_redraw(Glyph::ShiftLeft),
+                        Key::ShiftRight => self.insert_and_redraw(Glyph::ShiftRight),
+                        Key::BitAnd => self.insert_and_redraw(Glyph::BitAnd),
+                        Key::BitOr => self.insert_and_redraw(Glyph::BitOr),
+                        Key::BitXor => self.insert_and_redraw(Glyph::BitXor),
 
                         // TODO: nicer insertion mechanism, and treat as one token?
                         Key::Variable => self.insert_and_redraw(Glyph::Variable),
+                        Key::Answer => self.insert_and_redraw(Glyph::Answer),
+
+                        Key::LeftParen => {
+                            if self.settings.auto_close_parens {
+                                self.insert_parens_and_redraw();
+                            } else {
+                                self.insert_and_redraw(Glyph::LeftParen);
+                            }
+                        }
+                        Key::RightParen => self.insert_and_redraw(Glyph::RightParen),
             
                         Key::Left => {
                             if self.cursor_pos > 0 {
                                 self.cursor_pos -= 1;
-                                self.draw_expression();
-                                self.clear_evaluation(true);
+                                self.cursor_moved_and_redraw();
                             }
                         },
                         Key::Right => {
                             if self.cursor_pos < self.glyphs.len() {
                                 self.cursor_pos += 1;
-                                self.draw_expression();
-                                self.clear_evaluation(true);
+                                self.cursor_moved_and_redraw();
                             }
                         }
                         Key::Delete => {
@@ -100,9 +274,21 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                             }
                         },
                         Key::Exe => {
-                            self.evaluate();
-                            self.draw_header();
-                            self.draw_result();
+                            // When entering operator-first, only evaluate once both operands are in
+                            if self.enter_operand_and_redraw() {
+                                if self.settings.collapse_negations {
+                                    self.collapse_negations();
+                                    self.draw_expression();
+                                }
+                                self.evaluate();
+                                self.push_history();
+                                self.draw_header();
+                                if self.eval_error_index().is_some() {
+                                    // Highlight where the error is
+                                    self.draw_expression();
+                                }
+                                self.draw_result();
+                            }
                         }
 
                         Key::FormatSelect => {
@@ -115,11 +301,6 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                             self.draw_header();
                         }
 
-                        Key::Sleep => {
-                            // Do not redraw - the HAL is expected to deal with this
-                            self.clear_all(false);
-                        }
-
                         Key::Menu => {
                             let bits_digits = self.eval_config.data_type.bits.to_string();
                             self.state = ApplicationState::FormatMenu {
@@ -129,21 +310,28 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                             self.draw_full();
                         }
                         
-                        Key::DebugTerminate => (),
+                        // Handled before looking at the state
+                        Key::DebugTerminate | Key::Sleep | Key::Repeat => (),
                     }
                 },
             
             ApplicationState::OutputBaseSelect => match key {
                 Key::HexBase => self.set_output_format_and_redraw(Base::Hexadecimal),
                 Key::BinaryBase => self.set_output_format_and_redraw(Base::Binary),
+                Key::OctalBase | Key::Digit(8) => self.set_output_format_and_redraw(Base::Octal),
                 Key::FormatSelect => self.set_output_format_and_redraw(Base::Decimal),
 
+                Key::Exe => {
+                    self.state = ApplicationState::MultiBase;
+                    self.draw_full();
+                }
+
                 _ => (),
             }
 
             ApplicationState::VariableSet => match key {
                 Key::Digit(d) => {
-                    self.variables[d as usize] = Glyph::from_string(&self.eval_result_to_string().unwrap()).unwrap();
+                    self.variables[d as usize] = self.eval_result_to_glyphs().unwrap();
 
                     self.state = ApplicationState::Normal;
                     self.draw_full();
@@ -192,18 +380,22 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                     self.eval_config.data_type.signed = true;
                     self.draw_full();
                 }
+                Key::Multiply => {
+                    self.settings.zero_pad = !self.settings.zero_pad;
+                    self.draw_full();
+                }
 
                 Key::FormatSelect | Key::Menu | Key::Exe => {
                     // Apply bits evaluation settings
-                    if let Ok(mut bits) = bits_digits.parse() {
-                        // Minimum supported number of bits
-                        if bits < 3 {
-                            bits = 3;
-                        }
-
+                    if let Some(bits) = Self::parse_format_bits(bits_digits) {
                         self.eval_config.data_type.bits = bits;
                     }
 
+                    // The data type may have changed, so constants which overflowed before might
+                    // not now (or vice versa), and the answer might not fit
+                    self.check_constant_overflows();
+                    self.check_answer_data_type();
+
                     self.state = ApplicationState::Normal;
                     self.clear_evaluation(true);
                     self.draw_full();
@@ -215,14 +407,17 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
             ApplicationState::OutputSignedMenu => match key {
                 Key::Delete => {
                     self.signed_result = None;
+                    self.store_display_prefs();
                     self.draw_full();
                 }
                 Key::Add => {
                     self.signed_result = Some(false);
+                    self.store_display_prefs();
                     self.draw_full();
                 }
                 Key::Subtract => {
                     self.signed_result = Some(true);
+                    self.store_display_prefs();
                     self.draw_full();
                 }
 
@@ -240,6 +435,31 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                     self.state = ApplicationState::VariableView { page: 0 };
                     self.draw_full();
                 }
+                Key::Digit(2) => {
+                    self.state = ApplicationState::SettingsMenu { settings: Setting::ALL, page: 0 };
+                    self.draw_full();
+                }
+                Key::Digit(3) => {
+                    // Only copy successful results - there's no use in copying an error message
+                    if let Some(Ok(result)) = self.current_result() {
+                        let result = self.evaluation_result_to_string(result);
+                        self.hal.copy_to_clipboard(&result);
+                    }
+                    self.state = ApplicationState::Normal;
+                    self.draw_full();
+                }
+                Key::Digit(4) => {
+                    if !self.glyphs.is_empty() {
+                        let expression = self.glyphs.iter().map(|g| g.char()).collect::<String>();
+                        self.hal.copy_to_clipboard(&expression);
+                    }
+                    self.state = ApplicationState::Normal;
+                    self.draw_full();
+                }
+                Key::Digit(5) => {
+                    self.state = ApplicationState::History { index: 0 };
+                    self.draw_full();
+                }
                 Key::Delete => self.hal.enter_bootloader().await,
                 Key::Menu => {
                     self.state = ApplicationState::Normal;
@@ -249,6 +469,102 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                 _ => (),
             }
 
+            ApplicationState::SettingsMenu { settings, ref mut page } => match key {
+                Key::Digit(d) if (d as usize) < settings.len() => {
+                    match settings[d as usize] {
+                        Setting::Operators =>
+                            self.state = ApplicationState::SettingsMenu { settings: Setting::OPERATORS, page: 0 },
+                        setting => self.cycle_setting(setting),
+                    }
+                    self.draw_full();
+                }
+
+                Key::Left if *page > 0 => {
+                    *page -= 1;
+                    self.draw_full();
+                }
+                Key::Right if ((*page as usize + 1) * Setting::PER_PAGE) < settings.len() => {
+                    *page += 1;
+                    self.draw_full();
+                }
+
+                // Leaving a sub-menu goes back to the page of the main list which opened it
+                Key::FormatSelect | Key::Menu | Key::Exe if settings != Setting::ALL => {
+                    let index = Setting::ALL.iter().position(|s| *s == Setting::Operators).unwrap();
+                    self.state = ApplicationState::SettingsMenu {
+                        settings: Setting::ALL,
+                        page: (index / Setting::PER_PAGE) as u8,
+                    };
+                    self.draw_full();
+                }
+                Key::FormatSelect | Key::Menu | Key::Exe => {
+                    self.state = ApplicationState::Normal;
+                    self.clear_evaluation(true);
+                    self.draw_full();
+                }
+
+                _ => (),
+            }
+
+            ApplicationState::History { ref mut index } => match key {
+                // Left goes back in time, like undo
+                Key::Left if *index + 1 < self.history.len() => {
+                    *index += 1;
+                    self.draw_full();
+                }
+                Key::Right if *index > 0 => {
+                    *index -= 1;
+                    self.draw_full();
+                }
+
+                Key::Exe => {
+                    let index = *index;
+                    self.load_history_entry(index);
+                    self.state = ApplicationState::Normal;
+                    self.draw_full();
+                }
+                Key::FormatSelect | Key::Menu => {
+                    self.state = ApplicationState::Normal;
+                    self.draw_full();
+                }
+
+                _ => (),
+            }
+
+            ApplicationState::BitView { ref mut result, ref mut cursor } => match key {
+                // Left moves towards the most-significant bit, which is drawn on the left
+                Key::Left if *cursor + 1 < result.size() => {
+                    *cursor += 1;
+                    self.draw_full();
+                }
+                Key::Right if *cursor > 0 => {
+                    *cursor -= 1;
+                    self.draw_full();
+                }
+
+                Key::Digit(_) => {
+                    let bit = result.bit_mut(*cursor);
+                    *bit = !*bit;
+                    self.draw_full();
+                }
+
+                Key::FormatSelect | Key::Menu | Key::Exe => {
+                    self.state = ApplicationState::Normal;
+                    self.draw_full();
+                }
+
+                _ => (),
+            }
+
+            ApplicationState::MultiBase => match key {
+                Key::FormatSelect | Key::Menu | Key::Exe => {
+                    self.state = ApplicationState::Normal;
+                    self.draw_full();
+                }
+
+                _ => (),
+            }
+
             ApplicationState::VariableView { ref mut page } => match key {
                 Key::Left if *page > 0 => {
                     *page -= 1;
@@ -268,6 +584,11 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                 _ => (),
             }
         }
-        
+
+        // A new edit replaces whatever was undone, so it can no longer be redone
+        if !is_undo_or_redo && before.glyphs != self.glyphs {
+            self.push_undo_snapshot(before);
+            self.redo_stack.clear();
+        }
     }
 }
@@ -1,11 +1,16 @@
-use alloc::{vec::Vec, vec, string::{ToString, String}, format};
-use delta_radix_hal::{Hal, Display, Keypad, Key, DisplaySpecialCharacter, Glyph};
+use alloc::{vec::Vec, vec, string::{ToString, String}, format, collections::VecDeque};
+use delta_radix_hal::{Hal, Display, Keypad, Key, Glyph};
 use flex_int::FlexInt;
 
-use crate::calc::backend::{eval::{EvaluationResult, Configuration, DataType, evaluate}, parse::{Parser, Node, ParserError, NumberParser, ConstantOverflowChecker}};
+use crate::calc::backend::{eval::{EvaluationResult, Operation, Overflow, Configuration, DataType, OverflowBehaviour, DivideByZero, OperatorGroups, evaluate, estimate_memory_usage, assign_checked}, tokenize::{Tokenizer, redundant_negations}, parse::{Parser, Node, GlyphSpan, ParserError, ParserErrorKind, NumberParser, IncrementalOverflowChecker}};
 
 mod draw;
 mod input;
+mod settings;
+pub use settings::{Settings, DisplayPrefs};
+use settings::Setting;
+mod operator_first;
+use operator_first::OperatorFirstEntry;
 
 #[derive(PartialEq, Eq, Clone, Debug)]
 enum ApplicationState {
@@ -21,6 +26,23 @@ enum ApplicationState {
         page: u8,
     },
     MainMenu,
+    SettingsMenu {
+        /// The settings being listed - either all of them, or those in a sub-menu.
+        settings: &'static [Setting],
+        page: u8,
+    },
+    History {
+        /// How many calculations back the shown entry is, where 0 is the most recent.
+        index: usize,
+    },
+    /// Shows the current result in decimal, hexadecimal and binary at once.
+    MultiBase,
+    /// Shows the individual bits of a number, which can be toggled to see how its value changes.
+    BitView {
+        result: FlexInt,
+        /// The index of the selected bit, where 0 is the least-significant.
+        cursor: usize,
+    },
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -28,6 +50,7 @@ pub enum Base {
     Decimal,
     Hexadecimal,
     Binary,
+    Octal,
 }
 
 impl Base {
@@ -36,15 +59,28 @@ impl Base {
             Glyph::HexBase => Some(Base::Hexadecimal),
             Glyph::BinaryBase => Some(Base::Binary),
             Glyph::DecimalBase => Some(Base::Decimal),
+            Glyph::OctalBase => Some(Base::Octal),
             _ => None,
         }
     }
     
+    /// The base after this one when cycling through them: decimal, then hexadecimal, then binary.
+    /// Octal is rarely needed, so it isn't part of the cycle, but cycling from it goes back to
+    /// decimal.
+    pub fn next(&self) -> Self {
+        match self {
+            Base::Decimal => Base::Hexadecimal,
+            Base::Hexadecimal => Base::Binary,
+            Base::Binary | Base::Octal => Base::Decimal,
+        }
+    }
+
     pub fn radix(&self) -> u32 {
         match self {
             Base::Decimal => 10,
             Base::Hexadecimal => 16,
             Base::Binary => 2,
+            Base::Octal => 8,
         }
     }
 }
@@ -53,6 +89,40 @@ impl Base {
 // across changes in data type
 pub type VariableArray = [Vec<Glyph>; 16];
 
+/// The state of the expression before an edit, which can be restored by undoing it. Undoing also
+/// takes one of these, so that the edit can be redone.
+struct UndoSnapshot {
+    glyphs: Vec<Glyph>,
+    cursor_pos: usize,
+}
+
+/// A calculation which was evaluated, so that it can be looked back at later.
+struct HistoryEntry {
+    glyphs: Vec<Glyph>,
+
+    /// The result as it was displayed, which may have been in a different base or data type to
+    /// the current one.
+    result: String,
+}
+
+/// A result remembered to calculate an XOR mask from.
+struct MaskSource {
+    value: FlexInt,
+
+    /// The data type which the value was calculated with. This may have changed by the time the
+    /// mask is calculated.
+    data_type: DataType,
+}
+
+/// The result of the most recent successful evaluation, which [Glyph::Answer] refers to.
+struct Answer {
+    result: EvaluationResult,
+
+    /// The data type which the result was calculated with. The result's bits only make sense for
+    /// this data type, so the answer is forgotten if it changes.
+    data_type: DataType,
+}
+
 pub struct CalculatorApplication<'h, H: Hal> {
     hal: &'h mut H,
 
@@ -61,6 +131,7 @@ pub struct CalculatorApplication<'h, H: Hal> {
 
     output_format: Base,
     signed_result: Option<bool>,
+    settings: Settings,
 
     glyphs: Vec<Glyph>,
     cursor_pos: usize,
@@ -68,20 +139,65 @@ pub struct CalculatorApplication<'h, H: Hal> {
     scroll_offset: usize,
 
     eval_config: Configuration,
-    eval_result: Option<Result<EvaluationResult, ParserError>>,
+    eval_result: Option<Result<Vec<EvaluationResult>, ParserError>>,
+
+    /// If set, the next evaluation shows the XOR mask which transforms this value into the result.
+    mask_source: Option<MaskSource>,
+
+    /// Set while a calculation is being entered operator-first.
+    operator_first: Option<OperatorFirstEntry>,
 
     variables: VariableArray,
+
+    /// Kept when the expression is cleared, so that it can be used in the next calculation.
+    answer: Option<Answer>,
+
+    /// The expression before each recent edit, most recent last.
+    undo_stack: VecDeque<UndoSnapshot>,
+
+    /// The expression before each undo since the last edit, most recent last.
+    redo_stack: Vec<UndoSnapshot>,
+
+    /// Recently evaluated calculations, most recent last.
+    history: VecDeque<HistoryEntry>,
+
+    /// Used when checking for constant overflows, to avoid parsing the whole expression again after
+    /// each edit.
+    overflow_checker: IncrementalOverflowChecker,
+
+    /// Set after a [Key::Sleep], so that the display is redrawn upon waking.
+    asleep: bool,
+
+    /// The key which a [Key::Repeat] enters again, if the last key pressed can be repeated.
+    repeat_key: Option<Key>,
 }
 
 impl<'h, H: Hal> CalculatorApplication<'h, H> {
     pub const WIDTH: usize = 20;
 
+    /// The number of edits which can be undone. Each keeps a copy of the expression, so this is
+    /// limited to save memory.
+    pub const MAX_UNDO_DEPTH: usize = 32;
+
+    /// The number of calculations kept in the history.
+    pub const MAX_HISTORY_LENGTH: usize = 16;
+
+    /// Creates the application, restoring any [DisplayPrefs] previously saved by the HAL.
     pub fn new(hal: &'h mut H) -> Self {
+        let prefs = hal.load(DisplayPrefs::STORAGE_KEY)
+            .and_then(|bytes| DisplayPrefs::from_bytes(&bytes))
+            .unwrap_or_default();
+        Self::with_display_prefs(hal, prefs)
+    }
+
+    /// Creates the application with specific [DisplayPrefs], ignoring any saved by the HAL.
+    pub fn with_display_prefs(hal: &'h mut H, prefs: DisplayPrefs) -> Self {
         Self {
             hal,
             state: ApplicationState::Normal,
-            output_format: Base::Decimal,
-            signed_result: None,
+            output_format: prefs.output_format,
+            signed_result: prefs.signed_result,
+            settings: Settings::default(),
             input_shifted: false,
             glyphs: vec![],
             cursor_pos: 0,
@@ -90,15 +206,41 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
                 data_type: DataType {
                     bits: 32,
                     signed: false,
-                }
+                },
+                overflow_behaviour: OverflowBehaviour::Wrap,
+                divide_by_zero: DivideByZero::Error,
+                operators: OperatorGroups::ALL,
+                raw_based_literals: false,
             },
             eval_result: None,
+            mask_source: None,
+            operator_first: None,
             constant_overflows: false,
 
             // Variables are initially 0
             variables: (0..16).into_iter()
                 .map(|_| vec![Glyph::Digit(0)])
-                .collect::<Vec<_>>().try_into().unwrap()
+                .collect::<Vec<_>>().try_into().unwrap(),
+            answer: None,
+
+            undo_stack: VecDeque::new(),
+            redo_stack: vec![],
+            history: VecDeque::new(),
+            overflow_checker: IncrementalOverflowChecker::new(),
+            asleep: false,
+            repeat_key: None,
+        }
+    }
+
+    /// Gets the result of the most recent evaluation, or `None` if the expression has not been
+    /// evaluated since it was last changed.
+    /// 
+    /// If multiple expressions were evaluated, this is the result of the last one.
+    pub fn current_result(&self) -> Option<Result<&EvaluationResult, &ParserError>> {
+        match &self.eval_result {
+            Some(Ok(results)) => results.last().map(Ok),
+            Some(Err(e)) => Some(Err(e)),
+            None => None,
         }
     }
 
@@ -118,33 +260,335 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
         self.clear_evaluation(true);
     }
 
+    fn push_undo_snapshot(&mut self, snapshot: UndoSnapshot) {
+        if self.undo_stack.len() == Self::MAX_UNDO_DEPTH {
+            self.undo_stack.pop_front();
+        }
+        self.undo_stack.push_back(snapshot);
+    }
+
+    /// Adds the current expression and its result to the history, if it has been evaluated.
+    fn push_history(&mut self) {
+        if self.glyphs.is_empty() {
+            return;
+        }
+        let Some(result) = self.eval_result_to_string() else { return };
+
+        if self.history.len() == Self::MAX_HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(HistoryEntry { glyphs: self.glyphs.clone(), result });
+    }
+
+    /// Replaces the expression with one from the history, ready to be edited or evaluated again.
+    fn load_history_entry(&mut self, index: usize) {
+        let Some(entry) = self.history.iter().rev().nth(index) else { return };
+        let glyphs = entry.glyphs.clone();
+
+        self.clear_all(false);
+        self.glyphs = glyphs;
+        self.cursor_pos = self.glyphs.len();
+        self.adjust_scroll();
+    }
+
+    /// Restores the expression to how it was before the last edit, if there is one.
+    fn undo_and_redraw(&mut self) {
+        let Some(snapshot) = self.undo_stack.pop_back() else {
+            self.draw_header();
+            return
+        };
+
+        let current = self.restore_snapshot_and_redraw(snapshot);
+        self.redo_stack.push(current);
+    }
+
+    /// Applies the last undone edit again, if nothing else has been edited since.
+    fn redo_and_redraw(&mut self) {
+        let Some(snapshot) = self.redo_stack.pop() else {
+            self.draw_header();
+            return
+        };
+
+        let current = self.restore_snapshot_and_redraw(snapshot);
+        self.push_undo_snapshot(current);
+    }
+
+    /// Replaces the expression with a snapshot, returning a snapshot of how it was before.
+    fn restore_snapshot_and_redraw(&mut self, snapshot: UndoSnapshot) -> UndoSnapshot {
+        let current = UndoSnapshot {
+            glyphs: core::mem::replace(&mut self.glyphs, snapshot.glyphs),
+            cursor_pos: core::mem::replace(&mut self.cursor_pos, snapshot.cursor_pos),
+        };
+        self.draw_expression();
+        self.clear_evaluation(true);
+        current
+    }
+
+    /// Whether a digit inserted at the cursor would begin a new number, rather than being part of
+    /// an existing number or a variable reference.
+    fn cursor_at_fresh_number(&self) -> bool {
+        let is_number_part = |g: Option<&Glyph>| matches!(g,
+            Some(Glyph::Digit(_) | Glyph::HexBase | Glyph::BinaryBase | Glyph::DecimalBase | Glyph::OctalBase
+                | Glyph::Exponent | Glyph::Variable));
+
+        let before = if self.cursor_pos > 0 { self.glyphs.get(self.cursor_pos - 1) } else { None };
+        let after = self.glyphs.get(self.cursor_pos);
+        !is_number_part(before) && !is_number_part(after)
+    }
+
+    /// If the cursor is at the end of a number with a hexadecimal base prefix, returns the index of
+    /// that prefix.
+    fn hex_prefix_before_cursor(&self) -> Option<usize> {
+        let digits = self.glyphs[..self.cursor_pos].iter()
+            .rev()
+            .take_while(|g| matches!(g, Glyph::Digit(_)))
+            .count();
+        if digits == 0 || self.cursor_pos == digits {
+            return None;
+        }
+
+        let prefix_pos = self.cursor_pos - digits - 1;
+        if self.glyphs[prefix_pos] == Glyph::HexBase {
+            Some(prefix_pos)
+        } else {
+            None
+        }
+    }
+
+    /// Moves the cursor to just before the next operator after the cursor, or the previous one before
+    /// it, if there is one.
+    fn jump_to_operator_and_redraw(&mut self, forwards: bool) {
+        let target = if forwards {
+            self.glyphs.iter()
+                .enumerate()
+                .skip(self.cursor_pos + 1)
+                .find(|(_, g)| g.is_operator())
+        } else {
+            self.glyphs[..self.cursor_pos].iter()
+                .enumerate()
+                .rev()
+                .find(|(_, g)| g.is_operator())
+        };
+        let Some((index, _)) = target else { return };
+
+        self.cursor_pos = index;
+        self.cursor_moved_and_redraw();
+    }
+
+    /// Redraws the expression after moving the cursor without changing any glyphs. The result is
+    /// still correct, so it is only cleared if the settings ask for it, or if it's hiding the
+    /// expression.
+    fn cursor_moved_and_redraw(&mut self) {
+        self.draw_expression();
+        if self.settings.clear_result_on_move || self.result_covers_expression() {
+            self.clear_evaluation(true);
+        }
+    }
+
+    /// Inserts a pair of parentheses, and places the cursor between them.
+    fn insert_parens_and_redraw(&mut self) {
+        self.glyphs.insert(self.cursor_pos, Glyph::LeftParen);
+        self.cursor_pos += 1;
+        self.glyphs.insert(self.cursor_pos, Glyph::RightParen);
+        self.draw_expression();
+        self.clear_evaluation(true);
+    }
+
     fn set_output_format_and_redraw(&mut self, base: Base) {
         self.output_format = base;
+        self.store_display_prefs();
         self.state = ApplicationState::Normal;
         self.draw_full();
     }
 
-    fn parse<N: NumberParser>(&self) -> (Parser<N>, Result<Node, ParserError>) {
+    /// The value which [Glyph::Answer] refers to when evaluating with the given data type, or `None`
+    /// if there is no answer for that data type.
+    fn answer_value(&self, data_type: DataType) -> Option<&FlexInt> {
+        self.answer.as_ref()
+            .filter(|answer| answer.data_type == data_type)
+            .map(|answer| &answer.result.result)
+    }
+
+    /// Forgets the answer if it was calculated with a different data type to the current one.
+    fn check_answer_data_type(&mut self) {
+        if self.answer_value(self.eval_config.data_type).is_none() {
+            self.answer = None;
+        }
+    }
+
+    fn parse<N: NumberParser>(&self) -> (Parser<N>, Result<Vec<Node>, ParserError>) {
         let mut parser = Parser::new(&self.glyphs, &self.variables, self.eval_config);
-        let result = parser.parse();
+        parser.answer = self.answer_value(self.eval_config.data_type);
+        let result = parser.parse_list();
         (parser, result)
     }
 
     fn evaluate(&mut self) {
-        let (_, node) = self.parse::<FlexInt>();
-        self.eval_result = Some(node.map(|node| evaluate(&node, &self.eval_config)))
+        // Refuse to evaluate if it looks like it would run out of memory, rather than crashing
+        let required = estimate_memory_usage(self.eval_config.data_type.bits, self.glyphs.len());
+        if let Some(free) = self.hal.free_memory() {
+            if required > free {
+                self.mask_source = None;
+                self.eval_result = Some(Err(ParserError::new(0, ParserErrorKind::OutOfMemory)));
+                return;
+            }
+        }
+
+        let (_, nodes) = self.parse::<FlexInt>();
+        // The mask source must be converted to the current data type first, warning if it doesn't fit
+        let mask_source = self.mask_source.take()
+            .map(|source| assign_checked(&source.value, source.data_type.signed, self.eval_config.data_type));
+        let span = GlyphSpan::new(0, self.glyphs.len());
+
+        self.eval_result = Some(nodes.and_then(|nodes|
+            nodes.iter()
+                .map(|node| {
+                    let mut result = evaluate(node, &self.eval_config)?;
+                    if let Some((ref source, overflow)) = mask_source {
+                        let mut overflows = result.overflows;
+                        if overflow {
+                            overflows.push(Overflow { operation: Operation::Assign, span });
+                        }
+                        result = EvaluationResult::new(source.bitwise_xor(&result.result), overflows);
+                    }
+                    Ok(result)
+                })
+                .collect()
+        ));
+
+        if let Some(Ok(results)) = &self.eval_result {
+            if let Some(result) = results.last() {
+                self.answer = Some(Answer { result: result.clone(), data_type: self.eval_config.data_type });
+            }
+        }
+    }
+
+    /// Parses the number of bits entered into the format menu, if it's valid.
+    fn parse_format_bits(bits_digits: &str) -> Option<usize> {
+        // Minimum supported number of bits
+        bits_digits.parse().ok().map(|bits: usize| bits.max(3))
+    }
+
+    /// Evaluates the expression as it would be with the data type being chosen in the format menu,
+    /// so that the menu can preview how the result changes. Returns `None` if there's nothing to
+    /// show, such as if the expression is empty or has an error.
+    /// 
+    /// Any XOR mask isn't applied, since the preview is only a rough guide.
+    fn format_menu_preview(&self, bits_digits: &str) -> Option<String> {
+        let mut config = self.eval_config;
+        if let Some(bits) = Self::parse_format_bits(bits_digits) {
+            config.data_type.bits = bits;
+        }
+
+        // Like a real evaluation, don't risk running out of memory
+        let required = estimate_memory_usage(config.data_type.bits, self.glyphs.len());
+        if self.hal.free_memory().map(|free| required > free).unwrap_or(false) {
+            return None;
+        }
+
+        let mut parser = Parser::<FlexInt>::new(&self.glyphs, &self.variables, config);
+        parser.answer = self.answer_value(config.data_type);
+        let nodes = parser.parse_list().ok()?;
+        let result = evaluate(nodes.last()?, &config).ok()?;
+        let str = self.evaluation_result_to_string(&result);
+        Some(if result.overflow { format!("OVER {}", str) } else { str })
+    }
+
+    /// Removes negations from the expression which cancel each other out, keeping the cursor next
+    /// to the same glyphs.
+    fn collapse_negations(&mut self) {
+        let Ok(tokens) = Tokenizer::new(&self.glyphs).tokenize() else { return };
+        for index in redundant_negations(&tokens).into_iter().rev() {
+            self.glyphs.remove(index);
+            if index < self.cursor_pos {
+                self.cursor_pos -= 1;
+            }
+        }
+    }
+
+    /// Numerically negates the current result in place.
+    /// 
+    /// The largest possible negative signed number has no positive counterpart of the same width,
+    /// so negating it widens the result by a bit to show the correct value, and marks it as an
+    /// overflow. Negating it again brings it back to the data type's width.
+    fn negate_result(&mut self) {
+        let DataType { bits, signed } = self.eval_config.data_type;
+        let span = GlyphSpan::new(0, self.glyphs.len());
+        let Some(Ok(results)) = &mut self.eval_result else { return };
+        let Some(result) = results.last_mut() else { return };
+
+        let overflow = if signed {
+            let (mut negated, widened) = result.result.negate_widening();
+
+            // Narrow a previously-widened result if it fits again
+            if negated.size() > bits {
+                let (narrowed, overflow) = assign_checked(&negated, true, self.eval_config.data_type);
+                if !overflow {
+                    negated = narrowed;
+                }
+            }
+
+            result.result = negated;
+            widened
+        } else {
+            // Unsigned negation wraps, and only zero doesn't overflow
+            let negated = result.result.negate().unwrap_or_else(|| result.result.clone());
+            let overflow = !negated.is_zero();
+            result.result = negated;
+            overflow
+        };
+
+        // Negations within the expression are kept - only replace the one from negating the result
+        result.overflows.retain(|o| o.operation != Operation::Negate || o.span != span);
+        if overflow {
+            result.overflows.push(Overflow { operation: Operation::Negate, span });
+        }
+        result.overflow = !result.overflows.is_empty();
+    }
+
+    /// Remembers the current result and clears the expression, so that the next evaluation shows
+    /// the XOR mask between the two.
+    fn begin_mask(&mut self) {
+        let Some(Ok(result)) = self.current_result() else { return };
+        let source = MaskSource {
+            value: result.result.clone(),
+            data_type: self.eval_config.data_type,
+        };
+
+        self.clear_all(false);
+        self.mask_source = Some(source);
+    }
+
+    /// Parses the expression to find any constants which overflow the current data type, updating
+    /// `constant_overflows` and returning the indices of the glyphs which should show a warning.
+    fn check_constant_overflows(&mut self) -> Vec<usize> {
+        // This happens on every keypress, so only parse the part of the expression which changed
+        let warning_indices = self.overflow_checker.check(&self.glyphs, &self.variables, self.eval_config)
+            .iter()
+            .flat_map(|s| s.indices())
+            .collect::<Vec<_>>();
+
+        self.constant_overflows = !warning_indices.is_empty();
+        warning_indices
     }
 
     fn clear_evaluation(&mut self, redraw: bool) {
+        let had_error_index = self.eval_error_index().is_some();
         self.eval_result = None;
 
         if redraw {
+            if had_error_index {
+                // Remove the error's highlight from the expression
+                self.draw_expression();
+            }
             self.draw_result();
             self.draw_header();
         }
     }
 
     fn clear_all(&mut self, redraw: bool) {
+        self.mask_source = None;
+        self.operator_first = None;
         self.clear_evaluation(redraw);
         self.glyphs.clear();
         self.cursor_pos = 0;
@@ -153,57 +597,194 @@ impl<'h, H: Hal> CalculatorApplication<'h, H> {
     }
 
     fn adjust_scroll(&mut self) {
-        // Check if we need to scroll to the left
-        if self.cursor_pos == self.scroll_offset && self.cursor_pos > 0 {
-            self.scroll_offset -= 1;
-        }
+        // The glyphs either side of the cursor must always be visible, plus any extra requested by
+        // the scroll margin.
+        // The glyph after the end of the expression counts as visible, so that there is a blank
+        // space to the right of the cursor when it's at the end.
+        let context = self.settings.scroll_margin + 1;
+        let visible_start = self.cursor_pos.saturating_sub(context);
+        let visible_end = (self.cursor_pos + context).min(self.glyphs.len() + 1);
 
         // Check if we need to scroll to the right
-        if self.cursor_pos == self.scroll_offset + Self::WIDTH {
-            self.scroll_offset += 1;
+        if visible_end > self.scroll_offset + Self::WIDTH {
+            self.scroll_offset = visible_end - Self::WIDTH;
+        }
+
+        // Check if we need to scroll to the left
+        if visible_start < self.scroll_offset {
+            self.scroll_offset = visible_start;
         }
     }
 
-    fn eval_result_to_string(&self) -> Option<String> {
+    /// Converts the evaluation result into one string for each evaluated expression, or a single
+    /// string describing the error if parsing failed.
+    fn eval_result_to_strings(&self) -> Option<Vec<String>> {
         let Some(ref result) = self.eval_result else { return None };
 
         Some(match result {
-            Ok(result) => {
-                let signed = self.signed_result.unwrap_or(self.eval_config.data_type.signed);
-                match self.output_format {
-                    Base::Decimal => {
-                        if signed {
-                            result.result.to_signed_decimal_string()
-                        } else {
-                            result.result.to_unsigned_decimal_string()
-                        }
-                    }
-                    Base::Hexadecimal => {
-                        format!("x{}", if signed {
-                            result.result.to_signed_hex_string()
-                        } else {
-                            result.result.to_unsigned_hex_string()
-                        })
-                    }
-                    Base::Binary => {
-                        format!("b{}", if signed {
-                            result.result.to_signed_binary_string()
-                        } else {
-                            result.result.to_unsigned_binary_string()
-                        })
-                    }
-                }
-                
-            },
-            Err(e) => e.describe(),
+            Ok(results) => results.iter().map(|r| self.evaluation_result_to_string(r)).collect(),
+            Err(e) => vec![e.describe()],
         })
     }
 
+    /// Converts the evaluation result into a string. If multiple expressions were evaluated, this
+    /// gives the result of the last one.
+    fn eval_result_to_string(&self) -> Option<String> {
+        self.eval_result_to_strings()?.pop()
+    }
+
+    fn evaluation_result_to_string(&self, result: &EvaluationResult) -> String {
+        self.number_to_string(&result.result, self.result_is_signed(result))
+    }
+
+    /// Converts the evaluation result into a string as it would be shown with a different output
+    /// base, leaving the output base unchanged afterwards.
+    fn eval_result_to_string_in_base(&mut self, base: Base) -> Option<String> {
+        let original = core::mem::replace(&mut self.output_format, base);
+        let str = self.eval_result_to_string();
+        self.output_format = original;
+        str
+    }
+
+    /// Whether a result is shown as signed.
+    fn result_is_signed(&self, result: &EvaluationResult) -> bool {
+        if result.overflow && self.eval_config.overflow_behaviour == OverflowBehaviour::Reinterpret {
+            false
+        } else {
+            self.signed_result.unwrap_or(self.eval_config.data_type.signed)
+        }
+    }
+
+    /// Gets the result of the most recent evaluation as it would be shown with the opposite
+    /// signedness, labelled with that signedness, if [Settings::opposite_signedness] is enabled.
+    /// 
+    /// This is `None` if the result wouldn't look any different, which is the case unless the
+    /// most-significant bit is set. There's also nothing to show for multiple results, or for Gray
+    /// codes, which are never signed.
+    fn opposite_signedness_string(&self) -> Option<String> {
+        if !self.settings.opposite_signedness || self.settings.gray_code {
+            return None;
+        }
+        let Some(Ok(results)) = &self.eval_result else { return None };
+        let [result] = &results[..] else { return None };
+        if !result.result.is_negative() {
+            return None;
+        }
+
+        let signed = !self.result_is_signed(result);
+        Some(format!("{}:{}", if signed { 'S' } else { 'U' }, self.number_to_string(&result.result, signed)))
+    }
+
+    /// Converts a number to a string using the current display settings, such as the output base.
+    fn number_to_string(&self, number: &FlexInt, mut signed: bool) -> String {
+        // A Gray code is a pattern of bits rather than a number, so it's never shown as signed
+        let gray_code;
+        let number = if self.settings.gray_code {
+            signed = false;
+            gray_code = number.to_gray_code();
+            &gray_code
+        } else {
+            number
+        };
+
+        let mut str = match (self.output_format, signed) {
+            (Base::Decimal, true) => number.to_signed_decimal_string(),
+            (Base::Decimal, false) => number.to_unsigned_decimal_string(),
+            (Base::Hexadecimal, true) => number.to_signed_hex_string(),
+            (Base::Hexadecimal, false) => number.to_unsigned_hex_string(),
+            (Base::Binary, true) => number.to_signed_binary_string(),
+            (Base::Binary, false) => number.to_unsigned_binary_string(),
+            (Base::Octal, true) => number.to_signed_octal_string(),
+            (Base::Octal, false) => number.to_unsigned_octal_string(),
+        };
+
+        if self.settings.zero_pad {
+            match self.output_format {
+                Base::Hexadecimal => str = pad_digits(&str, number.size().div_ceil(4)),
+                Base::Binary => str = pad_digits(&str, number.size()),
+                Base::Decimal | Base::Octal => (),
+            }
+        }
+
+        let group_size = match self.output_format {
+            Base::Decimal | Base::Octal => Some(3),
+            Base::Hexadecimal => self.settings.hex_group_size,
+            Base::Binary => self.settings.binary_group_size,
+        };
+        if let (Some(separator), Some(size)) = (self.settings.thousands_separator, group_size) {
+            str = group_digits(&str, separator, size);
+        }
+
+        if signed && self.settings.explicit_plus_sign && !number.is_negative() && !number.is_zero() {
+            str.insert(0, '+');
+        }
+
+        match self.output_format {
+            Base::Decimal => str,
+            Base::Hexadecimal => format!("x{}", str),
+            Base::Binary => format!("b{}", str),
+            Base::Octal => format!("o{}", str),
+        }
+    }
+
+    /// Converts the evaluation result into glyphs which evaluate back to the same value in the
+    /// current data type. If multiple expressions were evaluated, this gives the last one.
+    /// 
+    /// This uses the value rather than how it's shown, since it may be shown in a way which can't
+    /// be parsed back, like with digit separators or as a Gray code.
+    fn eval_result_to_glyphs(&self) -> Option<Vec<Glyph>> {
+        let Some(Ok(results)) = &self.eval_result else { return None };
+        let result = &results.last()?.result;
+        let str = if self.eval_config.data_type.signed {
+            result.to_signed_decimal_string()
+        } else {
+            result.to_unsigned_decimal_string()
+        };
+        Glyph::from_string(&str)
+    }
+
+    /// The index of the glyph where the most recent evaluation failed, if it did. Running out of
+    /// memory isn't the fault of any particular glyph, so has no index.
+    fn eval_error_index(&self) -> Option<usize> {
+        match &self.eval_result {
+            Some(Err(e)) if *e.kind() != ParserErrorKind::OutOfMemory => Some(e.ptr()),
+            _ => None,
+        }
+    }
+
     fn eval_result_has_overflow(&self) -> bool {
         if let Some(Ok(r)) = &self.eval_result {
-            r.overflow || self.constant_overflows
+            r.iter().any(|r| r.overflow) || self.constant_overflows
         } else {
             false
         }
     }
 }
+
+/// Adds leading zeros to a number string until it has at least `width` digits. A leading sign is
+/// left alone.
+fn pad_digits(str: &str, width: usize) -> String {
+    let (sign, digits) = match str.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", str),
+    };
+    format!("{}{:0>width$}", sign, digits, width = width)
+}
+
+/// Inserts a separator between each group of `size` digits in a number string, counting from the
+/// right. A leading sign is left alone.
+fn group_digits(str: &str, separator: char, size: usize) -> String {
+    let (sign, digits) = match str.strip_prefix('-') {
+        Some(digits) => ("-", digits),
+        None => ("", str),
+    };
+
+    let mut result = String::from(sign);
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % size == 0 {
+            result.push(separator);
+        }
+        result.push(c);
+    }
+    result
+}
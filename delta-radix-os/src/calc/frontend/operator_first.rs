@@ -0,0 +1,66 @@
+use alloc::{vec, vec::Vec};
+use delta_radix_hal::{Hal, Glyph};
+
+use super::CalculatorApplication;
+
+/// A calculation being entered operator-first, where the operator is chosen before the operands,
+/// which are then entered one at a time.
+pub(super) struct OperatorFirstEntry {
+    pub operator: Glyph,
+
+    /// The operand entered first, or `None` if it's still being entered.
+    pub first_operand: Option<Vec<Glyph>>,
+}
+
+impl<'h, H: Hal> CalculatorApplication<'h, H> {
+    /// Whether pressing an operator key should begin operator-first entry, rather than inserting the
+    /// operator into the expression.
+    pub(super) fn can_begin_operator_first(&self) -> bool {
+        self.settings.operator_first && self.glyphs.is_empty() && self.operator_first.is_none()
+    }
+
+    pub(super) fn begin_operator_first(&mut self, operator: Glyph) {
+        self.operator_first = Some(OperatorFirstEntry { operator, first_operand: None });
+        self.clear_evaluation(false);
+        self.draw_full();
+    }
+
+    /// Takes the expression as the next operand. Once both operands have been entered, they're
+    /// assembled into an expression with the operator, and this returns true so that it can be
+    /// evaluated.
+    pub(super) fn enter_operand_and_redraw(&mut self) -> bool {
+        let Some(entry) = &mut self.operator_first else { return true };
+        if self.glyphs.is_empty() {
+            return false;
+        }
+
+        let operand = core::mem::take(&mut self.glyphs);
+        self.cursor_pos = 0;
+        self.scroll_offset = 0;
+
+        let Some(first_operand) = entry.first_operand.take() else {
+            entry.first_operand = Some(operand);
+            self.draw_full();
+            return false;
+        };
+
+        let operator = entry.operator;
+        self.operator_first = None;
+        self.glyphs = parenthesize_operand(first_operand);
+        self.glyphs.push(operator);
+        self.glyphs.extend(parenthesize_operand(operand));
+        self.cursor_pos = self.glyphs.len();
+        self.draw_expression();
+        true
+    }
+}
+
+/// Wraps an operand in parentheses if it contains operators of its own, so that it's evaluated
+/// as a whole regardless of precedence.
+fn parenthesize_operand(operand: Vec<Glyph>) -> Vec<Glyph> {
+    if operand.iter().any(Glyph::is_operator) {
+        [vec![Glyph::LeftParen], operand, vec![Glyph::RightParen]].concat()
+    } else {
+        operand
+    }
+}
@@ -0,0 +1,345 @@
+use alloc::{borrow::Cow, string::ToString};
+
+use delta_radix_hal::Hal;
+
+use crate::calc::backend::eval::OperatorGroup;
+
+use super::{CalculatorApplication, Base};
+
+/// Options which affect the behaviour of the calculator's interface, rather than evaluation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Settings {
+    /// Whether inserting an opening parenthesis also inserts a closing one after the cursor.
+    pub auto_close_parens: bool,
+
+    /// Whether positive signed results are shown with a `+` sign.
+    pub explicit_plus_sign: bool,
+
+    /// The character inserted between each group of digits in results, if any. Decimal digits are
+    /// always grouped in threes, while the group sizes for other bases are configurable.
+    pub thousands_separator: Option<char>,
+
+    /// How many digits are in each group of a hexadecimal result, or `None` to not group them.
+    pub hex_group_size: Option<usize>,
+
+    /// How many digits are in each group of a binary result, or `None` to not group them.
+    pub binary_group_size: Option<usize>,
+
+    /// How many glyphs of the expression to keep visible beyond those next to the cursor, before
+    /// scrolling.
+    pub scroll_margin: usize,
+
+    /// Whether results are shown as their Gray code, rather than as the number itself.
+    pub gray_code: bool,
+
+    /// Whether negations which cancel each other out, like in `--5`, are removed from the
+    /// expression when it is evaluated.
+    pub collapse_negations: bool,
+
+    /// Whether moving the cursor clears the result, as editing the expression does. Otherwise, the
+    /// result stays visible until the expression is changed.
+    pub clear_result_on_move: bool,
+
+    /// Whether to drop the header row, giving the result an extra row. The data type and overflow
+    /// marker are shown in the corner of the result instead.
+    pub compact_layout: bool,
+
+    /// Whether results with the most-significant bit set are also shown with the opposite
+    /// signedness, such as `-56` alongside `200` for an unsigned 8-bit result.
+    pub opposite_signedness: bool,
+
+    /// Whether pressing an operator key with an empty expression starts entering a calculation
+    /// operator-first, where the two operands are then entered one at a time.
+    pub operator_first: bool,
+
+    /// Whether hexadecimal and binary results are padded with leading zeros to the full width of
+    /// the data type. This is toggled from the format menu, rather than the settings menu.
+    pub zero_pad: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            auto_close_parens: true,
+            explicit_plus_sign: false,
+            thousands_separator: None,
+            hex_group_size: None,
+            binary_group_size: None,
+            scroll_margin: 2,
+            gray_code: false,
+            collapse_negations: false,
+            clear_result_on_move: false,
+            compact_layout: false,
+            opposite_signedness: false,
+            operator_first: false,
+            zero_pad: false,
+        }
+    }
+}
+
+/// How results are displayed. Unlike [Settings], these are remembered across restarts, using the
+/// HAL's storage.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct DisplayPrefs {
+    pub output_format: Base,
+    pub signed_result: Option<bool>,
+}
+
+impl Default for DisplayPrefs {
+    fn default() -> Self {
+        Self {
+            output_format: Base::Decimal,
+            signed_result: None,
+        }
+    }
+}
+
+impl DisplayPrefs {
+    /// The key which these preferences are stored under.
+    pub const STORAGE_KEY: &'static str = "display-prefs";
+
+    pub fn to_bytes(&self) -> [u8; 2] {
+        let base = match self.output_format {
+            Base::Decimal => 0,
+            Base::Hexadecimal => 1,
+            Base::Binary => 2,
+            Base::Octal => 3,
+        };
+        let signed = match self.signed_result {
+            None => 0,
+            Some(false) => 1,
+            Some(true) => 2,
+        };
+        [base, signed]
+    }
+
+    /// Reads preferences written by [Self::to_bytes], or returns `None` if they aren't valid.
+    pub fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let [base, signed] = bytes else { return None };
+        let output_format = match base {
+            0 => Base::Decimal,
+            1 => Base::Hexadecimal,
+            2 => Base::Binary,
+            3 => Base::Octal,
+            _ => return None,
+        };
+        let signed_result = match signed {
+            0 => None,
+            1 => Some(false),
+            2 => Some(true),
+            _ => return None,
+        };
+        Some(Self { output_format, signed_result })
+    }
+}
+
+/// An option which the user can change from the settings menu.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Setting {
+    OverflowBehaviour,
+    DivideByZero,
+    AutoCloseParens,
+    /// Opens a sub-menu of [Setting::OPERATORS], rather than having a value of its own.
+    Operators,
+    Operator(OperatorGroup),
+    ExplicitPlusSign,
+    ThousandsSeparator,
+    ScrollMargin,
+    GrayCode,
+    CollapseNegations,
+    RawBasedLiterals,
+    ClearResultOnMove,
+    CompactLayout,
+    OppositeSignedness,
+    OperatorFirst,
+    HexGroupSize,
+    BinaryGroupSize,
+}
+
+impl Setting {
+    /// All settings, in the order they are shown in the menu. The index of a setting in this list
+    /// is the digit key used to change it.
+    pub const ALL: &'static [Setting] = &[
+        Setting::OverflowBehaviour,
+        Setting::AutoCloseParens,
+        Setting::Operators,
+        Setting::ExplicitPlusSign,
+        Setting::ThousandsSeparator,
+        Setting::ScrollMargin,
+        Setting::GrayCode,
+        Setting::DivideByZero,
+        Setting::CollapseNegations,
+        Setting::RawBasedLiterals,
+        Setting::ClearResultOnMove,
+        Setting::CompactLayout,
+        Setting::OppositeSignedness,
+        Setting::OperatorFirst,
+        Setting::HexGroupSize,
+        Setting::BinaryGroupSize,
+    ];
+
+    /// The settings in the sub-menu opened by [Setting::Operators], which enable or disable each
+    /// group of operators.
+    pub const OPERATORS: &'static [Setting] = &[
+        Setting::Operator(OperatorGroup::Align),
+        Setting::Operator(OperatorGroup::Modulo),
+        Setting::Operator(OperatorGroup::Power),
+        Setting::Operator(OperatorGroup::Bitwise),
+        Setting::Operator(OperatorGroup::Shift),
+    ];
+
+    /// The number of settings shown on each page of the menu.
+    pub const PER_PAGE: usize = 4;
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Setting::OverflowBehaviour => "Overflow",
+            Setting::AutoCloseParens => "Auto-close ()",
+            Setting::Operators => "Operators",
+            Setting::Operator(OperatorGroup::Align) => "Align op",
+            Setting::Operator(OperatorGroup::Modulo) => "Modulo op",
+            Setting::Operator(OperatorGroup::Power) => "Power op",
+            Setting::Operator(OperatorGroup::Bitwise) => "Bitwise ops",
+            Setting::Operator(OperatorGroup::Shift) => "Shift ops",
+            Setting::ExplicitPlusSign => "Show + sign",
+            Setting::ThousandsSeparator => "Thousands sep",
+            Setting::ScrollMargin => "Scroll margin",
+            Setting::GrayCode => "Gray code",
+            Setting::DivideByZero => "Divide by 0",
+            Setting::CollapseNegations => "Collapse --",
+            Setting::RawBasedLiterals => "Raw hex/bin",
+            Setting::ClearResultOnMove => "Clear on move",
+            Setting::CompactLayout => "Compact layout",
+            Setting::OppositeSignedness => "Other sign",
+            Setting::OperatorFirst => "Operator first",
+            Setting::HexGroupSize => "Hex groups",
+            Setting::BinaryGroupSize => "Binary groups",
+        }
+    }
+}
+
+impl<'h, H: Hal> CalculatorApplication<'h, H> {
+    pub(super) fn display_prefs(&self) -> DisplayPrefs {
+        DisplayPrefs {
+            output_format: self.output_format,
+            signed_result: self.signed_result,
+        }
+    }
+
+    /// Saves the current [DisplayPrefs], so that they are restored when the device next starts.
+    pub(super) fn store_display_prefs(&mut self) {
+        let bytes = self.display_prefs().to_bytes();
+        self.hal.store(DisplayPrefs::STORAGE_KEY, &bytes);
+    }
+
+    /// Gets a short description of the current value of a setting.
+    pub(super) fn setting_value(&self, setting: Setting) -> Cow<'static, str> {
+        let value = match setting {
+            Setting::OverflowBehaviour => self.eval_config.overflow_behaviour.describe(),
+            Setting::AutoCloseParens => on_off(self.settings.auto_close_parens),
+            Setting::Operators => "...",
+            Setting::Operator(group) => on_off(self.eval_config.operators.is_enabled(group)),
+            Setting::ExplicitPlusSign => on_off(self.settings.explicit_plus_sign),
+            Setting::ThousandsSeparator => match self.settings.thousands_separator {
+                None => "Off",
+                Some(' ') => "Space",
+                Some(c) => return c.to_string().into(),
+            },
+            Setting::ScrollMargin => return self.settings.scroll_margin.to_string().into(),
+            Setting::GrayCode => on_off(self.settings.gray_code),
+            Setting::DivideByZero => self.eval_config.divide_by_zero.describe(),
+            Setting::CollapseNegations => on_off(self.settings.collapse_negations),
+            Setting::RawBasedLiterals => on_off(self.eval_config.raw_based_literals),
+            Setting::ClearResultOnMove => on_off(self.settings.clear_result_on_move),
+            Setting::CompactLayout => on_off(self.settings.compact_layout),
+            Setting::OppositeSignedness => on_off(self.settings.opposite_signedness),
+            Setting::OperatorFirst => on_off(self.settings.operator_first),
+            Setting::HexGroupSize => return group_size(self.settings.hex_group_size),
+            Setting::BinaryGroupSize => return group_size(self.settings.binary_group_size),
+        };
+        value.into()
+    }
+
+    /// Changes a setting to its next possible value.
+    pub(super) fn cycle_setting(&mut self, setting: Setting) {
+        match setting {
+            Setting::OverflowBehaviour =>
+                self.eval_config.overflow_behaviour = self.eval_config.overflow_behaviour.next(),
+            Setting::AutoCloseParens =>
+                self.settings.auto_close_parens = !self.settings.auto_close_parens,
+            // Handled by the menu, which opens the sub-menu instead
+            Setting::Operators => (),
+            Setting::Operator(group) => {
+                let enabled = self.eval_config.operators.is_enabled(group);
+                self.eval_config.operators.set_enabled(group, !enabled);
+            }
+            Setting::ExplicitPlusSign =>
+                self.settings.explicit_plus_sign = !self.settings.explicit_plus_sign,
+            Setting::ThousandsSeparator => {
+                // If the separator isn't one of the usual ones, start again from the first
+                let next = THOUSANDS_SEPARATORS.iter()
+                    .position(|s| *s == self.settings.thousands_separator)
+                    .map_or(0, |index| index + 1);
+                self.settings.thousands_separator =
+                    THOUSANDS_SEPARATORS[next % THOUSANDS_SEPARATORS.len()];
+            }
+            Setting::ScrollMargin =>
+                self.settings.scroll_margin = (self.settings.scroll_margin + 1) % (MAX_SCROLL_MARGIN + 1),
+            Setting::GrayCode =>
+                self.settings.gray_code = !self.settings.gray_code,
+            Setting::DivideByZero =>
+                self.eval_config.divide_by_zero = self.eval_config.divide_by_zero.next(),
+            Setting::CollapseNegations =>
+                self.settings.collapse_negations = !self.settings.collapse_negations,
+            Setting::RawBasedLiterals =>
+                self.eval_config.raw_based_literals = !self.eval_config.raw_based_literals,
+            Setting::ClearResultOnMove =>
+                self.settings.clear_result_on_move = !self.settings.clear_result_on_move,
+            Setting::CompactLayout =>
+                self.settings.compact_layout = !self.settings.compact_layout,
+            Setting::OppositeSignedness =>
+                self.settings.opposite_signedness = !self.settings.opposite_signedness,
+            Setting::OperatorFirst =>
+                self.settings.operator_first = !self.settings.operator_first,
+            Setting::HexGroupSize =>
+                self.settings.hex_group_size = next_group_size(self.settings.hex_group_size, &HEX_GROUP_SIZES),
+            Setting::BinaryGroupSize =>
+                self.settings.binary_group_size = next_group_size(self.settings.binary_group_size, &BINARY_GROUP_SIZES),
+        }
+    }
+}
+
+/// The possible values of [Settings::thousands_separator], in the order they are cycled through.
+/// 
+/// None of these are used by any [delta_radix_hal::Glyph], so a grouped result can never be
+/// mistaken for part of an expression.
+pub const THOUSANDS_SEPARATORS: [Option<char>; 5] = [None, Some(','), Some('.'), Some('_'), Some(' ')];
+
+/// The possible values of [Settings::hex_group_size], in the order they are cycled through - either
+/// bytes or nibbles.
+pub const HEX_GROUP_SIZES: [Option<usize>; 3] = [None, Some(2), Some(4)];
+
+/// The possible values of [Settings::binary_group_size], in the order they are cycled through -
+/// either nibbles or bytes.
+pub const BINARY_GROUP_SIZES: [Option<usize>; 3] = [None, Some(4), Some(8)];
+
+/// The largest possible value of [Settings::scroll_margin]. Any more would be awkward on a display
+/// only 20 glyphs wide.
+pub const MAX_SCROLL_MARGIN: usize = 4;
+
+fn on_off(value: bool) -> &'static str {
+    if value { "On" } else { "Off" }
+}
+
+fn group_size(size: Option<usize>) -> Cow<'static, str> {
+    match size {
+        None => "Off".into(),
+        Some(size) => size.to_string().into(),
+    }
+}
+
+/// Gets the size after `size` in `sizes`, or the first size if `size` isn't one of them.
+fn next_group_size(size: Option<usize>, sizes: &[Option<usize>]) -> Option<usize> {
+    let next = sizes.iter().position(|s| *s == size).map_or(0, |index| index + 1);
+    sizes[next % sizes.len()]
+}
@@ -1,15 +1,16 @@
 #![feature(async_fn_in_trait)]
 
-use std::{panic::catch_unwind, cell::RefCell, rc::Rc};
+use std::{cell::RefCell, rc::Rc, time::Duration};
 
 use delta_radix_hal::{Key, Hal};
-use delta_radix_os::main;
+use delta_radix_os::{main, calc::{frontend::{CalculatorApplication, DisplayPrefs, Base}, backend::eval::estimate_memory_usage}};
+use flex_int::FlexInt;
 use futures::executor::block_on;
 use hal::TestHal;
-use keys::{SetFormat, Number};
+use keys::{SetFormat, Number, CycleSetting, ToggleOperator, Held};
 use panic_message::panic_message;
 
-use crate::{hal::run_os, keys::Shifted};
+use crate::{hal::{run_os, run_os_interactive, run_os_with_free_memory}, keys::Shifted};
 
 mod hal;
 
@@ -75,6 +76,25 @@ fn test_hex_result() {
     assert!(!hal.overflow());
 }
 
+#[test]
+fn test_octal() {
+    let hal = run_os(&keys!(Key::OctalBase, Number(17), Key::Exe));
+    assert_eq!(hal.expression(), "o17");
+    assert_eq!(hal.result(), "15");
+    assert!(!hal.overflow());
+
+    // Octal has no key on the device, so it's also available as Shift > A
+    let hal = run_os(&keys!(
+        Key::FormatSelect,
+        Key::Digit(8),
+        Number(17),
+        Shifted(Key::Digit(0xA)),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "17o");
+    assert_eq!(hal.result(), "o17");
+}
+
 #[test]
 fn test_binary_input() {
     let hal = run_os(&keys!(
@@ -155,3 +175,1577 @@ fn test_parentheses() {
     assert_eq!(hal.result(), (2*(5+3)*4).to_string());
     assert!(!hal.overflow());
 }
+
+#[test]
+fn test_multiple_results() {
+    let hal = run_os(&keys!(
+        Number(2),
+        Key::Add,
+        Number(2),
+        Shifted(Key::Exe),
+        Number(3),
+        Key::Multiply,
+        Number(3),
+        Key::Exe,
+    ));
+    assert_eq!(hal.display_line(1).trim(), "4");
+    assert_eq!(hal.display_line(2).trim(), "9");
+    assert!(!hal.overflow());
+}
+
+#[test]
+fn test_constant_overflow_rechecked_on_format_change() {
+    let hal = run_os(&keys!(
+        SetFormat(16, false),
+        Number(200),
+    ));
+    assert!(!hal.display_line(1).contains('!'));
+
+    let hal = run_os(&keys!(
+        SetFormat(16, false),
+        Number(200),
+        SetFormat(8, true),
+    ));
+    assert!(hal.display_line(1).contains('!'));
+
+    let hal = run_os(&keys!(
+        SetFormat(16, false),
+        Number(200),
+        SetFormat(8, true),
+        SetFormat(16, false),
+    ));
+    assert!(!hal.display_line(1).contains('!'));
+}
+
+#[test]
+fn test_variable_constant_overflow_rechecked_on_format_change() {
+    let store = keys!(
+        SetFormat(16, true),
+        Number(200),
+        Key::Exe,
+        Shifted(Key::Variable),
+        Key::Digit(1),
+        Shifted(Key::Delete),
+    );
+
+    let hal = run_os(&[store.clone(), keys!(
+        SetFormat(8, true),
+        Key::Variable,
+        Key::Digit(1),
+    )].concat());
+    assert!(hal.display_line(1).contains('!'));
+
+    let hal = run_os(&[store.clone(), keys!(
+        SetFormat(8, true),
+        Key::Variable,
+        Key::Digit(1),
+        SetFormat(16, true),
+    )].concat());
+    assert!(!hal.display_line(1).contains('!'));
+
+    let hal = run_os(&[store, keys!(
+        SetFormat(8, true),
+        Key::Variable,
+        Key::Digit(1),
+        Key::Exe,
+        SetFormat(16, true),
+        Key::Exe,
+    )].concat());
+    assert!(!hal.display_line(1).contains('!'));
+    assert!(!hal.overflow());
+    assert_eq!(hal.result(), "200");
+}
+
+#[test]
+fn test_hex_digit_inserts_base() {
+    let hal = run_os(&keys!(
+        Key::Digit(0xA),
+        Key::Digit(1),
+        Key::Add,
+        Number(1),
+        Key::Digit(0xB),
+        Key::HexBase,
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "xA1+1Bx");
+    assert_eq!(hal.result(), (0xA1 + 0x1B).to_string());
+    assert!(!hal.overflow());
+}
+
+#[test]
+fn test_current_result() {
+    let mut hal = TestHal::new(&[]);
+    let mut app = CalculatorApplication::new(&mut hal);
+    assert!(app.current_result().is_none());
+
+    block_on(async {
+        for key in keys!(Number(12), Key::Multiply, Number(5), Key::Exe) {
+            app.process_input_and_redraw(key).await;
+        }
+    });
+
+    let result = app.current_result().unwrap().unwrap();
+    assert_eq!(result.result, FlexInt::from_int(60, 32));
+    assert_eq!(&result.result.bits()[..8], &[false, false, true, true, true, true, false, false]);
+    assert!(!result.overflow);
+}
+
+#[test]
+fn test_signed_overflow_behaviour() {
+    // Wrap (default)
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        Number(127),
+        Key::Add,
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "-128");
+    assert!(hal.overflow());
+
+    // Unsigned reinterpretation
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        CycleSetting(0, 1),
+        Number(127),
+        Key::Add,
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "128");
+    assert!(hal.overflow());
+
+    // Saturate
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        CycleSetting(0, 2),
+        Number(127),
+        Key::Add,
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "127");
+    assert!(hal.overflow());
+
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        CycleSetting(0, 2),
+        Number(-100),
+        Key::Subtract,
+        Number(100),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "-128");
+    assert!(hal.overflow());
+}
+
+#[test]
+fn test_big_mode_with_overflow() {
+    let hal = run_os(&keys!(
+        SetFormat(72, false),
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Number(0),
+        Key::Subtract,
+        Number(1),
+        Key::Exe,
+    ));
+
+    // "OVER " followed by "b" and 72 ones, wrapping across the whole screen
+    let expected = format!("OVER b{}", "1".repeat(72));
+    let screen = (0..4).map(|i| hal.display_line(i)).collect::<String>();
+    assert_eq!(screen.trim_end(), expected);
+}
+
+#[test]
+fn test_big_mode_snapshot() {
+    let hal = run_os(&keys!(
+        SetFormat(32, false),
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Number(0),
+        Key::Subtract,
+        Number(3),
+        Key::Exe,
+    ));
+    assert_eq!(hal.snapshot(), [
+        "+--------------------+",
+        "|U32 === BIG === OVER|",
+        "|b1111111111111111111|",
+        "|1111111111101       |",
+        "|                    |",
+        "+--------------------+",
+    ].join("\n"));
+}
+
+#[test]
+fn test_error_highlight() {
+    let hal = run_os(&keys!(
+        Number(12),
+        Key::Add,
+        Key::Multiply,
+        Number(3),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "unexpected multiply");
+
+    // The cursor is after the end of the expression, and the error is marked above the `*`
+    assert_eq!(hal.snapshot(), [
+        "+--------------------+",
+        "|U32 ================|",
+        "|   !\\/              |",
+        "|12+*3               |",
+        "| unexpected multiply|",
+        "+--------------------+",
+    ].join("\n"));
+
+    // Editing the expression clears the error, and its marker
+    let hal = run_os(&keys!(
+        Number(12),
+        Key::Add,
+        Key::Multiply,
+        Number(3),
+        Key::Exe,
+        Key::Left,
+        Key::Left,
+        Key::Delete,
+    ));
+    assert_eq!(hal.display_line(1).trim_end(), " \\/");
+    assert_eq!(hal.expression(), "12*3");
+}
+
+#[test]
+fn test_auto_close_parens() {
+    let hal = run_os(&keys!(
+        Key::LeftParen,
+    ));
+    assert_eq!(hal.expression(), "()");
+    assert_eq!(hal.display_line(1).trim_end(), "\\/");
+
+    let hal = run_os(&keys!(
+        Number(2),
+        Key::Multiply,
+        Key::LeftParen,
+        Number(1),
+        Key::Add,
+        Number(2),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "2*(1+2)");
+    assert_eq!(hal.result(), "6");
+
+    // Disabled
+    let hal = run_os(&keys!(
+        CycleSetting(1, 1),
+        Key::LeftParen,
+        Number(1),
+        Key::Add,
+        Number(2),
+        Key::RightParen,
+    ));
+    assert_eq!(hal.expression(), "(1+2)");
+}
+
+#[test]
+fn test_disabled_operator() {
+    let hal = run_os(&keys!(
+        Number(13),
+        Shifted(Key::Right),
+        Number(8),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "13>8");
+    assert_eq!(hal.result(), "16");
+
+    let hal = run_os(&keys!(
+        ToggleOperator(0),
+        Number(13),
+        Shifted(Key::Right),
+        Number(8),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "13>8");
+    assert_eq!(hal.result(), "align disabled");
+}
+
+#[test]
+fn test_operators_menu() {
+    // The operators have a sub-menu of their own, since the settings menu is full
+    let hal = run_os(&keys!(Shifted(Key::Menu), Key::Digit(2), Key::Digit(2), Key::Digit(1)));
+    assert_eq!(hal.display_line(0), "0)Align op        On");
+    assert_eq!(hal.display_line(1), "1)Modulo op      Off");
+
+    // There are more groups than fit on one page
+    let hal = run_os(&keys!(Shifted(Key::Menu), Key::Digit(2), Key::Digit(2), Key::Right, Key::Digit(4)));
+    assert_eq!(hal.display_line(0), "4)Shift ops      Off");
+
+    // Leaving it goes back to the settings menu
+    let hal = run_os(&keys!(Shifted(Key::Menu), Key::Digit(2), Key::Digit(2), Key::Menu));
+    assert_eq!(hal.display_line(2), "2)Operators      ...");
+
+    let hal = run_os(&keys!(ToggleOperator(1), Number(7), Shifted(Key::Divide), Number(3), Key::Exe));
+    assert_eq!(hal.result(), "modulo disabled");
+}
+
+#[test]
+fn test_exponent() {
+    let hal = run_os(&keys!(
+        Number(1),
+        Shifted(Key::Multiply),
+        Number(6),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "1e6");
+    assert_eq!(hal.result(), "1000000");
+    assert!(!hal.overflow());
+
+    let hal = run_os(&keys!(
+        SetFormat(8, false),
+        Number(1),
+        Shifted(Key::Multiply),
+        Number(3),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "1e3");
+    assert_eq!(hal.result(), (1000 % 256).to_string());
+    assert!(hal.overflow());
+
+    let hal = run_os(&keys!(
+        Number(1),
+        Shifted(Key::Multiply),
+        Key::Subtract,
+        Number(3),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "negative exponent");
+
+    // An exponent is part of the number, so A-F after it don't start a new hexadecimal one
+    let hal = run_os(&keys!(
+        Number(1),
+        Shifted(Key::Multiply),
+        Key::Digit(0xA),
+    ));
+    assert_eq!(hal.expression(), "1eA");
+}
+
+#[test]
+fn test_explicit_plus_sign() {
+    for (n, expected) in [(5, "+5"), (-5, "-5"), (0, "0")] {
+        let hal = run_os(&keys!(
+            SetFormat(8, true),
+            CycleSetting(3, 1),
+            Number(n),
+            Key::Exe,
+        ));
+        assert_eq!(hal.result(), expected);
+    }
+
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        CycleSetting(3, 1),
+        Key::FormatSelect,
+        Key::HexBase,
+        Number(20),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "x+14");
+
+    // Unsigned results never have a sign
+    let hal = run_os(&keys!(
+        SetFormat(8, false),
+        CycleSetting(3, 1),
+        Number(5),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "5");
+}
+
+#[test]
+fn test_explicit_plus_sign_variable() {
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        CycleSetting(3, 1),
+        Number(5),
+        Key::Exe,
+        Shifted(Key::Variable),
+        Key::Digit(1),
+        Shifted(Key::Delete),
+        Key::Variable,
+        Key::Digit(1),
+        Key::Add,
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "+6");
+}
+
+#[test]
+fn test_thousands_separator() {
+    for (times, expected) in [(0, "1000000"), (1, "1,000,000"), (2, "1.000.000"), (3, "1_000_000"), (4, "1 000 000")] {
+        let hal = run_os(&keys!(
+            CycleSetting(4, times),
+            Number(1000000),
+            Key::Exe,
+        ));
+        assert_eq!(hal.result(), expected);
+    }
+
+    // Signed numbers keep their sign outside of the groups
+    let hal = run_os(&keys!(
+        SetFormat(32, true),
+        CycleSetting(4, 1),
+        Key::Subtract,
+        Number(123456),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "-123,456");
+}
+
+#[test]
+fn test_thousands_separator_variable() {
+    let hal = run_os(&keys!(
+        CycleSetting(4, 1),
+        Number(1000000),
+        Key::Exe,
+        Shifted(Key::Variable),
+        Key::Digit(1),
+        Shifted(Key::Delete),
+        Key::Variable,
+        Key::Digit(1),
+        Key::Add,
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "1,000,001");
+}
+
+#[test]
+fn test_hex_grouped_by_2() {
+    let hal = run_os(&keys!(
+        SetFormat(32, false),
+        CycleSetting(4, 3),
+        CycleSetting(0xE, 1),
+        Key::FormatSelect,
+        Key::HexBase,
+        Key::HexBase,
+        Key::Digit(1), Key::Digit(2), Key::Digit(3), Key::Digit(4),
+        Key::Digit(5), Key::Digit(6), Key::Digit(7), Key::Digit(8),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "x12_34_56_78");
+
+    // Grouping only takes effect with a separator
+    let hal = run_os(&keys!(
+        SetFormat(32, false),
+        CycleSetting(0xE, 1),
+        Key::FormatSelect,
+        Key::HexBase,
+        Key::HexBase,
+        Key::Digit(1), Key::Digit(2), Key::Digit(3),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "x123");
+}
+
+#[test]
+fn test_binary_grouped_by_8() {
+    let hal = run_os(&keys!(
+        SetFormat(32, false),
+        CycleSetting(4, 3),
+        CycleSetting(0xF, 2),
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Key::HexBase,
+        Key::Digit(0xD), Key::Digit(0xE), Key::Digit(0xA), Key::Digit(0xD),
+        Key::Digit(0xB), Key::Digit(0xE), Key::Digit(0xE), Key::Digit(0xF),
+        Key::Exe,
+    ));
+
+    // BIG MODE breaks lines between groups, rather than splitting one
+    assert_eq!(hal.snapshot(), [
+        "+--------------------+",
+        "|U32 === BIG ========|",
+        "|b11011110_10101101_ |",
+        "|10111110_11101111   |",
+        "|                    |",
+        "+--------------------+",
+    ].join("\n"));
+}
+
+#[test]
+fn test_binary_grouped_into_nibbles() {
+    let hal = run_os(&keys!(
+        CycleSetting(4, 4),
+        CycleSetting(0xF, 1),
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Key::BinaryBase,
+        Number(11011101),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "b1101 1101");
+
+    // A group which ends right at the edge of the display stays on its line
+    let hal = run_os(&keys!(
+        SetFormat(32, false),
+        CycleSetting(4, 4),
+        CycleSetting(0xF, 1),
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Key::HexBase,
+        Key::Digit(0xD), Key::Digit(0xE), Key::Digit(0xA), Key::Digit(0xD),
+        Key::Digit(0xB), Key::Digit(0xE), Key::Digit(0xE), Key::Digit(0xF),
+        Key::Exe,
+    ));
+    assert_eq!(hal.snapshot(), [
+        "+--------------------+",
+        "|U32 === BIG ========|",
+        "|b1101 1110 1010 1101|",
+        "|1011 1110 1110 1111 |",
+        "|                    |",
+        "+--------------------+",
+    ].join("\n"));
+}
+
+#[test]
+fn test_xor_mask() {
+    let hal = run_os(&keys!(
+        Key::FormatSelect,
+        Key::HexBase,
+        Key::HexBase,
+        Key::Digit(0),
+        Key::Digit(0xF),
+        Key::Exe,
+        Shifted(Key::HexBase),
+        Key::HexBase,
+        Key::Digit(3),
+        Key::Digit(0xC),
+    ));
+    assert_eq!(hal.expression(), "x3C");
+    assert_eq!(hal.result(), "MASK?");
+
+    let hal = run_os(&keys!(
+        Key::FormatSelect,
+        Key::HexBase,
+        Key::HexBase,
+        Key::Digit(0),
+        Key::Digit(0xF),
+        Key::Exe,
+        Shifted(Key::HexBase),
+        Key::HexBase,
+        Key::Digit(3),
+        Key::Digit(0xC),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), format!("x{:X}", 0x0F ^ 0x3C));
+
+    // The mask only applies to the next evaluation
+    let hal = run_os(&keys!(
+        Number(15),
+        Key::Exe,
+        Shifted(Key::HexBase),
+        Number(60),
+        Key::Exe,
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "60");
+}
+
+#[test]
+fn test_copy_result() {
+    let hal = run_os(&keys!(
+        Number(12),
+        Key::Exe,
+        Key::FormatSelect,
+        Key::HexBase,
+        Shifted(Key::Menu),
+        Key::Digit(3),
+    ));
+    assert_eq!(hal.clipboard, Some("xC".to_string()));
+    assert_eq!(hal.result(), "xC");
+
+    // Nothing to copy before evaluating
+    let hal = run_os(&keys!(
+        Number(12),
+        Shifted(Key::Menu),
+        Key::Digit(3),
+    ));
+    assert_eq!(hal.clipboard, None);
+}
+
+#[test]
+fn test_copy_expression() {
+    let hal = run_os(&keys!(
+        Key::HexBase,
+        Key::Digit(0xF),
+        Key::Digit(0xF),
+        Key::Add,
+        Key::BinaryBase,
+        Number(101),
+        Key::Multiply,
+        Number(3),
+        Key::Exe,
+        Shifted(Key::Menu),
+        Key::Digit(4),
+    ));
+    assert_eq!(hal.expression(), "xFF+b101*3");
+    assert_eq!(hal.clipboard, Some(hal.expression()));
+
+    // Nothing to copy from an empty expression
+    let hal = run_os(&keys!(
+        Shifted(Key::Menu),
+        Key::Digit(4),
+    ));
+    assert_eq!(hal.clipboard, None);
+}
+
+#[test]
+fn test_all_bases() {
+    let hal = run_os(&keys!(Number(255), Key::Exe, Key::FormatSelect, Key::Exe));
+    assert_eq!(hal.display_line(0).trim(), "All bases");
+    assert_eq!(hal.display_line(1), "                 255");
+    assert_eq!(hal.display_line(2), "                 xFF");
+    assert_eq!(hal.display_line(3), "           b11111111");
+
+    // Binary is cut off if it's too long
+    let hal = run_os(&keys!(SetFormat(32, false), Number(4000000000), Key::Exe, Key::FormatSelect, Key::Exe));
+    assert_eq!(hal.display_line(3), "b111011100110101100>");
+
+    // Going back keeps the chosen output base
+    let hal = run_os(&keys!(Number(255), Key::Exe, Key::FormatSelect, Key::Exe, Key::Exe));
+    assert_eq!(hal.result(), "255");
+}
+
+#[test]
+fn test_bit_view() {
+    let hal = run_os(&keys!(
+        SetFormat(8, false),
+        Number(0),
+        Key::Exe,
+        Shifted(Key::Digit(0xB)),
+        Key::Digit(1),
+    ));
+    assert_eq!(hal.display_line(0), "Bit 0             U8");
+    assert_eq!(hal.display_line(1), "            00000001");
+    assert_eq!(hal.display_line(2), "                   ^");
+    assert_eq!(hal.display_line(3), "                   1");
+
+    // The value is recomputed as more bits are toggled
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        Number(0),
+        Key::Exe,
+        Shifted(Key::Digit(0xB)),
+        Key::Digit(1),
+        Key::Left,
+        Key::Left,
+        Key::Left,
+        Key::Left,
+        Key::Left,
+        Key::Left,
+        Key::Left,
+        Key::Digit(1),
+    ));
+    assert_eq!(hal.display_line(0), "Bit 7             S8");
+    assert_eq!(hal.display_line(1), "            10000001");
+    assert_eq!(hal.display_line(2), "            ^       ");
+    assert_eq!(hal.display_line(3), "                -127");
+
+    // Leaving goes back to the unchanged result
+    let hal = run_os(&keys!(
+        Number(0),
+        Key::Exe,
+        Shifted(Key::Digit(0xB)),
+        Key::Digit(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "0");
+}
+
+#[test]
+fn test_history() {
+    let calculations = keys!(
+        Number(12), Key::Add, Number(3), Key::Exe,
+        Shifted(Key::Delete),
+        Number(7), Key::Multiply, Number(6), Key::Exe,
+        Shifted(Key::Delete),
+    );
+
+    // The most recent calculation is shown first
+    let hal = run_os(&[calculations.clone(), keys!(Shifted(Key::Menu), Key::Digit(5))].concat());
+    assert_eq!(hal.display_line(0), "History          2/2");
+    assert_eq!(hal.display_line(2), "7*6                 ");
+    assert_eq!(hal.display_line(3), "                  42");
+
+    // Left goes further back, and stops at the oldest
+    let hal = run_os(&[calculations.clone(), keys!(Shifted(Key::Menu), Key::Digit(5), Key::Left, Key::Left)].concat());
+    assert_eq!(hal.display_line(0), "History          1/2");
+    assert_eq!(hal.display_line(2), "12+3                ");
+    assert_eq!(hal.display_line(3), "                  15");
+
+    let hal = run_os(&[calculations.clone(), keys!(Shifted(Key::Menu), Key::Digit(5), Key::Left, Key::Right)].concat());
+    assert_eq!(hal.display_line(2), "7*6                 ");
+
+    // EXE loads the expression back in, ready to be edited
+    let hal = run_os(&[
+        calculations.clone(),
+        keys!(Shifted(Key::Menu), Key::Digit(5), Key::Left, Key::Exe, Key::Add, Number(1), Key::Exe),
+    ].concat());
+    assert_eq!(hal.expression(), "12+3+1");
+    assert_eq!(hal.result(), "16");
+
+    let hal = run_os(&keys!(Shifted(Key::Menu), Key::Digit(5)));
+    assert_eq!(hal.display_line(2), "Nothing yet!        ");
+}
+
+#[test]
+fn test_scroll_margin() {
+    let long_expression = keys!(Number(1234567890), Number(1234567890), Number(1234567890));
+
+    // Typing to the end leaves a blank space after the cursor, but no more
+    let hal = run_os(&long_expression);
+    assert_eq!(hal.display_line(2), "2345678901234567890 ");
+
+    // Moving left keeps two glyphs visible before the cursor
+    let hal = run_os(&[long_expression.clone(), vec![Key::Left; 17]].concat());
+    assert_eq!(hal.display_line(1).find('\\'), Some(2));
+    assert_eq!(hal.display_line(2), "12345678901234567890");
+
+    // ...and moving right again keeps two visible after it
+    let hal = run_os(&[long_expression.clone(), vec![Key::Left; 30], vec![Key::Right; 18]].concat());
+    assert_eq!(hal.display_line(1).find('/'), Some(17));
+    assert_eq!(hal.display_line(2), "23456789012345678901");
+
+    // The margin can be changed, including to 0 to only scroll at the edge
+    let hal = run_os(&[keys!(CycleSetting(5, 3)), long_expression, vec![Key::Left; 18]].concat());
+    assert_eq!(hal.display_line(1).find('\\'), Some(0));
+    assert_eq!(hal.display_line(2), "2345678901234567890 ");
+}
+
+#[test]
+fn test_interactive() {
+    // Keep doubling until the result overflows, counting how many times it took
+    let doublings = Rc::new(RefCell::new(0));
+    let doublings_ref = doublings.clone();
+    let hal = run_os_interactive(&keys!(SetFormat(8, false), Number(3), Key::Exe), move |display| {
+        if display.overflow() {
+            vec![]
+        } else {
+            *doublings_ref.borrow_mut() += 1;
+            keys!(Key::Multiply, Number(2), Key::Exe)
+        }
+    });
+
+    assert_eq!(*doublings.borrow(), 7);
+    assert_eq!(hal.expression(), "3*2*2*2*2*2*2*2");
+    assert_eq!(hal.result(), "128");
+}
+
+#[test]
+fn test_gray_code() {
+    let hal = run_os(&keys!(
+        CycleSetting(6, 1),
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Number(5),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "b111");
+
+    // Shown as unsigned, even on a signed data type
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        CycleSetting(6, 1),
+        Key::Subtract,
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "128");
+}
+
+#[test]
+fn test_gray_code_variable() {
+    // The value is stored, rather than the Gray code which is shown
+    let hal = run_os(&keys!(
+        CycleSetting(6, 1),
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Number(10),
+        Key::Exe,
+        Shifted(Key::Variable),
+        Key::Digit(1),
+        Shifted(Key::Delete),
+        CycleSetting(6, 1),
+        Key::FormatSelect,
+        Key::FormatSelect,
+        Key::Variable,
+        Key::Digit(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "10");
+
+    // Negative values survive too, despite Gray codes being shown as unsigned
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        CycleSetting(6, 1),
+        Key::Subtract,
+        Number(5),
+        Key::Exe,
+        Shifted(Key::Variable),
+        Key::Digit(1),
+        Shifted(Key::Delete),
+        CycleSetting(6, 1),
+        Key::Variable,
+        Key::Digit(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "-5");
+    assert!(!hal.overflow());
+}
+
+#[test]
+fn test_sleep() {
+    let hal = run_os(&keys!(
+        Number(12),
+        Key::Add,
+        Number(3),
+        Key::Exe,
+        Key::Sleep,
+    ));
+    // The test display isn't cleared by sleeping, so the OS' drawing should be untouched
+    assert_eq!(hal.expression(), "12+3");
+    assert_eq!(hal.result(), "15");
+
+    // Waking redraws everything, with the expression cleared
+    let hal = run_os(&keys!(
+        Number(12),
+        Key::Add,
+        Number(3),
+        Key::Exe,
+        Key::Sleep,
+        Number(4),
+    ));
+    assert_eq!(hal.format(), "U32");
+    assert_eq!(hal.expression(), "4");
+    assert_eq!(hal.result(), "");
+
+    // Sleeping from a menu returns to the calculator
+    let hal = run_os(&keys!(
+        Number(12),
+        Shifted(Key::Menu),
+        Key::Sleep,
+        Number(4),
+    ));
+    assert_eq!(hal.format(), "U32");
+    assert_eq!(hal.expression(), "4");
+}
+
+#[test]
+fn test_out_of_memory() {
+    let keys = keys!(Number(12), Key::Add, Number(3), Key::Exe);
+    let required = estimate_memory_usage(32, 4);
+
+    // Exactly enough memory is fine
+    let hal = run_os_with_free_memory(&keys, required);
+    assert_eq!(hal.result(), "15");
+
+    // Any less is refused
+    let hal = run_os_with_free_memory(&keys, required - 1);
+    assert_eq!(hal.result(), "out of memory");
+
+    // Narrower data types need less memory
+    let hal = run_os_with_free_memory(&keys!(
+        SetFormat(8, false),
+        Number(12), Key::Add, Number(3), Key::Exe,
+    ), required - 1);
+    assert_eq!(hal.result(), "15");
+}
+
+#[test]
+fn test_display_prefs() {
+    let mut hal = TestHal::new(&[]);
+    let mut app = CalculatorApplication::with_display_prefs(&mut hal, DisplayPrefs {
+        output_format: Base::Hexadecimal,
+        signed_result: Some(true),
+    });
+    block_on(async {
+        for key in keys!(Number(0), Key::Subtract, Number(1), Key::Exe) {
+            app.process_input_and_redraw(key).await;
+        }
+    });
+    drop(app);
+    assert_eq!(hal.format(), "U32>S");
+    assert_eq!(hal.result(), "x-1");
+}
+
+#[test]
+fn test_display_prefs_persisted() {
+    // Changing the display mode stores it...
+    let hal = run_os(&keys!(
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Shifted(Key::FormatSelect),
+        Key::Add,
+        Key::Exe,
+    ));
+    let stored = hal.storage.get(DisplayPrefs::STORAGE_KEY).unwrap();
+    assert_eq!(DisplayPrefs::from_bytes(stored), Some(DisplayPrefs {
+        output_format: Base::Binary,
+        signed_result: Some(false),
+    }));
+
+    // ...and it's restored when starting up again
+    let mut hal = TestHal::new(&[]);
+    hal.storage.insert(DisplayPrefs::STORAGE_KEY.to_string(), stored.clone());
+    let mut app = CalculatorApplication::new(&mut hal);
+    block_on(async {
+        for key in keys!(Number(5), Key::Exe) {
+            app.process_input_and_redraw(key).await;
+        }
+    });
+    drop(app);
+    assert_eq!(hal.result(), "b101");
+}
+
+#[test]
+fn test_negate_result() {
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        Number(5),
+        Key::Exe,
+        Shifted(Key::Subtract),
+    ));
+    assert_eq!(hal.result(), "-5");
+    assert!(!hal.overflow());
+
+    // -128 has no positive counterpart in S8, so it's widened and marked as an overflow
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        Key::Subtract,
+        Number(128),
+        Key::Exe,
+        Shifted(Key::Subtract),
+    ));
+    assert_eq!(hal.result(), "128");
+    assert!(hal.overflow());
+
+    // Negating again returns to the original
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        Key::Subtract,
+        Number(128),
+        Key::Exe,
+        Shifted(Key::Subtract),
+        Shifted(Key::Subtract),
+    ));
+    assert_eq!(hal.result(), "-128");
+    assert!(!hal.overflow());
+
+    // Unsigned negation wraps
+    let hal = run_os(&keys!(
+        SetFormat(8, false),
+        Number(1),
+        Key::Exe,
+        Shifted(Key::Subtract),
+    ));
+    assert_eq!(hal.result(), "255");
+    assert!(hal.overflow());
+}
+
+#[test]
+fn test_jump_to_operator() {
+    let expression = keys!(Number(12), Key::Multiply, Number(3), Key::Add, Number(45), Key::Subtract, Number(6));
+
+    // Jumping forwards from the start skips to the `*`, then to the `+`
+    let hal = run_os(&[
+        expression.clone(),
+        vec![Key::Left; 9],
+        keys!(Shifted(Key::Digit(6)), Shifted(Key::Digit(6)), Number(7), Key::Exe),
+    ].concat());
+    assert_eq!(hal.expression(), "12*37+45-6");
+    assert_eq!(hal.result(), "483");
+
+    // Jumping backwards from the end goes to the `-`
+    let hal = run_os(&[
+        expression.clone(),
+        keys!(Shifted(Key::Digit(4)), Number(7), Key::Exe),
+    ].concat());
+    assert_eq!(hal.expression(), "12*3+457-6");
+
+    // There's nothing to jump to beyond the first or last operator
+    let hal = run_os(&[
+        expression,
+        keys!(Shifted(Key::Digit(6)), Number(7)),
+    ].concat());
+    assert_eq!(hal.expression(), "12*3+45-67");
+}
+
+#[test]
+fn test_divide_by_zero_setting() {
+    let hal = run_os(&keys!(Number(5), Key::Divide, Number(0), Key::Exe));
+    assert_eq!(hal.result(), "division by zero");
+
+    // The error is marked above the operator
+    let hal = run_os(&keys!(Number(12), Key::Add, Number(5), Key::Divide, Number(0), Key::Exe));
+    assert_eq!(hal.display_line(1).trim_end(), "    !\\/");
+
+    let hal = run_os(&keys!(CycleSetting(7, 2), Number(5), Key::Divide, Number(0), Key::Exe));
+    assert_eq!(hal.result(), "4294967295");
+    assert!(hal.overflow());
+}
+
+#[test]
+fn test_modulo() {
+    let hal = run_os(&keys!(Number(17), Key::Modulo, Number(5), Key::Exe));
+    assert_eq!(hal.expression(), "17%5");
+    assert_eq!(hal.result(), "2");
+    assert!(!hal.overflow());
+
+    // Same precedence as multiplication, and available with Shift > `/`
+    let hal = run_os(&keys!(Number(2), Key::Multiply, Number(17), Shifted(Key::Divide), Number(5), Key::Exe));
+    assert_eq!(hal.expression(), "2*17%5");
+    assert_eq!(hal.result(), "4");
+
+    // The remainder takes the sign of the left-hand side
+    let hal = run_os(&keys!(SetFormat(8, true), Key::Subtract, Number(17), Key::Modulo, Number(5), Key::Exe));
+    assert_eq!(hal.result(), "-2");
+
+    let hal = run_os(&keys!(Number(5), Key::Modulo, Number(0), Key::Exe));
+    assert_eq!(hal.result(), "division by zero");
+}
+
+#[test]
+fn test_power() {
+    let hal = run_os(&keys!(SetFormat(16, false), Number(2), Key::Power, Number(10), Key::Exe));
+    assert_eq!(hal.expression(), "2^10");
+    assert_eq!(hal.result(), "1024");
+    assert!(!hal.overflow());
+
+    // Also available with Shift > 8
+    let hal = run_os(&keys!(SetFormat(16, true), Key::Subtract, Number(2), Shifted(Key::Digit(8)), Number(2), Key::Exe));
+    assert_eq!(hal.expression(), "-2^2");
+    assert_eq!(hal.result(), "-4");
+}
+
+#[test]
+fn test_answer() {
+    let calculation = keys!(Number(20), Key::Multiply, Number(3), Key::Exe);
+
+    // The previous result survives clearing the expression
+    let hal = run_os(&[
+        calculation.clone(),
+        keys!(Shifted(Key::Delete), Key::Answer, Key::Add, Number(1), Key::Exe),
+    ].concat());
+    assert_eq!(hal.expression(), "@+1");
+    assert_eq!(hal.result(), "61");
+
+    // Each evaluation updates it, so it can be chained
+    let hal = run_os(&[
+        calculation.clone(),
+        keys!(Shifted(Key::Delete), Shifted(Key::Digit(5)), Key::Add, Number(1), Key::Exe),
+        keys!(Shifted(Key::Delete), Shifted(Key::Digit(5)), Key::Multiply, Number(2), Key::Exe),
+    ].concat());
+    assert_eq!(hal.result(), "122");
+
+    // Before anything is evaluated, or after the data type changes, it's zero
+    let hal = run_os(&keys!(Key::Answer, Key::Add, Number(1), Key::Exe));
+    assert_eq!(hal.result(), "1");
+    let hal = run_os(&[
+        calculation.clone(),
+        keys!(SetFormat(16, false), Shifted(Key::Delete), Key::Answer, Key::Add, Number(1), Key::Exe),
+    ].concat());
+    assert_eq!(hal.result(), "1");
+
+    // An error doesn't replace it
+    let hal = run_os(&[
+        calculation,
+        keys!(Shifted(Key::Delete), Key::Add, Key::Exe),
+        keys!(Shifted(Key::Delete), Key::Answer, Key::Exe),
+    ].concat());
+    assert_eq!(hal.result(), "60");
+}
+
+#[test]
+fn test_shift() {
+    let hal = run_os(&keys!(Number(1), Key::ShiftLeft, Number(4), Key::Exe));
+    assert_eq!(hal.expression(), "1«4");
+    assert_eq!(hal.result(), "16");
+    assert!(!hal.overflow());
+
+    let hal = run_os(&keys!(
+        SetFormat(8, false),
+        Key::Digit(0xF), Key::Digit(0xF), Shifted(Key::Digit(9)), Number(4),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "xFF»4");
+    assert_eq!(hal.result(), "15");
+
+    // Bits shifted out of the top are an overflow
+    let hal = run_os(&keys!(SetFormat(8, false), Number(3), Shifted(Key::Digit(7)), Number(7), Key::Exe));
+    assert_eq!(hal.result(), "128");
+    assert!(hal.overflow());
+}
+
+#[test]
+fn test_bitwise() {
+    let hal = run_os(&keys!(
+        Key::HexBase, Key::Digit(0xF), Key::Digit(0),
+        Key::BitOr,
+        Key::HexBase, Key::Digit(0), Key::Digit(0xF),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "xF0|x0F");
+    assert_eq!(hal.result(), "255");
+    assert!(!hal.overflow());
+
+    // Shift > 1/2/3 insert AND/OR/XOR, and bind less tightly than arithmetic
+    let hal = run_os(&keys!(
+        Number(12), Shifted(Key::Digit(1)), Number(10),
+        Shifted(Key::Digit(3)), Number(1), Key::Add, Number(2),
+        Shifted(Key::Digit(2)), Number(16),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "12&10⊕1+2|16");
+    assert_eq!(hal.result(), "27");
+}
+
+#[test]
+fn test_signedness_override() {
+    let compute = keys!(SetFormat(8, false), Number(200), Key::Exe);
+
+    // No override to begin with
+    let hal = run_os(&compute);
+    assert_eq!(hal.format(), "U8");
+    assert_eq!(hal.result(), "200");
+
+    // The menu marks the current choice
+    let hal = run_os(&[compute.clone(), keys!(Shifted(Key::FormatSelect))].concat());
+    assert_eq!(hal.display_line(0), "Ans signedness ovrd.");
+    assert_eq!(hal.display_line(1), "DEL) None     <     ");
+
+    // Overriding to signed reinterprets the result
+    let hal = run_os(&[
+        compute.clone(),
+        keys!(Shifted(Key::FormatSelect), Key::Subtract),
+    ].concat());
+    assert_eq!(hal.display_line(2), "  -) Signed   <     ");
+    let hal = run_os(&[
+        compute.clone(),
+        keys!(Shifted(Key::FormatSelect), Key::Subtract, Key::Exe, Key::Exe),
+    ].concat());
+    assert_eq!(hal.format(), "U8>S");
+    assert_eq!(hal.result(), "-56");
+
+    // Overriding a signed type to unsigned does the opposite
+    let hal = run_os(&keys!(
+        SetFormat(8, true),
+        Key::Subtract, Number(56), Key::Exe,
+        Shifted(Key::FormatSelect), Key::Add, Key::Exe,
+        Key::Exe,
+    ));
+    assert_eq!(hal.format(), "S8>U");
+    assert_eq!(hal.result(), "200");
+
+    // Removing the override goes back to the data type's signedness
+    let hal = run_os(&[
+        compute,
+        keys!(
+            Shifted(Key::FormatSelect), Key::Subtract, Key::Exe,
+            Shifted(Key::FormatSelect), Key::Delete, Key::Exe,
+            Key::Exe,
+        ),
+    ].concat());
+    assert_eq!(hal.format(), "U8");
+    assert_eq!(hal.result(), "200");
+}
+
+#[test]
+fn test_collapse_negations() {
+    // Off by default
+    let hal = run_os(&keys!(Key::Subtract, Key::Subtract, Number(5), Key::Exe));
+    assert_eq!(hal.expression(), "--5");
+    assert_eq!(hal.result(), "5");
+
+    let hal = run_os(&keys!(CycleSetting(8, 1), Key::Subtract, Key::Subtract, Number(5), Key::Exe));
+    assert_eq!(hal.expression(), "5");
+    assert_eq!(hal.result(), "5");
+
+    // Odd runs keep one negation, and binary subtraction is untouched
+    let hal = run_os(&keys!(
+        CycleSetting(8, 1),
+        SetFormat(8, true),
+        Number(2), Key::Subtract, Key::Subtract, Key::Subtract, Key::Subtract, Number(3),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "2--3");
+    assert_eq!(hal.result(), "5");
+}
+
+#[test]
+fn test_raw_based_literals() {
+    let hex_ff = keys!(SetFormat(8, true), Key::HexBase, Key::Digit(0xF), Key::Digit(0xF), Key::Exe);
+
+    // Normally, this is 255, which overflows
+    let hal = run_os(&hex_ff);
+    assert!(hal.overflow());
+
+    // As a raw bit pattern, it's -1
+    let hal = run_os(&[keys!(CycleSetting(9, 1)), hex_ff].concat());
+    assert_eq!(hal.result(), "-1");
+    assert!(!hal.overflow());
+    assert!(!hal.display_line(2).contains('!'));
+
+    // Patterns which are too wide still overflow
+    let hal = run_os(&keys!(
+        CycleSetting(9, 1),
+        SetFormat(8, true),
+        Key::HexBase, Key::Digit(1), Key::Digit(0xF), Key::Digit(0xF),
+        Key::Exe,
+    ));
+    assert!(hal.overflow());
+
+    // Decimal numbers are unaffected
+    let hal = run_os(&keys!(CycleSetting(9, 1), SetFormat(8, true), Number(255), Key::Exe));
+    assert!(hal.overflow());
+}
+
+#[test]
+fn test_cycle_output_base() {
+    let result = keys!(Number(10), Key::Exe);
+
+    let hal = run_os(&[result.clone(), keys!(Shifted(Key::BinaryBase))].concat());
+    assert_eq!(hal.result(), "xA");
+
+    let hal = run_os(&[result.clone(), keys!(Shifted(Key::BinaryBase), Shifted(Key::BinaryBase))].concat());
+    assert_eq!(hal.result(), "b1010");
+
+    let hal = run_os(&[result, keys!(
+        Shifted(Key::BinaryBase), Shifted(Key::BinaryBase), Shifted(Key::BinaryBase)
+    )].concat());
+    assert_eq!(hal.result(), "10");
+}
+
+#[test]
+fn test_xor_mask_narrower_type() {
+    // A 16-bit mask source which doesn't fit in 8 bits is flagged as an overflow
+    let hal = run_os(&keys!(
+        SetFormat(16, false),
+        Number(300),
+        Key::Exe,
+        Shifted(Key::HexBase),
+        SetFormat(8, false),
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), format!("{}", (300 & 0xFF) ^ 1));
+    assert!(hal.overflow());
+
+    // ...but one which fits isn't
+    let hal = run_os(&keys!(
+        SetFormat(16, false),
+        Number(200),
+        Key::Exe,
+        Shifted(Key::HexBase),
+        SetFormat(8, false),
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "201");
+    assert!(!hal.overflow());
+}
+
+#[test]
+fn test_undo() {
+    // Undoing an insertion
+    let hal = run_os(&keys!(Number(12), Key::Add, Number(3), Shifted(Key::Left)));
+    assert_eq!(hal.expression(), "12+");
+
+    // Undoing deletions, one at a time
+    let hal = run_os(&keys!(
+        Number(12), Key::Add, Number(34),
+        Key::Left, Key::Delete, Key::Delete,
+        Shifted(Key::Left),
+    ));
+    assert_eq!(hal.expression(), "12+4");
+    let hal = run_os(&keys!(
+        Number(12), Key::Add, Number(34),
+        Key::Left, Key::Delete, Key::Delete,
+        Shifted(Key::Left), Shifted(Key::Left), Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "12+34");
+    assert_eq!(hal.result(), "46");
+
+    // Undoing clearing everything
+    let hal = run_os(&keys!(Number(12), Key::Add, Number(34), Shifted(Key::Delete), Shifted(Key::Left)));
+    assert_eq!(hal.expression(), "12+34");
+
+    // Undoing too many times just leaves the expression empty
+    let hal = run_os(&[keys!(Number(12)), [Key::Shift, Key::Left].repeat(5), keys!(Number(3))].concat());
+    assert_eq!(hal.expression(), "3");
+}
+
+#[test]
+fn test_redo() {
+    // Redoing restores the state after the edit, including the cursor
+    let hal = run_os(&keys!(
+        Number(12), Key::Add, Number(34),
+        Key::Left, Key::Left, Key::Delete,
+        Shifted(Key::Left), Shifted(Key::Add),
+        Number(5), Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "12534");
+    assert_eq!(hal.result(), "12534");
+
+    // Several undos can be redone in order
+    let hal = run_os(&keys!(
+        Number(1), Key::Add, Number(2),
+        Shifted(Key::Left), Shifted(Key::Left), Shifted(Key::Left),
+        Shifted(Key::Add), Shifted(Key::Add),
+    ));
+    assert_eq!(hal.expression(), "1+");
+
+    // Redone edits can be undone again
+    let hal = run_os(&keys!(
+        Number(1), Key::Add, Number(2),
+        Shifted(Key::Left), Shifted(Key::Add), Shifted(Key::Left),
+    ));
+    assert_eq!(hal.expression(), "1+");
+
+    // A new edit means there's nothing to redo
+    let hal = run_os(&keys!(
+        Number(1), Key::Add, Number(2),
+        Shifted(Key::Left), Number(3), Shifted(Key::Add),
+    ));
+    assert_eq!(hal.expression(), "1+3");
+}
+
+#[test]
+fn test_undo_depth_limited() {
+    let mut keys = vec![];
+    for _ in 0..(CalculatorApplication::<TestHal>::MAX_UNDO_DEPTH + 5) {
+        keys.extend(keys!(Number(1)));
+    }
+    keys.extend([Key::Shift, Key::Left].repeat(100));
+    keys.push(Key::Exe);
+
+    // Only the most recent edits can be undone
+    let hal = run_os(&keys);
+    assert_eq!(hal.result(), "11111");
+}
+
+#[test]
+fn test_clear_result_on_move() {
+    // By default, moving the cursor keeps the result
+    let hal = run_os(&keys!(Number(12), Key::Add, Number(3), Key::Exe, Key::Left, Key::Left, Key::Right));
+    assert_eq!(hal.result(), "15");
+    let hal = run_os(&keys!(Number(12), Key::Add, Number(3), Key::Exe, Shifted(Key::Digit(4))));
+    assert_eq!(hal.result(), "15");
+
+    // Editing still clears it
+    let hal = run_os(&keys!(Number(12), Key::Add, Number(3), Key::Exe, Key::Left, Number(4)));
+    assert_eq!(hal.result(), "");
+
+    // The setting brings back the old behaviour
+    let hal = run_os(&keys!(
+        CycleSetting(0xA, 1),
+        Number(12), Key::Add, Number(3), Key::Exe, Key::Left,
+    ));
+    assert_eq!(hal.result(), "");
+}
+
+#[test]
+fn test_compact_layout() {
+    let compact = keys!(CycleSetting(0xB, 1));
+
+    // The expression moves up into the header row, and the data type moves next to the result
+    let hal = run_os(&[compact.clone(), keys!(Number(12), Key::Add, Number(3), Key::Exe)].concat());
+    assert!(!hal.display_line(0).contains('='));
+    assert_eq!(hal.display_line(1).trim(), "12+3");
+    assert_eq!(hal.display_line(2).trim(), "U32");
+    assert_eq!(hal.display_line(3).trim(), "15");
+
+    // Overflow is still shown
+    let hal = run_os(&[compact.clone(), keys!(SetFormat(8, false), Number(255), Key::Add, Number(1), Key::Exe)].concat());
+    assert_eq!(hal.display_line(2).trim(), "U8 OVER");
+    assert_eq!(hal.display_line(3).trim(), "0");
+
+    // Results too wide for one row use both, rather than covering the expression
+    let hal = run_os(&[compact, keys!(Number(4000000000), Key::Exe, Key::FormatSelect, Key::BinaryBase)].concat());
+    assert_eq!(hal.display_line(1).trim(), "4000000000");
+    let area = hal.display_line(2) + &hal.display_line(3);
+    assert!(area.starts_with("U32 "));
+    assert!(area.ends_with("b11101110011010110010100000000000"));
+}
+
+#[test]
+fn test_zero_pad() {
+    let hal = run_os(&keys!(
+        SetFormat(8, false),
+        Key::Menu,
+        Key::Multiply,
+        Key::Exe,
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Number(10),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "b00001010");
+
+    // Hex pads to whole digits, rounding up
+    let hal = run_os(&keys!(
+        SetFormat(10, false),
+        Key::Menu,
+        Key::Multiply,
+        Key::Exe,
+        Key::FormatSelect,
+        Key::HexBase,
+        Number(10),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "x00A");
+
+    // Pressing it again turns padding back off
+    let hal = run_os(&keys!(
+        SetFormat(8, false),
+        Key::Menu,
+        Key::Multiply,
+        Key::Multiply,
+        Key::Exe,
+        Key::FormatSelect,
+        Key::BinaryBase,
+        Number(10),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "b1010");
+}
+
+#[test]
+fn test_format_menu_preview() {
+    let expression = keys!(SetFormat(8, false), Number(100), Key::Add, Number(100));
+
+    // The preview uses the data type currently being chosen
+    let hal = run_os(&[expression.clone(), keys!(Key::Menu)].concat());
+    assert_eq!(hal.display_line(1), "Bits: 8          200");
+
+    let hal = run_os(&[expression.clone(), keys!(Key::Menu, Key::Subtract)].concat());
+    assert_eq!(hal.display_line(1), "Bits: 8     OVER -56");
+
+    let hal = run_os(&[expression.clone(), keys!(Key::Menu, Key::Subtract, Key::Add)].concat());
+    assert_eq!(hal.display_line(1), "Bits: 8          200");
+
+    let hal = run_os(&[expression.clone(), keys!(Key::Menu, Key::Digit(6))].concat());
+    assert_eq!(hal.display_line(1), "Bits: 86         200");
+
+    // Nothing is previewed for an incomplete expression
+    let hal = run_os(&[expression, keys!(Key::Add, Key::Menu)].concat());
+    assert_eq!(hal.display_line(1).trim(), "Bits: 8");
+}
+
+#[test]
+fn test_held_digit_repeats() {
+    // One press, then a repeat at 500ms and every 100ms after that
+    let hal = run_os(&keys!(Held(Key::Digit(1), Duration::from_millis(850))));
+    assert_eq!(hal.expression(), "11111");
+
+    // Repeated digits are checked for overflow as they're entered
+    let hal = run_os(&keys!(SetFormat(8, false), Key::HexBase, Held(Key::Digit(0xF), Duration::from_millis(550))));
+    assert_eq!(hal.expression(), "xFF");
+    assert!(!hal.display_line(1).contains('!'));
+    let hal = run_os(&keys!(SetFormat(8, false), Key::HexBase, Held(Key::Digit(0xF), Duration::from_millis(650))));
+    assert_eq!(hal.expression(), "xFFF");
+    assert!(hal.display_line(1).contains('!'));
+
+    // Other keys don't repeat
+    let hal = run_os(&keys!(Number(12), Held(Key::Delete, Duration::from_millis(1000))));
+    assert_eq!(hal.expression(), "1");
+
+    // A shifted key is entered once, rather than repeating unshifted
+    let hal = run_os(&keys!(Number(2), Held(Shifted(Key::Digit(8)), Duration::from_millis(850)), Number(3)));
+    assert_eq!(hal.expression(), "2^3");
+    let hal = run_os(&keys!(Held(Shifted(Key::Digit(0)), Duration::from_millis(850))));
+    assert_eq!(hal.expression(), "()");
+
+    // ...and doesn't keep acting on a screen it opens
+    let hal = run_os(&keys!(Number(0), Key::Exe, Held(Shifted(Key::Digit(0xB)), Duration::from_millis(850))));
+    assert_eq!(hal.display_line(3), "                   0");
+}
+
+#[test]
+fn test_held_digit_in_menu() {
+    // Holding a digit in a menu acts on it only once
+    let hal = run_os(&keys!(
+        Shifted(Key::Menu),
+        Key::Digit(2),
+        Held(Key::Digit(6), Duration::from_millis(950)),
+        Key::Menu,
+        Number(5),
+        Key::Exe,
+    ));
+    assert_eq!(hal.result(), "7");
+
+    let hal = run_os(&keys!(Key::Menu, Key::Delete, Key::Delete, Held(Key::Digit(1), Duration::from_millis(850))));
+    assert!(hal.display_line(1).starts_with("Bits: 1 "));
+
+    // Repeats are still ignored after leaving the menu, until another digit is pressed
+    let hal = run_os(&keys!(
+        Key::FormatSelect,
+        Held(Key::Digit(8), Duration::from_millis(850)),
+        Number(1),
+        Key::Exe,
+    ));
+    assert_eq!(hal.expression(), "1");
+}
+
+#[test]
+fn test_opposite_signedness() {
+    let other_sign = keys!(CycleSetting(0xC, 1));
+
+    // Both interpretations are shown when the top bit is set
+    let hal = run_os(&[other_sign.clone(), keys!(SetFormat(8, false), Key::HexBase, Key::Digit(0xC), Key::Digit(8), Key::Exe)].concat());
+    assert_eq!(hal.display_line(3), "S:-56            200");
+
+    let hal = run_os(&[other_sign.clone(), keys!(SetFormat(8, true), Number(0), Key::Subtract, Number(56), Key::Exe)].concat());
+    assert_eq!(hal.display_line(3), "U:200            -56");
+
+    // Otherwise they'd be the same, so only one is shown
+    let hal = run_os(&[other_sign.clone(), keys!(SetFormat(8, false), Number(100), Key::Exe)].concat());
+    assert_eq!(hal.display_line(3).trim(), "100");
+
+    // The setting is off by default
+    let hal = run_os(&keys!(SetFormat(8, false), Key::HexBase, Key::Digit(0xC), Key::Digit(8), Key::Exe));
+    assert_eq!(hal.display_line(3).trim(), "200");
+}
+
+#[test]
+fn test_operator_first() {
+    let operator_first = keys!(CycleSetting(0xD, 1));
+
+    // Each operand is prompted for in turn
+    let hal = run_os(&[operator_first.clone(), keys!(Key::Add)].concat());
+    assert_eq!(hal.result(), "+ 1st?");
+    let hal = run_os(&[operator_first.clone(), keys!(Key::Add, Number(12), Key::Exe)].concat());
+    assert_eq!(hal.expression(), "");
+    assert_eq!(hal.result(), "+ 2nd?");
+
+    // The expression is assembled and evaluated once both are entered
+    let hal = run_os(&[operator_first.clone(), keys!(Key::Add, Number(12), Key::Exe, Number(30), Key::Exe)].concat());
+    assert_eq!(hal.expression(), "12+30");
+    assert_eq!(hal.result(), "42");
+
+    // Operands with their own operators are kept together
+    let hal = run_os(&[operator_first.clone(), keys!(
+        Key::Multiply, Number(1), Key::Add, Number(2), Key::Exe, Number(3), Key::Exe,
+    )].concat());
+    assert_eq!(hal.expression(), "(1+2)*3");
+    assert_eq!(hal.result(), "9");
+
+    // Operators in a non-empty expression are inserted as normal
+    let hal = run_os(&[operator_first.clone(), keys!(Number(1), Key::Add, Number(2), Key::Exe)].concat());
+    assert_eq!(hal.expression(), "1+2");
+    assert_eq!(hal.result(), "3");
+
+    // Clearing cancels it
+    let hal = run_os(&[operator_first, keys!(Key::Add, Number(12), Key::Exe, Shifted(Key::Delete), Number(5), Key::Exe)].concat());
+    assert_eq!(hal.expression(), "5");
+    assert_eq!(hal.result(), "5");
+}
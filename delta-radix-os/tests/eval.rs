@@ -0,0 +1,336 @@
+use delta_radix_hal::Glyph;
+use delta_radix_os::calc::{backend::{eval::{evaluate, Configuration, DataType, OverflowBehaviour, OperatorGroup, OperatorGroups, Operation, Overflow, EvaluationResult, DivideByZero, estimate_memory_usage, assign_checked}, parse::{Parser, Node, GlyphSpan, ParserError, ParserErrorKind, ConstantOverflowChecker}}, frontend::VariableArray};
+use flex_int::FlexInt;
+
+const U8: DataType = DataType { bits: 8, signed: false };
+const S8: DataType = DataType { bits: 8, signed: true };
+const S16: DataType = DataType { bits: 16, signed: true };
+
+fn config(data_type: DataType) -> Configuration {
+    Configuration {
+        data_type,
+        overflow_behaviour: OverflowBehaviour::Wrap,
+        divide_by_zero: DivideByZero::Error,
+        operators: OperatorGroups::ALL,
+        raw_based_literals: false,
+    }
+}
+
+fn parse(s: &str, variables: &VariableArray, config: Configuration) -> Result<Node, ParserError> {
+    Parser::<FlexInt>::new(&Glyph::from_string(s).unwrap(), variables, config).parse()
+}
+
+fn evaluate_with(s: &str, data_type: DataType) -> EvaluationResult {
+    let config = config(data_type);
+    let node = parse(s, &Default::default(), config).unwrap();
+    evaluate(&node, &config).unwrap()
+}
+
+/// Parses with all operators enabled except for one group, expecting an error.
+fn parse_disabled(s: &str, group: OperatorGroup) -> ParserError {
+    let mut config = config(U8);
+    config.operators.set_enabled(group, false);
+    let Err(error) = parse(s, &Default::default(), config) else { panic!("expected an error") };
+    error
+}
+
+fn evaluate_u8_with(s: &str, divide_by_zero: DivideByZero) -> Result<EvaluationResult, ParserError> {
+    let config = Configuration { divide_by_zero, ..config(U8) };
+    let node = parse(s, &Default::default(), config).unwrap();
+    evaluate(&node, &config)
+}
+
+fn evaluate_u8(s: &str) -> EvaluationResult {
+    evaluate_with(s, U8)
+}
+
+#[test]
+fn test_no_overflow() {
+    let result = evaluate_u8("1+2*3");
+    assert_eq!(result.result, FlexInt::from_int(7, 8));
+    assert!(!result.overflow);
+    assert!(result.overflows.is_empty());
+}
+
+#[test]
+fn test_base_position() {
+    assert_eq!(evaluate_u8("110b").result, FlexInt::from_int(6, 8));
+    assert_eq!(evaluate_u8("b110").result, FlexInt::from_int(6, 8));
+    assert_eq!(evaluate_u8("110b+b110").result, FlexInt::from_int(12, 8));
+}
+
+#[test]
+fn test_recursive_variable() {
+    let config = config(U8);
+    let mut variables: VariableArray = Default::default();
+    variables[0] = Glyph::from_string("?0").unwrap();
+    variables[1] = Glyph::from_string("1+?2").unwrap();
+    variables[2] = Glyph::from_string("2*?1").unwrap();
+    variables[3] = Glyph::from_string("?4+?4").unwrap();
+    variables[4] = Glyph::from_string("5").unwrap();
+
+    let parse = |s: &str| parse(s, &variables, config);
+
+    // Referring to itself, directly or through another variable
+    let Err(error) = parse("?0") else { panic!("expected an error") };
+    assert_eq!(*error.kind(), ParserErrorKind::RecursiveVariable);
+    assert_eq!(error.ptr(), 0);
+
+    let Err(error) = parse("3+?1") else { panic!("expected an error") };
+    assert_eq!(*error.kind(), ParserErrorKind::RecursiveVariable);
+    assert_eq!(error.ptr(), 2);
+
+    // Using the same variable more than once isn't recursion
+    let node = parse("?3+?4").unwrap();
+    assert_eq!(evaluate(&node, &config).unwrap().result, FlexInt::from_int(15, 8));
+}
+
+#[test]
+fn test_constant_overflow_spans() {
+    let variables = VariableArray::default();
+    let spans = |s: &str| {
+        let glyphs = Glyph::from_string(s).unwrap();
+        let mut parser = Parser::<ConstantOverflowChecker>::new(&glyphs, &variables, config(S8));
+        let _ = parser.parse_list();
+        parser.constant_overflow_spans
+    };
+
+    // The warning for a negative literal includes its negations, but no others
+    assert_eq!(spans("1+-200"), vec![GlyphSpan::new(2, 4)]);
+    assert_eq!(spans("--5+-200"), vec![GlyphSpan::new(4, 4)]);
+
+    // Literals are checked until reaching an error
+    assert_eq!(spans("300+)+400"), vec![GlyphSpan::new(0, 3)]);
+
+    // A power binds more tightly than negation, so the literal isn't negative
+    assert_eq!(spans("-128^1"), vec![GlyphSpan::new(1, 3)]);
+}
+
+#[test]
+fn test_variable_positions() {
+    let config = config(U8);
+    let mut variables: VariableArray = Default::default();
+    variables[0] = Glyph::from_string("10÷0").unwrap();
+    variables[1] = Glyph::from_string("200+100").unwrap();
+
+    let evaluate = |s: &str| evaluate(&parse(s, &variables, config).unwrap(), &config);
+
+    // Anything happening within a variable is reported at the reference to it
+    let error = evaluate("55+3+?0").unwrap_err();
+    assert_eq!(*error.kind(), ParserErrorKind::DivisionByZero);
+    assert_eq!(error.ptr(), 5);
+
+    let result = evaluate("1+?1").unwrap();
+    assert_eq!(result.overflows, vec![
+        Overflow { operation: Operation::Add, span: GlyphSpan::new(2, 2) },
+    ]);
+
+    // An empty variable is zero, and can be used as an operand like anything else
+    let result = evaluate("?2&?3|1").unwrap();
+    assert_eq!(result.result, FlexInt::from_int(1, 8));
+}
+
+#[test]
+fn test_power() {
+    let evaluate_s16 = |s: &str| {
+        let result = evaluate_with(s, S16);
+        (result.result.to_signed_decimal_string(), result.overflow)
+    };
+
+    assert_eq!(evaluate_s16("2^10"), ("1024".to_string(), false));
+    assert_eq!(evaluate_s16("2^3^2"), ("512".to_string(), false));
+    assert_eq!(evaluate_s16("(2^3)^2"), ("64".to_string(), false));
+    assert_eq!(evaluate_s16("3*2^2+1"), ("13".to_string(), false));
+    assert_eq!(evaluate_s16("2^16"), ("0".to_string(), true));
+
+    // Negation applies after the power, unless it's part of the exponent
+    assert_eq!(evaluate_s16("-2^2"), ("-4".to_string(), false));
+    assert_eq!(evaluate_s16("--2^2"), ("4".to_string(), false));
+    assert_eq!(evaluate_s16("(-2)^2"), ("4".to_string(), false));
+    assert_eq!(evaluate_s16("-(2^2)"), ("-4".to_string(), false));
+    assert_eq!(evaluate_s16("2^-1"), ("0".to_string(), false));
+    assert_eq!(evaluate_s16("1-2^2"), ("-3".to_string(), false));
+    assert_eq!(evaluate_s16("-32768"), ("-32768".to_string(), false));
+}
+
+#[test]
+fn test_shift() {
+    assert_eq!(evaluate_u8("1«4").result, FlexInt::from_int(16, 8));
+    assert_eq!(evaluate_u8("xFF»4").result, FlexInt::from_int(0x0F, 8));
+    assert_eq!(evaluate_u8("1«8").result, FlexInt::from_int(0, 8));
+    assert!(evaluate_u8("1«8").overflow);
+    assert!(!evaluate_u8("1«7").overflow);
+
+    // Between addition and multiplication in precedence
+    assert_eq!(evaluate_u8("1+1«2").result, FlexInt::from_int(5, 8));
+    assert_eq!(evaluate_u8("2*3«1").result, FlexInt::from_int(12, 8));
+    assert_eq!(evaluate_u8("1«2*3").result, FlexInt::from_int(64, 8));
+
+    // Signed shifts are arithmetic, and overflow if the sign changes
+    let evaluate_s8 = |s: &str| {
+        let result = evaluate_with(s, S8);
+        (result.result.to_signed_decimal_string(), result.overflow)
+    };
+    assert_eq!(evaluate_s8("-76»2"), ("-19".to_string(), false));
+    assert_eq!(evaluate_s8("-1«7"), ("-128".to_string(), false));
+    assert_eq!(evaluate_s8("1«7"), ("-128".to_string(), true));
+    assert_eq!(evaluate_s8("-65«1"), ("126".to_string(), true));
+}
+
+#[test]
+fn test_bitwise_precedence() {
+    assert_eq!(evaluate_u8("xF0|x0F").result, FlexInt::from_int(0xFF, 8));
+    assert_eq!(evaluate_u8("x0F&x3C").result, FlexInt::from_int(0x0C, 8));
+    assert_eq!(evaluate_u8("x0F⊕x3C").result, FlexInt::from_int(0x33, 8));
+
+    // AND, then XOR, then OR, like in C
+    assert_eq!(evaluate_u8("1|6⊕3&5").result, FlexInt::from_int(7, 8));
+    assert_eq!(evaluate_u8("6&3⊕8|1").result, FlexInt::from_int(11, 8));
+
+    // Arithmetic binds more tightly
+    assert_eq!(evaluate_u8("1+2&3").result, FlexInt::from_int(3, 8));
+    assert_eq!(evaluate_u8("2*3|1").result, FlexInt::from_int(7, 8));
+
+    // Never overflows, even with operands which did
+    let result = evaluate_u8("255+1|255");
+    assert_eq!(result.result, FlexInt::from_int(255, 8));
+    assert_eq!(result.overflows, vec![
+        Overflow { operation: Operation::Add, span: GlyphSpan::new(0, 5) },
+    ]);
+}
+
+#[test]
+fn test_negate_expression() {
+    let mut variables: VariableArray = Default::default();
+    variables[0] = Glyph::from_string("2+3").unwrap();
+    let config = config(S8);
+    let evaluate_s8 = |s: &str| evaluate(&parse(s, &variables, config).unwrap(), &config).unwrap();
+
+    // The whole parenthesised expression or variable is negated, not just its first number
+    assert_eq!(evaluate_s8("-(2+3)").result, FlexInt::from_int(5, 8).negate().unwrap());
+    assert_eq!(evaluate_s8("-?0+4").result, FlexInt::from_int(1, 8).negate().unwrap());
+    assert_eq!(evaluate_s8("--(2+3)").result, FlexInt::from_int(5, 8));
+
+    let result = evaluate_s8("-(-100-28)");
+    assert_eq!(result.result, FlexInt::min_value(8, true));
+    assert_eq!(result.overflows, vec![
+        Overflow { operation: Operation::Negate, span: GlyphSpan::new(0, 10) },
+    ]);
+
+    // Unsigned negation only fits for zero
+    let result = evaluate_u8("-(2+3)");
+    assert_eq!(result.result, FlexInt::from_int(251, 8));
+    assert!(result.overflow);
+    assert!(!evaluate_u8("-(0)").overflow);
+}
+
+#[test]
+fn test_disabled_modulo() {
+    let error = parse_disabled("7*2%3", OperatorGroup::Modulo);
+    assert_eq!(*error.kind(), ParserErrorKind::OperatorDisabled(OperatorGroup::Modulo));
+    assert_eq!(error.ptr(), 3);
+
+    // Multiplication and division have the same precedence, but aren't part of the group
+    let mut config = config(U8);
+    config.operators.set_enabled(OperatorGroup::Modulo, false);
+    assert!(parse("7*2÷3", &Default::default(), config).is_ok());
+}
+
+#[test]
+fn test_disabled_power() {
+    let error = parse_disabled("2^3", OperatorGroup::Power);
+    assert_eq!(*error.kind(), ParserErrorKind::OperatorDisabled(OperatorGroup::Power));
+    assert_eq!(error.ptr(), 1);
+
+    // Even where the power is found while looking ahead past negations
+    let error = parse_disabled("-2^3", OperatorGroup::Power);
+    assert_eq!(*error.kind(), ParserErrorKind::OperatorDisabled(OperatorGroup::Power));
+}
+
+#[test]
+fn test_disabled_bitwise() {
+    for (s, ptr) in [("12&10", 2), ("1+12|10", 4), ("12⊕10", 2)] {
+        let error = parse_disabled(s, OperatorGroup::Bitwise);
+        assert_eq!(*error.kind(), ParserErrorKind::OperatorDisabled(OperatorGroup::Bitwise));
+        assert_eq!(error.ptr(), ptr);
+    }
+}
+
+#[test]
+fn test_disabled_shift() {
+    for (s, ptr) in [("1«4", 1), ("16»2", 2)] {
+        let error = parse_disabled(s, OperatorGroup::Shift);
+        assert_eq!(*error.kind(), ParserErrorKind::OperatorDisabled(OperatorGroup::Shift));
+        assert_eq!(error.ptr(), ptr);
+    }
+}
+
+#[test]
+fn test_multiple_overflows() {
+    // 200+100 overflows to 44, and 200*2 overflows to 144, but their sum doesn't overflow
+    let result = evaluate_u8("200+100+200*2");
+    assert_eq!(result.result, FlexInt::from_int(188, 8));
+    assert!(result.overflow);
+    assert_eq!(result.overflows, vec![
+        Overflow { operation: Operation::Add, span: GlyphSpan::new(0, 7) },
+        Overflow { operation: Operation::Multiply, span: GlyphSpan::new(8, 5) },
+    ]);
+}
+
+#[test]
+fn test_estimate_memory_usage() {
+    // More bits or more glyphs always need more memory
+    assert!(estimate_memory_usage(64, 10) > estimate_memory_usage(32, 10));
+    assert!(estimate_memory_usage(32, 20) > estimate_memory_usage(32, 10));
+
+    // Small calculations fit comfortably within the Pico's heap
+    assert!(estimate_memory_usage(64, 40) < 20_000);
+
+    // ...but huge ones don't
+    assert!(estimate_memory_usage(4096, 100) > 230_000);
+}
+
+#[test]
+fn test_divide_by_zero() {
+    let error = evaluate_u8_with("5÷0", DivideByZero::Error).unwrap_err();
+    assert_eq!(error.describe(), "division by zero");
+
+    // The error points at the operator, rather than the start of the operation
+    let error = evaluate_u8_with("12+5÷0", DivideByZero::Error).unwrap_err();
+    assert_eq!(error.ptr(), 4);
+    let error = evaluate_u8_with("(1+2)%(3-3)", DivideByZero::Error).unwrap_err();
+    assert_eq!(error.ptr(), 5);
+
+    let zero = evaluate_u8_with("5÷0", DivideByZero::Zero).unwrap();
+    assert_eq!(zero.result, FlexInt::from_int(0, 8));
+    assert_eq!(zero.overflows, vec![
+        Overflow { operation: Operation::Divide, span: GlyphSpan::new(0, 3) },
+    ]);
+
+    let saturate = evaluate_u8_with("5÷0", DivideByZero::Saturate).unwrap();
+    assert_eq!(saturate.result, FlexInt::from_int(255, 8));
+    assert!(saturate.overflow);
+
+    // Only a zero divisor counts
+    let fine = evaluate_u8_with("5÷1", DivideByZero::Error).unwrap();
+    assert_eq!(fine.result, FlexInt::from_int(5, 8));
+}
+
+#[test]
+fn test_assign_checked() {
+    // Narrowing only overflows if significant bits are lost
+    assert_eq!(assign_checked(&FlexInt::from_int(300, 16), false, U8), (FlexInt::from_int(300 & 0xFF, 8), true));
+    assert_eq!(assign_checked(&FlexInt::from_int(200, 16), false, U8), (FlexInt::from_int(200, 8), false));
+    assert_eq!(assign_checked(&FlexInt::from_int(200, 16), false, S8), (FlexInt::from_int(200, 8), true));
+    assert_eq!(assign_checked(&FlexInt::from_int(0xFFFF, 16), true, S8), (FlexInt::from_int(0xFF, 8), false));
+
+    // Widening keeps the value, but signedness still matters
+    assert_eq!(
+        assign_checked(&FlexInt::from_int(0xFF, 8), true, DataType { bits: 16, signed: true }),
+        (FlexInt::from_int(0xFFFF, 16), false),
+    );
+    assert_eq!(
+        assign_checked(&FlexInt::from_int(0xFF, 8), true, DataType { bits: 16, signed: false }),
+        (FlexInt::from_int(0xFFFF, 16), true),
+    );
+}
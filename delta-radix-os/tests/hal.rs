@@ -1,29 +1,99 @@
 #![feature(async_fn_in_trait)]
 
-use std::{collections::VecDeque, time::Duration, panic::catch_unwind};
+use std::{collections::{VecDeque, HashMap}, time::Duration, panic::{catch_unwind, AssertUnwindSafe}, rc::Rc, cell::RefCell, ops::Deref};
 
-use delta_radix_hal::{Key, Display, Keypad, Time, Hal};
+use delta_radix_hal::{Key, Display, DisplaySpecialCharacter, Keypad, Time, Hal};
 use delta_radix_os::main;
 use futures::executor::block_on;
 use panic_message::panic_message;
 
+/// The display of a [TestHal]. Clones of this share the same lines, so that they can be inspected
+/// from elsewhere in the HAL.
+#[derive(Clone)]
 pub struct TestDisplay {
-    lines: [String; 4],
+    lines: Rc<RefCell<[String; 4]>>,
+
+    /// The special characters currently on the display, by position. These are printed into
+    /// `lines` as their default characters too, but some of those are ambiguous.
+    specials: Rc<RefCell<HashMap<(u8, u8), DisplaySpecialCharacter>>>,
+
     cursor: (u8, u8),
 }
 
 impl TestDisplay {
     pub fn new() -> Self {
         TestDisplay {
-            lines: [
-                " ".repeat(20),
-                " ".repeat(20),
-                " ".repeat(20),
-                " ".repeat(20),
-            ],
+            lines: Rc::new(RefCell::new(Self::blank_lines())),
+            specials: Rc::new(RefCell::new(HashMap::new())),
             cursor: (0, 0)
         }
     }
+
+    fn blank_lines() -> [String; 4] {
+        [
+            " ".repeat(20),
+            " ".repeat(20),
+            " ".repeat(20),
+            " ".repeat(20),
+        ]
+    }
+
+    pub fn display_contents(&self) -> String {
+        self.lines.borrow().join("\n")
+    }
+
+    /// Renders the whole display as a deterministic multi-line string, for comparing against a
+    /// golden snapshot in tests.
+    /// 
+    /// Each row is framed by `|`, so that trailing spaces are visible. Special characters are
+    /// mapped to distinct ASCII characters, unlike [Self::display_contents]:
+    /// 
+    /// - Cursor: `\/`
+    /// - Cursor with warning: `[]`
+    /// - Warning: `!`
+    pub fn snapshot(&self) -> String {
+        let lines = self.lines.borrow();
+        let specials = self.specials.borrow();
+        let border = format!("+{}+", "-".repeat(20));
+
+        let mut rows = vec![border.clone()];
+        for (y, line) in lines.iter().enumerate() {
+            let row = line.chars()
+                .enumerate()
+                .map(|(x, c)| match specials.get(&(x as u8, y as u8)) {
+                    Some(DisplaySpecialCharacter::CursorLeft) => '\\',
+                    Some(DisplaySpecialCharacter::CursorRight) => '/',
+                    Some(DisplaySpecialCharacter::Warning) => '!',
+                    Some(DisplaySpecialCharacter::CursorLeftWithWarning) => '[',
+                    Some(DisplaySpecialCharacter::CursorRightWithWarning) => ']',
+                    None => c,
+                })
+                .collect::<String>();
+            rows.push(format!("|{row}|"));
+        }
+        rows.push(border);
+        rows.join("\n")
+    }
+
+    pub fn display_line(&self, index: usize) -> String {
+        self.lines.borrow()[index].clone()
+    }
+
+    pub fn result(&self) -> String {
+        self.display_line(3).trim().to_string()
+    }
+
+    pub fn expression(&self) -> String {
+        self.display_line(2).trim().to_string()
+    }
+
+    pub fn overflow(&self) -> bool {
+        self.display_line(0).ends_with("OVER")
+    }
+
+    pub fn format(&self) -> String {
+        self.display_line(0).split_once(' ').unwrap().0.to_string()
+    }
 }
 
 impl Display for TestDisplay {
@@ -32,17 +102,36 @@ impl Display for TestDisplay {
     }
 
     fn clear(&mut self) {
-        *self = TestDisplay::new();
+        *self.lines.borrow_mut() = Self::blank_lines();
+        self.specials.borrow_mut().clear();
+        self.cursor = (0, 0);
     }
 
     fn print_char(&mut self, c: char) {
-        self.lines[self.cursor.1 as usize].replace_range(
-            (self.cursor.0 as usize)..(self.cursor.0 as usize + 1),
-            &c.to_string()
-        );
+        // Some glyphs, like `÷`, aren't ASCII, so replace by character rather than byte index
+        let mut lines = self.lines.borrow_mut();
+        let line = &mut lines[self.cursor.1 as usize];
+        assert!((self.cursor.0 as usize) < line.chars().count(), "printed beyond the display");
+        *line = line.chars()
+            .enumerate()
+            .map(|(i, existing)| if i == self.cursor.0 as usize { c } else { existing })
+            .collect();
+        self.specials.borrow_mut().remove(&self.cursor);
         self.cursor.0 += 1;
     }
 
+    fn print_special(&mut self, character: DisplaySpecialCharacter) {
+        let position = self.cursor;
+        self.print_char(match character {
+            DisplaySpecialCharacter::CursorLeft
+            | DisplaySpecialCharacter::CursorLeftWithWarning => '\\',
+            DisplaySpecialCharacter::CursorRight
+            | DisplaySpecialCharacter::CursorRightWithWarning => '/',
+            DisplaySpecialCharacter::Warning => '!',
+        });
+        self.specials.borrow_mut().insert(position, character);
+    }
+
     fn set_position(&mut self, x: u8, y: u8) {
         self.cursor = (x, y)
     }
@@ -51,11 +140,25 @@ impl Display for TestDisplay {
     }
 }
 
+pub type NextKeysFn = Box<dyn FnMut(&TestDisplay) -> Vec<Key>>;
+
 pub struct TestKeypad {
     key_queue: VecDeque<Key>,
+
+    /// Called when the queue runs out, to get more keys based on the state of the display.
+    next_keys: Option<(NextKeysFn, TestDisplay)>,
 }
 impl Keypad for TestKeypad {
     async fn wait_key(&mut self) -> Key {
+        if self.key_queue.is_empty() {
+            if let Some((next_keys, display)) = &mut self.next_keys {
+                self.key_queue.extend(next_keys(display));
+                if self.key_queue.is_empty() {
+                    return Key::DebugTerminate;
+                }
+            }
+        }
+
         self.key_queue.pop_front().expect("no more keys")
     }
 }
@@ -69,39 +172,29 @@ pub struct TestHal {
     display: TestDisplay,
     keypad: TestKeypad,
     time: TestTime,
+    pub clipboard: Option<String>,
+    pub free_memory: Option<usize>,
+    pub storage: HashMap<String, Vec<u8>>,
 }
 
 impl TestHal {
     pub fn new(keys: &[Key]) -> Self {
         Self {
             display: TestDisplay::new(),
-            keypad: TestKeypad { key_queue: keys.iter().copied().collect() },
+            keypad: TestKeypad { key_queue: keys.iter().copied().collect(), next_keys: None },
             time: TestTime,
+            clipboard: None,
+            free_memory: None,
+            storage: HashMap::new(),
         }
     }
+}
 
-    pub fn display_contents(&self) -> String {
-        self.display.lines.join("\n")
-    }
-
-    pub fn display_line(&self, index: usize) -> String {
-        self.display.lines[index].clone()
-    }
-
-    pub fn result(&self) -> String {
-        self.display_line(3).trim().to_string()
-    }
-
-    pub fn expression(&self) -> String {
-        self.display_line(2).trim().to_string()
-    }
-
-    pub fn overflow(&self) -> bool {
-        self.display_line(0).ends_with("OVER")
-    }
+impl Deref for TestHal {
+    type Target = TestDisplay;
 
-    pub fn format(&self) -> String {
-        self.display_line(0).split_once(' ').unwrap().0.to_string()
+    fn deref(&self) -> &Self::Target {
+        &self.display
     }
 }
 
@@ -126,15 +219,54 @@ impl Hal for TestHal {
     async fn enter_bootloader(&mut self) {
         panic!("test entered bootloader")
     }
+
+    fn copy_to_clipboard(&mut self, text: &str) {
+        self.clipboard = Some(text.to_string());
+    }
+
+    fn free_memory(&self) -> Option<usize> {
+        self.free_memory
+    }
+
+    fn load(&self, key: &str) -> Option<Vec<u8>> {
+        self.storage.get(key).cloned()
+    }
+
+    fn store(&mut self, key: &str, value: &[u8]) {
+        self.storage.insert(key.to_string(), value.to_vec());
+    }
 }
 
 pub fn run_os(keys: &[Key]) -> TestHal {
+    let hal = TestHal::new(
+        &keys.iter().chain(&[Key::DebugTerminate]).copied().collect::<Vec<_>>()[..]
+    );
+    run_hal(hal)
+}
+
+/// Runs the OS by pressing some keys, on a device reporting a fixed amount of free memory.
+pub fn run_os_with_free_memory(keys: &[Key], free_memory: usize) -> TestHal {
     let mut hal = TestHal::new(
         &keys.iter().chain(&[Key::DebugTerminate]).copied().collect::<Vec<_>>()[..]
     );
+    hal.free_memory = Some(free_memory);
+    run_hal(hal)
+}
+
+/// Runs the OS by pressing some keys, then calling `next_keys` each time they run out to decide
+/// which keys to press next, based on the display. The OS stops when `next_keys` returns no keys.
+pub fn run_os_interactive(keys: &[Key], next_keys: impl FnMut(&TestDisplay) -> Vec<Key> + 'static) -> TestHal {
+    let mut hal = TestHal::new(keys);
+    hal.keypad.next_keys = Some((Box::new(next_keys), hal.display.clone()));
+    run_hal(hal)
+}
+
+fn run_hal(mut hal: TestHal) -> TestHal {
     let hal_ptr = &mut hal as *mut TestHal;
     
-    match catch_unwind(|| block_on(main(unsafe { hal_ptr.as_mut().unwrap() }))) {
+    // The display and key callback aren't unwind-safe, but nothing will observe them in a broken
+    // state, since the test fails if the OS panics unexpectedly
+    match catch_unwind(AssertUnwindSafe(|| block_on(main(unsafe { hal_ptr.as_mut().unwrap() })))) {
         // This is what we expect from pressing the DebugTerminate key!
         Err(e) if panic_message(&e) == "debug terminate" => (),
 
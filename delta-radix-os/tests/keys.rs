@@ -1,4 +1,6 @@
-use delta_radix_hal::Key;
+use std::time::Duration;
+
+use delta_radix_hal::{AutoRepeat, Key};
 
 macro_rules! keys {
     ($($x:expr),+ $(,)?) => { 
@@ -74,3 +76,51 @@ impl KeySequence for Number {
             .collect()
     }
 }
+
+/// Opens the settings menu, changes the setting with the given index to its next value the given
+/// number of times, and then exits the menu.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct CycleSetting(pub u8, pub usize);
+impl KeySequence for CycleSetting {
+    fn keys(&self) -> Vec<Key> {
+        let CycleSetting(index, times) = *self;
+        let mut keys = vec![Key::Shift, Key::Menu, Key::Digit(2)];
+        for _ in 0..times {
+            keys.push(Key::Digit(index));
+        }
+        keys.push(Key::Menu);
+        keys
+    }
+}
+
+/// Opens the operators sub-menu of the settings menu, toggles the operator group with the given
+/// index, and then exits both menus.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct ToggleOperator(pub u8);
+impl KeySequence for ToggleOperator {
+    fn keys(&self) -> Vec<Key> {
+        vec![Key::Shift, Key::Menu, Key::Digit(2), Key::Digit(2), Key::Digit(self.0), Key::Menu, Key::Menu]
+    }
+}
+
+/// Presses a key sequence, and holds its last key down for some time, producing the presses which a
+/// HAL using [AutoRepeat] with its default timings would report, if it checked the key every
+/// millisecond.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct Held<K: KeySequence>(pub K, pub Duration);
+impl<K: KeySequence> KeySequence for Held<K> {
+    fn keys(&self) -> Vec<Key> {
+        let Held(sequence, duration) = self;
+        let mut repeat = AutoRepeat::default();
+        let mut keys = sequence.keys();
+        repeat.press(*keys.last().unwrap());
+
+        let step = Duration::from_millis(1);
+        let mut elapsed = Duration::ZERO;
+        while elapsed < *duration {
+            keys.extend(repeat.hold(step));
+            elapsed += step;
+        }
+        keys
+    }
+}
@@ -0,0 +1,231 @@
+use std::time::Instant;
+
+use delta_radix_hal::Glyph;
+use delta_radix_os::calc::{backend::{tokenize::{Tokenizer, Token, TokenKind, IncrementalTokenizer}, parse::{GlyphSpan, Parser, ConstantOverflowChecker, IncrementalOverflowChecker}, eval::{Configuration, DataType, OverflowBehaviour, DivideByZero, OperatorGroup, OperatorGroups}}, frontend::{Base, VariableArray}};
+
+fn glyphs(s: &str) -> Vec<Glyph> {
+    // `>` isn't a valid character for `from_string`, so use it for align here
+    s.chars()
+        .map(|c| if c == '>' { Glyph::Align } else { Glyph::from_char(c).unwrap() })
+        .collect()
+}
+
+fn config() -> Configuration {
+    Configuration {
+        data_type: DataType { bits: 32, signed: true },
+        overflow_behaviour: OverflowBehaviour::Wrap,
+        divide_by_zero: DivideByZero::Error,
+        operators: OperatorGroups::ALL,
+        raw_based_literals: false,
+    }
+}
+
+fn tokenize(s: &str) -> Vec<Token> {
+    Tokenizer::new(&glyphs(s)).tokenize().unwrap()
+}
+
+fn token(start: usize, length: usize, kind: TokenKind) -> Token {
+    Token { span: GlyphSpan::new(start, length), kind }
+}
+
+fn number(digits: &[u8], base: Option<Base>, exponent: usize) -> TokenKind {
+    TokenKind::Number { digits: digits.to_vec(), base, exponent }
+}
+
+#[test]
+fn test_tokenize_expression() {
+    assert_eq!(
+        tokenize("x1F*(-12+?3)>b101;2e3"),
+        vec![
+            token(0, 3, number(&[1, 0xF], Some(Base::Hexadecimal), 0)),
+            token(3, 1, TokenKind::Operator(Glyph::Multiply)),
+            token(4, 1, TokenKind::LeftParen),
+            token(5, 1, TokenKind::Operator(Glyph::Subtract)),
+            token(6, 2, number(&[1, 2], None, 0)),
+            token(8, 1, TokenKind::Operator(Glyph::Add)),
+            token(9, 2, TokenKind::Variable(3)),
+            token(11, 1, TokenKind::RightParen),
+            token(12, 1, TokenKind::Operator(Glyph::Align)),
+            token(13, 4, number(&[1, 0, 1], Some(Base::Binary), 0)),
+            token(17, 1, TokenKind::Separator),
+            token(18, 3, number(&[2], None, 3)),
+        ],
+    );
+}
+
+#[test]
+fn test_tokenize_suffix_base() {
+    assert_eq!(
+        tokenize("12d-C0x"),
+        vec![
+            token(0, 3, number(&[1, 2], Some(Base::Decimal), 0)),
+            token(3, 1, TokenKind::Operator(Glyph::Subtract)),
+            token(4, 3, number(&[0xC, 0], Some(Base::Hexadecimal), 0)),
+        ],
+    );
+}
+
+#[test]
+fn test_tokenize_base_position() {
+    // A base after digits is their suffix, not the prefix of something else
+    assert_eq!(tokenize("110b"), vec![token(0, 4, number(&[1, 1, 0], Some(Base::Binary), 0))]);
+    assert_eq!(tokenize("b110"), vec![token(0, 4, number(&[1, 1, 0], Some(Base::Binary), 0))]);
+    assert_eq!(
+        tokenize("110b*b110"),
+        vec![
+            token(0, 4, number(&[1, 1, 0], Some(Base::Binary), 0)),
+            token(4, 1, TokenKind::Operator(Glyph::Multiply)),
+            token(5, 4, number(&[1, 1, 0], Some(Base::Binary), 0)),
+        ],
+    );
+}
+
+#[test]
+fn test_tokenize_errors() {
+    for s in ["x12b", "?", "x1e2", "2e", "2e-1", "e", "110b1", "110bx1"] {
+        let glyphs = Glyph::from_string(s).unwrap();
+        assert!(Tokenizer::new(&glyphs).tokenize().is_err(), "{s}");
+    }
+}
+
+/// A small, deterministic pseudo-random number generator (xorshift), so that failures can be
+/// reproduced.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self, max: usize) -> usize {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 % max as u64) as usize
+    }
+
+    fn glyph(&mut self) -> Glyph {
+        // Mostly digits and operators, with occasional glyphs which are likely to cause errors
+        const GLYPHS: &[Glyph] = &[
+            Glyph::Add, Glyph::Subtract, Glyph::Multiply, Glyph::Divide, Glyph::Modulo, Glyph::Power,
+            Glyph::ShiftLeft, Glyph::ShiftRight,
+            Glyph::BitAnd, Glyph::BitOr, Glyph::BitXor, Glyph::Align,
+            Glyph::LeftParen, Glyph::RightParen, Glyph::Separator, Glyph::HexBase,
+            Glyph::BinaryBase, Glyph::DecimalBase, Glyph::OctalBase, Glyph::Variable, Glyph::Exponent,
+        ];
+        if self.next(2) == 0 {
+            Glyph::Digit(self.next(16) as u8)
+        } else {
+            GLYPHS[self.next(GLYPHS.len())]
+        }
+    }
+}
+
+#[test]
+fn test_incremental_tokenize_matches_full() {
+    let mut random = Random(0x1234_5678_9ABC_DEF0);
+    let mut incremental = IncrementalTokenizer::new();
+    let mut glyphs = vec![];
+
+    for step in 0..20_000 {
+        // Make a random edit, similar to what the user could do at the cursor
+        if glyphs.is_empty() || (glyphs.len() < 30 && random.next(3) != 0) {
+            let index = random.next(glyphs.len() + 1);
+            glyphs.insert(index, random.glyph());
+        } else if random.next(10) == 0 {
+            glyphs.clear();
+        } else {
+            glyphs.remove(random.next(glyphs.len()));
+        }
+
+        assert_eq!(
+            incremental.tokenize(&glyphs).map(|t| t.to_vec()),
+            Tokenizer::new(&glyphs).tokenize(),
+            "step {step}: {glyphs:?}",
+        );
+    }
+}
+
+#[test]
+fn test_incremental_overflow_check_matches_full() {
+    let mut random = Random(0x0F1E_2D3C_4B5A_6978);
+    let mut checker = IncrementalOverflowChecker::new();
+    let mut input = vec![];
+    let mut config = config();
+
+    // Variables which overflow, are invalid, or are recursive, as well as empty ones
+    let mut variables = VariableArray::default();
+    variables[0] = glyphs("99999999999999999999");
+    variables[1] = glyphs("1+");
+    variables[2] = glyphs("?3");
+    variables[3] = glyphs("?2");
+    variables[4] = glyphs("-128");
+    variables[5] = glyphs("2^-1");
+
+    for step in 0..50_000 {
+        // Make a random edit, biased towards digits so that long, overflowing literals come up
+        if input.is_empty() || (input.len() < 30 && random.next(3) != 0) {
+            let index = random.next(input.len() + 1);
+            let glyph = match random.next(20) {
+                0..=7 => Glyph::Digit(random.next(10) as u8),
+                8 => Glyph::Answer,
+                _ => random.glyph(),
+            };
+            input.insert(index, glyph);
+        } else if random.next(10) == 0 {
+            input.clear();
+        } else {
+            input.remove(random.next(input.len()));
+        }
+
+        // Occasionally change the configuration
+        if random.next(50) == 0 {
+            config.data_type = DataType { bits: [8, 16, 32, 64][random.next(4)], signed: random.next(2) == 1 };
+            config.raw_based_literals = random.next(2) == 1;
+            config.operators = OperatorGroups::ALL;
+            for group in [OperatorGroup::Align, OperatorGroup::Modulo, OperatorGroup::Power, OperatorGroup::Bitwise, OperatorGroup::Shift] {
+                config.operators.set_enabled(group, random.next(4) != 0);
+            }
+        }
+
+        let mut parser = Parser::<ConstantOverflowChecker>::new(&input, &variables, config);
+        let _ = parser.parse_list();
+        assert_eq!(
+            checker.check(&input, &variables, config),
+            parser.constant_overflow_spans,
+            "step {step}: {input:?}",
+        );
+    }
+}
+
+#[test]
+#[ignore = "benchmark - run with `cargo test --release -- --ignored --nocapture`"]
+fn bench_incremental_overflow_check() {
+    // Type out a long expression one glyph at a time, in the middle of an existing one, checking
+    // for constant overflows after each keypress like the frontend does
+    let base = glyphs(&"123456+x1F*(b101-42)÷".repeat(10));
+    let typed = glyphs(&"987654-".repeat(10));
+    let midpoint = base.len() / 2;
+    let versions = (0..=typed.len())
+        .map(|i| {
+            let mut glyphs = base.clone();
+            glyphs.splice(midpoint..midpoint, typed[..i].iter().copied());
+            glyphs
+        })
+        .collect::<Vec<_>>();
+    let variables = VariableArray::default();
+
+    let start = Instant::now();
+    for _ in 0..100 {
+        for glyphs in &versions {
+            let mut parser = Parser::<ConstantOverflowChecker>::new(glyphs, &variables, config());
+            let _ = parser.parse_list();
+        }
+    }
+    println!("full:        {:?}", start.elapsed());
+
+    let start = Instant::now();
+    for _ in 0..100 {
+        let mut checker = IncrementalOverflowChecker::new();
+        for glyphs in &versions {
+            checker.check(glyphs, &variables, config());
+        }
+    }
+    println!("incremental: {:?}", start.elapsed());
+}
@@ -0,0 +1,61 @@
+use alloc::string::String;
+
+use crate::FlexInt;
+
+impl FlexInt {
+    /// Converts this number into binary-coded decimal (BCD), treating it as unsigned. Each decimal
+    /// digit is packed into a nibble, with the least-significant digit in the lowest nibble.
+    /// 
+    /// The result is the same size as this number. Returns None if there are more decimal digits
+    /// than can fit into the nibbles of that size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(1234, 16);
+    /// assert_eq!(i.to_bcd(), Some(FlexInt::from_int(0x1234, 16)));
+    /// 
+    /// let too_big = FlexInt::from_int(12345, 16);
+    /// assert_eq!(too_big.to_bcd(), None);
+    /// ```
+    pub fn to_bcd(&self) -> Option<FlexInt> {
+        let digits = self.unsigned_decimal_digits();
+        if digits.len() * 4 > self.size() {
+            return None;
+        }
+
+        let mut result = FlexInt::new(self.size());
+        for (nibble, digit) in digits.iter().rev().enumerate() {
+            for i in 0..4 {
+                *result.bit_mut(nibble * 4 + i) = (digit >> i) & 1 == 1;
+            }
+        }
+        Some(result)
+    }
+
+    /// Interprets this number as binary-coded decimal (BCD), converting it into the number which
+    /// the decimal digits in its nibbles represent. This is the inverse of [FlexInt::to_bcd].
+    /// 
+    /// The result is the same size as this number. Returns None if any nibble isn't a valid
+    /// decimal digit.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let bcd = FlexInt::from_int(0x1234, 16);
+    /// assert_eq!(bcd.from_bcd(), Some(FlexInt::from_int(1234, 16)));
+    /// 
+    /// let invalid = FlexInt::from_int(0x12A4, 16);
+    /// assert_eq!(invalid.from_bcd(), None);
+    /// ```
+    pub fn from_bcd(&self) -> Option<FlexInt> {
+        let mut digits = String::new();
+        for nibble in self.bits().chunks(4).rev() {
+            let digit = nibble.iter().rev().fold(0, |acc, bit| (acc << 1) | (*bit as u32));
+            digits.push(char::from_digit(digit, 10)?);
+        }
+
+        // A number of decimal digits always fits into the same number of nibbles, so this can't
+        // overflow
+        let (result, _) = FlexInt::from_unsigned_decimal_string(&digits, self.size())?;
+        Some(result)
+    }
+}
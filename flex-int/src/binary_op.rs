@@ -1,3 +1,5 @@
+use core::cmp::Ordering;
+
 use crate::FlexInt;
 
 impl FlexInt {
@@ -117,26 +119,9 @@ impl FlexInt {
         }
 
         // Cut back down to size
-        let (result, cut_zeroes, cut_ones) = result_ext.shrink(self.size());
+        let (result, cut_overflow) = result_ext.shrink_checked(self.size(), signed);
+        overflow = overflow || cut_overflow;
         if signed {
-            // In a signed number, overflow has only occurred if a mixture of zeroes and ones were
-            // cut. If just ones were cut, then we've shrunk a negative number, and just zeroes a
-            // positive number
-            if cut_zeroes > 0 && cut_ones > 0 {
-                overflow = true;
-            }
-
-            // If ones were cut but the number is no longer negative, this is also invalid
-            // e.g.
-            //      \/ cut point
-            //   0b1110000 -> 0b10000    = valid, same signed number
-            //
-            //      \/ cut point
-            //   0b1100000 -> 0b00000    = invalid, different number
-            if cut_ones > 0 && !result.is_negative() {
-                overflow = true;
-            }
-
             // Another thing to check - check that the resultant signedness matches the combined
             // signedness of the operands
             // (Two of the same sign = pos, two different signs = neg)
@@ -146,11 +131,6 @@ impl FlexInt {
                     overflow = true;
                 }
             }
-        } else {
-            // In an unsigned number, overflow has occurred if any ones were cut
-            if cut_ones > 0 {
-                overflow = true;
-            }
         }
 
         (result, overflow)
@@ -161,6 +141,9 @@ impl FlexInt {
     /// 
     /// Division must know whether the numbers being used should be treated as signed.
     /// 
+    /// Dividing by zero gives zero, and is treated as an overflow. Callers wanting different
+    /// behaviour should check for a zero divisor first.
+    /// 
     /// Panics unless the two integers are the same size.
     /// 
     /// ```rust
@@ -168,12 +151,55 @@ impl FlexInt {
     /// let a = FlexInt::from_int(12, 8);
     /// let b = FlexInt::from_int(3, 8);
     /// assert_eq!(a.divide(&b, false), (FlexInt::from_int(4, 8), false));
+    /// 
+    /// let zero = FlexInt::new(8);
+    /// assert_eq!(a.divide(&zero, false), (FlexInt::new(8), true));
     /// ```
     pub fn divide(&self, other: &FlexInt, signed: bool) -> (FlexInt, bool) {
+        let (quotient, _, overflow) = self.div_rem(other, signed);
+        (quotient, overflow)
+    }
+
+    /// Divides this integer by another, and returns both the quotient and the remainder, plus a
+    /// boolean indicating whether overflow occurred. This is quicker than calling both
+    /// [FlexInt::divide] and [FlexInt::remainder], which would each perform the same division.
+    /// 
+    /// The quotient and remainder are the same as those methods would give, so the remainder of a
+    /// signed division has the same sign as the dividend. The overflow boolean applies to the
+    /// quotient - for example, the smallest signed value divided by -1 overflows, but the
+    /// remainder is still exactly 0.
+    /// 
+    /// Dividing by zero gives zero for both, and is treated as an overflow.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(14, 8);
+    /// let b = FlexInt::from_int(4, 8);
+    /// assert_eq!(a.div_rem(&b, false), (FlexInt::from_int(3, 8), FlexInt::from_int(2, 8), false));
+    /// 
+    /// // -14 / 4 == -3, remainder -2
+    /// let a = FlexInt::from_int(14, 8).negate().unwrap();
+    /// assert_eq!(
+    ///     a.div_rem(&b, true),
+    ///     (FlexInt::from_int(3, 8).negate().unwrap(), FlexInt::from_int(2, 8).negate().unwrap(), false),
+    /// );
+    /// 
+    /// // -128 / -1 overflows
+    /// let a = FlexInt::min_value(8, true);
+    /// let b = FlexInt::from_int(1, 8).negate().unwrap();
+    /// assert_eq!(a.div_rem(&b, true), (FlexInt::new(8), FlexInt::new(8), true));
+    /// ```
+    pub fn div_rem(&self, other: &FlexInt, signed: bool) -> (FlexInt, FlexInt, bool) {
         self.validate_size(other);
 
+        if other.is_zero() {
+            return (FlexInt::new(self.size()), FlexInt::new(self.size()), true)
+        }
+
         // Special cases - there are problems dividing the largest possible negative by 1 (or -1), 
-        // so handle this explicitly
+        // so handle this explicitly. Either way, there's no remainder
         let other_is_one = 
             if signed {
                 other.abs() == Some(Self::new_one(self.size()))
@@ -181,52 +207,114 @@ impl FlexInt {
                 other == &Self::new_one(self.size())
             };
         if other_is_one {
+            let zero = Self::new(self.size());
             if other.is_negative() {
                 if let Some(neg) = self.negate() {
-                    return (neg, false)
+                    return (neg, zero, false)
                 } else {
-                    return (Self::new(self.size()), true)
+                    return (Self::new(self.size()), zero, true)
                 }
             } else {
-                return (self.clone(), false)
+                return (self.clone(), zero, false)
             }
         }
 
-        let a;
-        let b;
-        let negate_result;
-        if signed {
-            // Two's complement division is probably really hard, so sign-extend the numbers by one
-            // bit, negate the negative ones to be positive, and keep track of whether we need to
-            // negate again at the end.
-            // The reason we sign-extend is so we don't overflow if negating the lowest possible
-            // negative
-            a = self.sign_extend(self.size() + 1).abs().expect("unexpected overflow while preparing division");
-            b = other.sign_extend(self.size() + 1).abs().expect("unexpected overflow while preparing division");
-            negate_result = self.is_negative() ^ other.is_negative();
-        } else {
-            a = self.clone();
-            b = other.clone();
-            negate_result = false;
+        if !signed {
+            let (quotient, remainder) = self.long_divide_unsigned(other);
+            return (quotient, remainder, false)
         }
 
-        if other.is_zero() {
-            return (FlexInt::new(self.size()), true)
+        // Two's complement division is probably really hard, so sign-extend the numbers by one
+        // bit, negate the negative ones to be positive, and keep track of whether we need to
+        // negate again at the end.
+        // The reason we sign-extend is so we don't overflow if negating the lowest possible
+        // negative
+        let a = self.sign_extend(self.size() + 1).abs().expect("unexpected overflow while preparing division");
+        let b = other.sign_extend(self.size() + 1).abs().expect("unexpected overflow while preparing division");
+        let (quotient, remainder) = a.long_divide_unsigned(&b);
+
+        // Get the sign bit and then chop it off
+        // (Remember we sign-extended by one earlier)
+        let sign = quotient.is_negative();
+        let (mut quotient, _, _) = quotient.shrink(quotient.size() - 1);
+
+        // Overflow is whether we've changed the sign
+        let mut overflow = sign != quotient.is_negative();
+        
+        // We also might need to negate the result - if this fails, report overflow too
+        if self.is_negative() ^ other.is_negative() {
+            if let Some(r) = quotient.negate() {
+                quotient = r;
+            } else {
+                overflow = true;
+            }
+        }
+
+        // The remainder is smaller than the divisor's magnitude, so always fits back into the
+        // original size. It takes the sign of the dividend
+        let (mut remainder, _, _) = remainder.shrink(self.size());
+        if self.is_negative() {
+            remainder = remainder.negate().expect("unexpected overflow while negating remainder");
         }
 
-        let mut quotient = FlexInt::new(a.size());
-        let mut remainder = FlexInt::new(a.size());
-        for (i, bit) in a.bits().iter().enumerate().rev() {
+        (quotient, remainder, overflow)
+    }
+
+    /// Finds the remainder of dividing this integer by another, and returns it along with a boolean
+    /// indicating whether overflow occurred.
+    /// 
+    /// For signed integers, the division is truncated, so the remainder has the same sign as this
+    /// integer (the dividend) - like Rust's `%` operator.
+    /// 
+    /// Like [FlexInt::divide], dividing by zero gives zero, and is treated as an overflow.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(14, 8);
+    /// let b = FlexInt::from_int(4, 8);
+    /// assert_eq!(a.remainder(&b, false), (FlexInt::from_int(2, 8), false));
+    /// 
+    /// // -14 % 4 == -2
+    /// let a = FlexInt::from_int(14, 8).negate().unwrap();
+    /// assert_eq!(a.remainder(&b, true), (FlexInt::from_int(2, 8).negate().unwrap(), false));
+    /// 
+    /// // 14 % -4 == 2
+    /// let a = FlexInt::from_int(14, 8);
+    /// let b = FlexInt::from_int(4, 8).negate().unwrap();
+    /// assert_eq!(a.remainder(&b, true), (FlexInt::from_int(2, 8), false));
+    /// 
+    /// // As unsigned, the same bits are 242 % 4 == 2
+    /// let a = FlexInt::from_int(242, 8);
+    /// assert_eq!(a.remainder(&FlexInt::from_int(4, 8), false), (FlexInt::from_int(2, 8), false));
+    /// assert_eq!(a.remainder(&FlexInt::from_int(4, 8), true), (FlexInt::from_int(2, 8).negate().unwrap(), false));
+    /// 
+    /// let zero = FlexInt::new(8);
+    /// assert_eq!(a.remainder(&zero, false), (FlexInt::new(8), true));
+    /// ```
+    pub fn remainder(&self, other: &FlexInt, signed: bool) -> (FlexInt, bool) {
+        // Overflow in the quotient doesn't affect the remainder - only dividing by zero does
+        let (_, remainder, _) = self.div_rem(other, signed);
+        (remainder, other.is_zero())
+    }
+
+    /// Divides this integer by another, returning the quotient and remainder. Both integers are
+    /// treated as unsigned, must be the same size, and the divisor must not be zero.
+    fn long_divide_unsigned(&self, other: &FlexInt) -> (FlexInt, FlexInt) {
+        let mut quotient = FlexInt::new(self.size());
+        let mut remainder = FlexInt::new(self.size());
+        for (i, bit) in self.bits().iter().enumerate().rev() {
             remainder = remainder.unchecked_shift_left(1);
             *remainder.bit_mut(0) = *bit;
 
-            if remainder.is_greater_than_unsigned(&b) || remainder.equals(&b) {
-                let (rem, over) = remainder.subtract_unsigned(&b);
+            if remainder.is_greater_than_unsigned(other) || remainder.equals(other) {
+                let (rem, over) = remainder.subtract_unsigned(other);
                 if over {
                     panic!(
                         "unexpected overflow during division when performing {} - {}",
                         remainder.to_unsigned_decimal_string(),
-                        b.to_unsigned_decimal_string(),
+                        other.to_unsigned_decimal_string(),
                     );
                 }
                 remainder = rem;
@@ -234,28 +322,56 @@ impl FlexInt {
             }
         }
 
-        if signed {
-            // Get the sign bit and then chop it off
-            // (Remember we sign-extended by one earlier)
-            let sign = quotient.is_negative();
-            (quotient, _, _) = quotient.shrink(quotient.size() - 1);
-
-            // Overflow is whether we've changed the sign
-            let overflow = sign != quotient.is_negative();
-            
-            // We also might need to negate the result - if this fails, report overflow too
-            if negate_result {
-                if let Some(r) = quotient.negate() {
-                    (r, overflow)
-                } else {
-                    (quotient, true)
-                }
-            } else {
-                (quotient, overflow)
+        (quotient, remainder)
+    }
+
+    /// Finds the greatest common divisor of this integer and another, treating both as unsigned.
+    /// 
+    /// The GCD of zero and another number is that number, so the GCD of zero and zero is zero.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(48, 8);
+    /// let b = FlexInt::from_int(36, 8);
+    /// assert_eq!(a.gcd(&b), FlexInt::from_int(12, 8));
+    /// 
+    /// let c = FlexInt::from_int(35, 8);
+    /// assert_eq!(a.gcd(&c), FlexInt::from_int(1, 8));
+    /// assert_eq!(a.gcd(&FlexInt::new(8)), a);
+    /// ```
+    pub fn gcd(&self, other: &FlexInt) -> FlexInt {
+        self.validate_size(other);
+
+        if self.is_zero() {
+            return other.clone();
+        }
+        if other.is_zero() {
+            return self.clone();
+        }
+
+        // Uses the binary GCD algorithm, which only needs shifts and subtractions rather than
+        // division: https://en.wikipedia.org/wiki/Binary_GCD_algorithm
+        // Factors of two common to both are removed first, and put back at the end
+        let common_twos = self.trailing_zeros().min(other.trailing_zeros());
+        let mut a = self.shift_right_logical(self.trailing_zeros());
+        let mut b = other.clone();
+        loop {
+            // Both are now odd, so their difference is even, and 2 isn't a factor of the GCD
+            b = b.shift_right_logical(b.trailing_zeros());
+            if a.is_greater_than_unsigned(&b) {
+                core::mem::swap(&mut a, &mut b);
+            }
+            (b, _) = b.subtract_unsigned(&a);
+
+            if b.is_zero() {
+                break;
             }
-        } else {
-            (quotient, false)
         }
+
+        // The GCD can't be bigger than either number, so this can't overflow
+        a.unchecked_shift_left(common_twos)
     }
 
     /// Subtracts another integer from this one.
@@ -339,6 +455,243 @@ impl FlexInt {
         }
     }
 
+    /// Finds the absolute difference between this integer and another, by subtracting the smaller
+    /// from the larger, and returns it along with a boolean indicating whether overflow occurred.
+    /// 
+    /// Unsigned differences never overflow. Signed differences overflow if they're too large to be
+    /// positive in the size, but the bits of the result are still the correct difference if read
+    /// as unsigned.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(3, 8);
+    /// let b = FlexInt::from_int(10, 8);
+    /// assert_eq!(a.abs_diff(&b, false), (FlexInt::from_int(7, 8), false));
+    /// assert_eq!(b.abs_diff(&a, false), (FlexInt::from_int(7, 8), false));
+    /// 
+    /// // Signed
+    /// let a = FlexInt::from_int(3, 8).negate().unwrap();
+    /// assert_eq!(a.abs_diff(&b, true), (FlexInt::from_int(13, 8), false));
+    /// 
+    /// // Signed, overflowing
+    /// let a = FlexInt::min_value(8, true);
+    /// let b = FlexInt::max_value(8, true);
+    /// assert_eq!(a.abs_diff(&b, true), (FlexInt::from_int(255, 8), true));
+    /// ```
+    pub fn abs_diff(&self, other: &FlexInt, signed: bool) -> (FlexInt, bool) {
+        if self.compare(other, signed) == Ordering::Less {
+            other.subtract(self, signed)
+        } else {
+            self.subtract(other, signed)
+        }
+    }
+
+    /// Adds one integer to another, but if the result overflows, gives the largest or smallest value
+    /// which fits in the size instead of wrapping around.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(250, 8);
+    /// let b = FlexInt::from_int(10, 8);
+    /// assert_eq!(a.saturating_add(&b, false), FlexInt::from_int(255, 8));
+    /// assert_eq!(b.saturating_add(&b, false), FlexInt::from_int(20, 8));
+    /// 
+    /// // Signed, saturating in both directions
+    /// let a = FlexInt::from_int(100, 8);
+    /// assert_eq!(a.saturating_add(&a, true), FlexInt::max_value(8, true));
+    /// let a = a.negate().unwrap();
+    /// assert_eq!(a.saturating_add(&a, true), FlexInt::min_value(8, true));
+    /// ```
+    pub fn saturating_add(&self, other: &FlexInt, signed: bool) -> FlexInt {
+        let (result, overflow) = self.add(other, signed);
+        if !overflow {
+            result
+        } else if signed && other.is_negative() {
+            FlexInt::min_value(self.size(), signed)
+        } else {
+            FlexInt::max_value(self.size(), signed)
+        }
+    }
+
+    /// Subtracts one integer from another, but if the result overflows, gives the largest or
+    /// smallest value which fits in the size instead of wrapping around.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(5, 8);
+    /// let b = FlexInt::from_int(10, 8);
+    /// assert_eq!(a.saturating_sub(&b, false), FlexInt::from_int(0, 8));
+    /// assert_eq!(b.saturating_sub(&a, false), FlexInt::from_int(5, 8));
+    /// 
+    /// // Signed, saturating in both directions
+    /// let a = FlexInt::from_int(100, 8);
+    /// let b = a.negate().unwrap();
+    /// assert_eq!(a.saturating_sub(&b, true), FlexInt::max_value(8, true));
+    /// assert_eq!(b.saturating_sub(&a, true), FlexInt::min_value(8, true));
+    /// ```
+    pub fn saturating_sub(&self, other: &FlexInt, signed: bool) -> FlexInt {
+        let (result, overflow) = self.subtract(other, signed);
+        if !overflow {
+            result
+        } else if signed && other.is_negative() {
+            FlexInt::max_value(self.size(), signed)
+        } else {
+            FlexInt::min_value(self.size(), signed)
+        }
+    }
+
+    /// Adds one integer to another, or returns `None` if the result overflows.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(250, 8);
+    /// assert_eq!(a.checked_add(&FlexInt::from_int(5, 8), false), Some(FlexInt::from_int(255, 8)));
+    /// assert_eq!(a.checked_add(&FlexInt::from_int(6, 8), false), None);
+    /// ```
+    pub fn checked_add(&self, other: &FlexInt, signed: bool) -> Option<FlexInt> {
+        match self.add(other, signed) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+
+    /// Subtracts one integer from another, or returns `None` if the result overflows.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(5, 8);
+    /// assert_eq!(a.checked_sub(&FlexInt::from_int(5, 8), false), Some(FlexInt::from_int(0, 8)));
+    /// assert_eq!(a.checked_sub(&FlexInt::from_int(6, 8), false), None);
+    /// assert_eq!(a.checked_sub(&FlexInt::from_int(6, 8), true), Some(FlexInt::from_int(0xFF, 8)));
+    /// ```
+    pub fn checked_sub(&self, other: &FlexInt, signed: bool) -> Option<FlexInt> {
+        match self.subtract(other, signed) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+
+    /// Multiplies one integer by another, or returns `None` if the result overflows.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(16, 8);
+    /// assert_eq!(a.checked_mul(&FlexInt::from_int(15, 8), false), Some(FlexInt::from_int(240, 8)));
+    /// assert_eq!(a.checked_mul(&FlexInt::from_int(8, 8), true), None);
+    /// ```
+    pub fn checked_mul(&self, other: &FlexInt, signed: bool) -> Option<FlexInt> {
+        match self.multiply(other, signed) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+
+    /// Divides one integer by another, or returns `None` if the result overflows or the divisor is
+    /// zero.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(100, 8);
+    /// assert_eq!(a.checked_div(&FlexInt::from_int(7, 8), false), Some(FlexInt::from_int(14, 8)));
+    /// 
+    /// // Dividing by zero is also treated as overflow
+    /// assert_eq!(a.checked_div(&FlexInt::from_int(0, 8), false), None);
+    /// 
+    /// // Chaining with `?`
+    /// fn mean(a: &FlexInt, b: &FlexInt) -> Option<FlexInt> {
+    ///     a.checked_add(b, false)?.checked_div(&FlexInt::from_int(2, a.size()), false)
+    /// }
+    /// assert_eq!(mean(&a, &FlexInt::from_int(50, 8)), Some(FlexInt::from_int(75, 8)));
+    /// assert_eq!(mean(&a, &FlexInt::from_int(200, 8)), None);
+    /// ```
+    pub fn checked_div(&self, other: &FlexInt, signed: bool) -> Option<FlexInt> {
+        match self.divide(other, signed) {
+            (result, false) => Some(result),
+            (_, true) => None,
+        }
+    }
+
+    /// Raises this integer to the power of another, and returns the result, plus a boolean
+    /// indicating whether overflow occurred.
+    ///
+    /// A negative exponent gives the reciprocal, truncated towards zero like [FlexInt::divide], so
+    /// the result is zero unless the base is 1 or -1. Raising zero to a negative power is a
+    /// division by zero, so gives zero and is treated as an overflow.
+    ///
+    /// Panics unless the two integers are the same size.
+    ///
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(2, 16);
+    /// assert_eq!(a.pow(&FlexInt::from_int(10, 16), false), (FlexInt::from_int(1024, 16), false));
+    /// assert_eq!(a.pow(&FlexInt::from_int(0, 16), false), (FlexInt::from_int(1, 16), false));
+    ///
+    /// // Overflowing, unsigned
+    /// let a = FlexInt::from_int(2, 8);
+    /// assert_eq!(a.pow(&FlexInt::from_int(9, 8), false), (FlexInt::from_int(0, 8), true));
+    ///
+    /// // -2^7 == -128 just fits in signed 8-bit
+    /// let a = FlexInt::from_int(2, 8).negate().unwrap();
+    /// assert_eq!(a.pow(&FlexInt::from_int(7, 8), true), (FlexInt::min_value(8, true), false));
+    ///
+    /// // Negative exponents
+    /// let minus_one = FlexInt::from_int(1, 8).negate().unwrap();
+    /// assert_eq!(a.pow(&minus_one, true), (FlexInt::new(8), false));
+    /// assert_eq!(minus_one.pow(&minus_one, true), (minus_one.clone(), false));
+    /// assert_eq!(FlexInt::new(8).pow(&minus_one, true), (FlexInt::new(8), true));
+    /// ```
+    pub fn pow(&self, exponent: &FlexInt, signed: bool) -> (FlexInt, bool) {
+        self.validate_size(exponent);
+
+        let one = FlexInt::from_int(1, self.size());
+        if signed && exponent.is_negative() {
+            return if self.is_zero() {
+                (FlexInt::new(self.size()), true)
+            } else if *self == one {
+                (one, false)
+            } else if self.count_ones() == self.size() {
+                // -1 stays as -1 for odd powers
+                (if exponent.bit(0) { self.clone() } else { one }, false)
+            } else {
+                (FlexInt::new(self.size()), false)
+            };
+        }
+
+        // Square and multiply, working down from the most significant set bit of the exponent.
+        // Each intermediate result is the base raised to a smaller power than the final one, so if
+        // any of them overflows, the final result would too
+        let mut result = one;
+        let mut overflow = false;
+        let Some(top) = exponent.bits.iter().rposition(|b| *b) else { return (result, false) };
+        for bit in exponent.bits[..=top].iter().rev() {
+            let (squared, over) = result.multiply(&result, signed);
+            result = squared;
+            overflow = overflow || over;
+
+            if *bit {
+                let (multiplied, over) = result.multiply(self, signed);
+                result = multiplied;
+                overflow = overflow || over;
+            }
+        }
+
+        (result, overflow)
+    }
+
     /// Performs bitwise AND on the bits of this number and another.
     /// 
     /// Panics unless the two integers are the same size.
@@ -358,4 +711,60 @@ impl FlexInt {
         }
         result
     }
+
+    /// Performs bitwise OR on the bits of this number and another.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(0b10110111, 8);
+    /// let b = FlexInt::from_int(0b01100110, 8);
+    /// assert_eq!(a.bitwise_or(&b), FlexInt::from_int(0b11110111, 8));
+    /// ```
+    pub fn bitwise_or(&self, other: &FlexInt) -> FlexInt {
+        self.validate_size(other);
+        
+        let mut result = FlexInt::new(self.size());
+        for i in 0..self.size() {
+            result.bits[i] = self.bits[i] || other.bits[i];
+        }
+        result
+    }
+
+    /// Performs bitwise XOR on the bits of this number and another.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(0b10110111, 8);
+    /// let b = FlexInt::from_int(0b01100110, 8);
+    /// assert_eq!(a.bitwise_xor(&b), FlexInt::from_int(0b11010001, 8));
+    /// ```
+    pub fn bitwise_xor(&self, other: &FlexInt) -> FlexInt {
+        self.validate_size(other);
+        
+        let mut result = FlexInt::new(self.size());
+        for i in 0..self.size() {
+            result.bits[i] = self.bits[i] ^ other.bits[i];
+        }
+        result
+    }
+
+    /// Counts the number of bits which differ between this number and another.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(0b1010, 4);
+    /// let b = FlexInt::from_int(0b1100, 4);
+    /// assert_eq!(a.hamming_distance(&b), 2);
+    /// assert_eq!(a.hamming_distance(&a), 0);
+    /// ```
+    pub fn hamming_distance(&self, other: &FlexInt) -> usize {
+        self.validate_size(other);
+        self.bitwise_xor(other).count_ones()
+    }
 }
\ No newline at end of file
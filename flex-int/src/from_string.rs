@@ -1,7 +1,35 @@
-use alloc::{string::ToString, vec::Vec};
+use alloc::string::ToString;
+use core::fmt;
 
 use crate::FlexInt;
 
+/// The reason that a string couldn't be parsed into a [FlexInt].
+/// 
+/// ```rust
+/// # use flex_int::ParseFlexIntError;
+/// assert_eq!(ParseFlexIntError::UnsupportedRadix(3).to_string(), "unsupported radix 3");
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ParseFlexIntError {
+    /// The radix isn't one of 2, 8, 10 or 16.
+    UnsupportedRadix(u32),
+
+    /// A character isn't a valid digit in the radix, or is a sign where one isn't allowed.
+    InvalidDigit,
+}
+
+impl fmt::Display for ParseFlexIntError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseFlexIntError::UnsupportedRadix(radix) => write!(f, "unsupported radix {radix}"),
+            ParseFlexIntError::InvalidDigit => write!(f, "invalid digit"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseFlexIntError {}
+
 impl FlexInt {
         /// Creates a new unsigned integer of a given size by parsing a string of decimal digits.
     /// 
@@ -132,7 +160,15 @@ impl FlexInt {
                 'F' | 'f' => [true,  true,  true,  true ],
                 _ => return None,
             };
-            result.bits.splice(0..4, bits);
+
+            // For sizes smaller than a nibble, any set bits which don't fit are also overflow
+            for (i, bit) in bits.into_iter().enumerate() {
+                if i < size {
+                    *result.bit_mut(i) = bit;
+                } else if bit {
+                    overflow = true;
+                }
+            }
         }
 
         Some((result, overflow))
@@ -156,6 +192,81 @@ impl FlexInt {
         Self::from_signed_string(s, size, Self::from_unsigned_hex_string)
     }
 
+    /// Creates a new unsigned integer of a given size by parsing a string of octal digits.
+    /// 
+    /// Only the digits 0 to 7 are permitted in the string; this will return None if other
+    /// characters are encountered.
+    /// 
+    /// Also returns a boolean indicating whether the digits overflow the given size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (i_str, over) = FlexInt::from_unsigned_octal_string("1234", 16).unwrap();
+    /// let i_num = FlexInt::from_int(0o1234, 16);
+    /// assert_eq!(i_str, i_num);
+    /// assert!(!over);
+    /// 
+    /// // 8 bits don't divide evenly into digits, so the top digit can only be up to 3
+    /// let (i_str, over) = FlexInt::from_unsigned_octal_string("377", 8).unwrap();
+    /// assert_eq!(i_str, FlexInt::from_int(0xFF, 8));
+    /// assert!(!over);
+    /// 
+    /// let (i_str, over) = FlexInt::from_unsigned_octal_string("477", 8).unwrap();
+    /// assert_eq!(i_str, FlexInt::from_int(0x3F, 8));
+    /// assert!(over);
+    /// 
+    /// assert_eq!(FlexInt::from_unsigned_octal_string("128", 8), None);
+    /// ```
+    pub fn from_unsigned_octal_string(s: &str, size: usize) -> Option<(Self, bool)> {
+        let mut result = Self::new(size);
+        let mut overflow = false;
+
+        for c in s.chars() {
+            let d = char::to_digit(c, 8)?;
+
+            // Shift left by 3 - if any of the bits that this will truncate are 1s, then overflow
+            // has occurred
+            let (new_result, shifted_bits) = result.pop_shift_left(3);
+            result = new_result;
+            if shifted_bits.contains(&true) {
+                overflow = true;
+            }
+
+            // For sizes smaller than a digit, any set bits which don't fit are also overflow
+            for i in 0..3 {
+                let bit = d & (1 << i) != 0;
+                if i < size {
+                    *result.bit_mut(i) = bit;
+                } else if bit {
+                    overflow = true;
+                }
+            }
+        }
+
+        Some((result, overflow))
+    }
+
+    /// Creates a new signed integer of a given size by parsing a string of octal digits.
+    /// 
+    /// The first character may optionally be a sign, then only the digits 0 to 7 are permitted in
+    /// the string. This will return None if other characters are encountered.
+    /// 
+    /// Also returns a boolean indicating whether the digits overflow the given size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (i_str, over) = FlexInt::from_signed_octal_string("-1234", 16).unwrap();
+    /// let i_num = FlexInt::from_int(0o1234, 16).negate().unwrap();
+    /// assert_eq!(i_str, i_num);
+    /// assert!(!over);
+    /// 
+    /// let (_, over) = FlexInt::from_signed_octal_string("200", 8).unwrap();
+    /// assert!(over);
+    /// ```
+    pub fn from_signed_octal_string(s: &str, size: usize) -> Option<(Self, bool)> {
+        Self::from_signed_string(s, size, Self::from_unsigned_octal_string)
+    }
+
     /// Creates a new unsigned integer of a given size by parsing a string of binary digits.
     /// 
     /// Only '1' and '0' are permitted in the string; this will return None if other characters are
@@ -174,36 +285,49 @@ impl FlexInt {
     /// let i_num = FlexInt::from_int(0b01100110, 8);
     /// assert_eq!(i_str, i_num);
     /// assert!(over);
+    /// 
+    /// let (i_str, over) = FlexInt::from_unsigned_binary_string("1011", 8).unwrap();
+    /// assert_eq!(i_str, FlexInt::from_int(0b1011, 8));
+    /// assert!(!over);
+    /// 
+    /// // Leading zeroes never overflow
+    /// let (i_str, over) = FlexInt::from_unsigned_binary_string("00001011", 4).unwrap();
+    /// assert_eq!(i_str, FlexInt::from_int(0b1011, 4));
+    /// assert!(!over);
+    /// 
+    /// let (i_str, over) = FlexInt::from_unsigned_binary_string("11011", 4).unwrap();
+    /// assert_eq!(i_str, FlexInt::from_int(0b1011, 4));
+    /// assert!(over);
+    /// 
+    /// assert_eq!(FlexInt::from_unsigned_binary_string("1021", 8), None);
     /// ```
     pub fn from_unsigned_binary_string(s: &str, size: usize) -> Option<(Self, bool)> {
-        let mut bits = s.chars()
-            // Skip leading zeroes
-            .skip_while(|x| *x == '0')
-            // Create true/false bits from characters
-            .map(|x| match x {
-                '0' => Some(false),
-                '1' => Some(true),
-                _ => None
-            })
-            .collect::<Option<Vec<_>>>()?
-            // Reverse to give our LSB->MSB order
-            .into_iter()
-            .rev()
-            .collect::<Vec<_>>();
-
-        if bits.len() > size {
-            // We already reversed, so we can pop from the end to trim
-            while bits.len() > size {
-                bits.pop();
+        let mut result = Self::new(size);
+        let mut overflow = false;
+
+        for c in s.chars() {
+            let bit = match c {
+                '0' => false,
+                '1' => true,
+                _ => return None,
+            };
+
+            // Shift left by 1 - if the bit that this truncates is a 1, then overflow has occurred
+            let (new_result, shifted_bits) = result.pop_shift_left(1);
+            result = new_result;
+            if shifted_bits.contains(&true) {
+                overflow = true;
             }
-            return Some((Self::from_bits(&bits), true))
-        }
 
-        // Add padding bits if needed
-        while bits.len() < size {
-            bits.push(false);
+            // For zero-width integers, there's nowhere to put the bit
+            if size > 0 {
+                *result.bit_mut(0) = bit;
+            } else if bit {
+                overflow = true;
+            }
         }
-        Some((Self::from_bits(&bits), false))
+
+        Some((result, overflow))
     }
 
     /// Creates a new signed integer of a given size by parsing a string of binary digits.
@@ -219,11 +343,157 @@ impl FlexInt {
     /// let i_num = FlexInt::from_int(0b1101110101, 16).negate().unwrap();
     /// assert_eq!(i_str, i_num);
     /// assert!(!over);
+    /// 
+    /// // The most negative 4-bit value fits, but not one beyond it
+    /// let (i_str, over) = FlexInt::from_signed_binary_string("-1000", 4).unwrap();
+    /// assert_eq!(i_str, FlexInt::from_int(0b1000, 4));
+    /// assert!(!over);
+    /// 
+    /// let (_, over) = FlexInt::from_signed_binary_string("-1001", 4).unwrap();
+    /// assert!(over);
+    /// 
+    /// let (_, over) = FlexInt::from_signed_binary_string("1000", 4).unwrap();
+    /// assert!(over);
     /// ```
     pub fn from_signed_binary_string(s: &str, size: usize) -> Option<(Self, bool)> {
         Self::from_signed_string(s, size, Self::from_unsigned_binary_string)
     }
 
+    /// Creates a new integer of a given size by parsing a string of digits in the given radix, which
+    /// may be 2, 8, 10 or 16. This is equivalent to calling the parser for that base, such as
+    /// [Self::from_signed_hex_string] if `signed` is true, or [Self::from_unsigned_hex_string]
+    /// otherwise.
+    /// 
+    /// Returns None if the radix isn't supported, or if any character isn't a valid digit in the
+    /// radix (other than a leading sign, if signed). Otherwise, also returns a boolean indicating
+    /// whether the digits overflow the given size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (i, over) = FlexInt::from_str_radix("12A4", 16, false, 16).unwrap();
+    /// assert_eq!(i, FlexInt::from_int(0x12A4, 16));
+    /// assert!(!over);
+    /// 
+    /// let (i, over) = FlexInt::from_str_radix("-101", 2, true, 8).unwrap();
+    /// assert_eq!(i, FlexInt::from_int(5, 8).negate().unwrap());
+    /// assert!(!over);
+    /// 
+    /// let (_, over) = FlexInt::from_str_radix("400", 8, false, 8).unwrap();
+    /// assert!(over);
+    /// 
+    /// // Digits must be valid in the radix
+    /// assert_eq!(FlexInt::from_str_radix("12A4", 10, false, 16), None);
+    /// assert_eq!(FlexInt::from_str_radix("-1", 10, false, 16), None);
+    /// 
+    /// // Only some radices are supported
+    /// assert_eq!(FlexInt::from_str_radix("12", 3, false, 16), None);
+    /// ```
+    pub fn from_str_radix(s: &str, radix: u32, signed: bool, size: usize) -> Option<(Self, bool)> {
+        Self::parse_str_radix(s, radix, signed, size).ok()
+    }
+
+    /// Like [Self::from_str_radix], but returns an error describing why the string couldn't be
+    /// parsed, rather than `None`.
+    /// 
+    /// ```rust
+    /// # use flex_int::{FlexInt, ParseFlexIntError};
+    /// let (i, over) = FlexInt::parse_str_radix("-80", 16, true, 8).unwrap();
+    /// assert_eq!(i, FlexInt::from_int(0x80, 8));
+    /// assert!(!over);
+    /// 
+    /// assert_eq!(FlexInt::parse_str_radix("12A4", 10, false, 16), Err(ParseFlexIntError::InvalidDigit));
+    /// assert_eq!(FlexInt::parse_str_radix("12", 3, false, 16), Err(ParseFlexIntError::UnsupportedRadix(3)));
+    /// ```
+    pub fn parse_str_radix(s: &str, radix: u32, signed: bool, size: usize) -> Result<(Self, bool), ParseFlexIntError> {
+        let parse: fn(&str, usize) -> Option<(Self, bool)> = match (radix, signed) {
+            (2, false) => Self::from_unsigned_binary_string,
+            (2, true) => Self::from_signed_binary_string,
+            (8, false) => Self::from_unsigned_octal_string,
+            (8, true) => Self::from_signed_octal_string,
+            (10, false) => Self::from_unsigned_decimal_string,
+            (10, true) => Self::from_signed_decimal_string,
+            (16, false) => Self::from_unsigned_hex_string,
+            (16, true) => Self::from_signed_hex_string,
+            _ => return Err(ParseFlexIntError::UnsupportedRadix(radix)),
+        };
+        parse(s, size).ok_or(ParseFlexIntError::InvalidDigit)
+    }
+
+    /// Creates a new integer of a given size from a sequence of digit values in the given radix,
+    /// most-significant first. Unlike the string conversions, each digit is a number (e.g. 0xA,
+    /// not `'A'`), which avoids converting them to characters when they're already known.
+    /// 
+    /// If `signed` is true, overflow is checked against the range of a signed integer. If
+    /// `negative` is true, the digits are the magnitude of a negative number - this is always
+    /// treated as signed.
+    /// 
+    /// Returns None if the radix isn't between 2 and 16, or if any digit is out of range for the
+    /// radix. Otherwise, also returns a boolean indicating whether the digits overflow the given
+    /// size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (i, over) = FlexInt::from_ascii_digits(&[1, 2, 0xA, 4], 16, false, false, 16).unwrap();
+    /// assert_eq!(i, FlexInt::from_int(0x12A4, 16));
+    /// assert!(!over);
+    /// 
+    /// let (i, over) = FlexInt::from_ascii_digits(&[1, 2, 8], 10, true, true, 8).unwrap();
+    /// assert_eq!(i, FlexInt::from_signed_decimal_string("-128", 8).unwrap().0);
+    /// assert!(!over);
+    /// 
+    /// let (_, over) = FlexInt::from_ascii_digits(&[1, 2, 8], 10, true, false, 8).unwrap();
+    /// assert!(over);
+    /// 
+    /// assert_eq!(FlexInt::from_ascii_digits(&[1, 2], 2, false, false, 8), None);
+    /// ```
+    pub fn from_ascii_digits(digits: &[u8], radix: u32, signed: bool, negative: bool, size: usize) -> Option<(Self, bool)> {
+        if !(2..=16).contains(&radix) || digits.iter().any(|d| *d as u32 >= radix) {
+            return None;
+        }
+
+        let mut result = Self::new(size);
+        let mut overflow = false;
+        if radix.is_power_of_two() {
+            // Each digit is a whole number of bits, so it can be shifted in, like the string
+            // conversions do. Any set bits which are shifted or don't fit out are overflow
+            let digit_bits = radix.trailing_zeros() as usize;
+            for digit in digits {
+                let (shifted, popped) = result.pop_shift_left(digit_bits);
+                result = shifted;
+                overflow = overflow || popped.contains(&true);
+
+                for i in 0..digit_bits {
+                    let bit = (digit >> i) & 1 == 1;
+                    if i < size {
+                        *result.bit_mut(i) = bit;
+                    } else if bit {
+                        overflow = true;
+                    }
+                }
+            }
+        } else {
+            // Each step is calculated with a few more bits than needed, so that any bits which
+            // spill out of the size can be detected as overflow, and then discarded. A radix of
+            // at most 16 (and a digit below it) can't spill further than this
+            let wide_size = size + 5;
+            let wide_radix = Self::from_int(radix as u64, wide_size);
+            for digit in digits {
+                let (wide, _) = result.zero_extend(wide_size).multiply(&wide_radix, false);
+                let (wide, _) = wide.add(&Self::from_int(*digit as u64, wide_size), false);
+
+                let (narrow, _, spilled_ones) = wide.shrink(size);
+                overflow = overflow || spilled_ones > 0;
+                result = narrow;
+            }
+        }
+
+        if signed || negative {
+            Some(Self::apply_sign(result, overflow, negative))
+        } else {
+            Some((result, overflow))
+        }
+    }
+
     /// A convenience methods which performs a signed string-to-number conversion by using an
     /// existing implementation of an unsigned conversion.
     fn from_signed_string(s: &str, size: usize, unsigned_string_fn: impl FnOnce(&str, usize) -> Option<(Self, bool)>) -> Option<(Self, bool)> {
@@ -244,8 +514,13 @@ impl FlexInt {
         }
 
         // Parse as an unsigned number
-        let (num, mut over) = unsigned_string_fn(&s, size)?;
+        let (num, over) = unsigned_string_fn(&s, size)?;
+        Some(Self::apply_sign(num, over, is_negative))
+    }
 
+    /// Given the magnitude of a signed number which has been parsed as unsigned, checks that it
+    /// fits in the signed range, and negates it if needed.
+    fn apply_sign(num: Self, mut over: bool, is_negative: bool) -> (Self, bool) {
         // If the most-significant bit is set, there's already been overflow - unless this number
         // is going to be negated to the largest possible negative number
         // (Remember we can represent one more negative number than positive number)
@@ -256,15 +531,15 @@ impl FlexInt {
         // Try to negate if the number is supposed to be negative, overflow if this fails
         if is_negative {
             if let Some(negated) = num.negate() {
-                Some((negated, over))
+                (negated, over)
             } else {
                 // Negation might fail if we had the largest possible negative before - override
                 // this
                 let over = !num.is_largest_possible_negative();
-                Some((num, over))
+                (num, over)
             }
         } else {
-            Some((num, over))
+            (num, over)
         }
     }
 }
\ No newline at end of file
@@ -1,15 +1,33 @@
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
 mod from_string;
 mod to_string;
 mod op;
 mod binary_op;
+mod bcd;
 
+pub use from_string::ParseFlexIntError;
+
+use core::cmp::Ordering;
 use alloc::{vec, vec::Vec};
 
 /// An arbitrary-precision integer, stored as a sequence of bits.
-#[derive(PartialEq, Eq, Clone, Debug)]
+/// 
+/// Integers are only equal if they have the same size as well as the same value, and the same goes
+/// for their hashes, so integers of different sizes can be used as distinct keys:
+/// 
+/// ```rust
+/// # use flex_int::FlexInt;
+/// use std::collections::HashMap;
+/// 
+/// let mut names = HashMap::new();
+/// names.insert(FlexInt::from_int(0x7F, 8), "S8 max");
+/// names.insert(FlexInt::from_int(0x7F, 16), "not S16 max");
+/// assert_eq!(names[&FlexInt::from_int(127, 8)], "S8 max");
+/// assert_eq!(names.get(&FlexInt::from_int(127, 32)), None);
+/// ```
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
 pub struct FlexInt {
     /// The bits composing this integer.
     /// 
@@ -31,12 +49,86 @@ impl FlexInt {
 
     /// Creates an integer of a particular number of bits, where only the least-significant bit is
     /// set.
+    /// 
+    /// Panics if the size is zero, since a zero-width integer cannot represent one.
     pub fn new_one(size: usize) -> Self {
+        if size == 0 {
+            panic!("cannot create a one with zero bits");
+        }
+
         let mut result = Self::new(size);
         *result.bit_mut(0) = true;
         result
     }
 
+    /// Creates an integer of a particular number of bits, where every bit is set.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::all_ones(8);
+    /// assert_eq!(i, FlexInt::from_int(0xFF, 8));
+    /// assert_eq!(i.to_signed_decimal_string(), "-1");
+    /// ```
+    pub fn all_ones(size: usize) -> Self {
+        Self { bits: vec![true; size] }
+    }
+
+    /// Creates an integer of a particular number of bits by repeating a pattern of bits, starting
+    /// from the least-significant bit. The pattern is given least-significant first, like
+    /// [`from_bits`], and the last repetition is truncated if it doesn't fit.
+    /// 
+    /// Panics if the pattern is empty, unless the size is zero.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// // 0b10 repeated
+    /// let i = FlexInt::repeat_pattern(&[false, true], 8);
+    /// assert_eq!(i, FlexInt::from_int(0b10101010, 8));
+    /// 
+    /// // 0b011 repeated, with the final repetition cut short
+    /// let i = FlexInt::repeat_pattern(&[true, true, false], 8);
+    /// assert_eq!(i, FlexInt::from_int(0b11011011, 8));
+    /// ```
+    pub fn repeat_pattern(pattern: &[bool], size: usize) -> Self {
+        if pattern.is_empty() && size > 0 {
+            panic!("cannot repeat an empty pattern");
+        }
+
+        Self { bits: pattern.iter().copied().cycle().take(size).collect() }
+    }
+
+    /// Creates an integer of a particular number of bits, holding the largest value which can be
+    /// represented with that many bits.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::max_value(8, false), FlexInt::from_int(255, 8));
+    /// assert_eq!(FlexInt::max_value(8, true), FlexInt::from_int(127, 8));
+    /// ```
+    pub fn max_value(size: usize, signed: bool) -> Self {
+        let mut result = Self { bits: vec![true; size] };
+        if signed && size > 0 {
+            *result.bit_mut(size - 1) = false;
+        }
+        result
+    }
+
+    /// Creates an integer of a particular number of bits, holding the smallest value which can be
+    /// represented with that many bits.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::min_value(8, false), FlexInt::from_int(0, 8));
+    /// assert_eq!(FlexInt::min_value(8, true).to_signed_decimal_string(), "-128");
+    /// ```
+    pub fn min_value(size: usize, signed: bool) -> Self {
+        let mut result = Self::new(size);
+        if signed && size > 0 {
+            *result.bit_mut(size - 1) = true;
+        }
+        result
+    }
+
     /// Creates a new integer from a slice of bits, with the least-significant first.
     pub fn from_bits(bits: &[bool]) -> Self {
         Self { bits: bits.to_vec() }
@@ -59,6 +151,97 @@ impl FlexInt {
         Self::from_bits(&bits)
     }
 
+    /// Converts this integer to a `u64`, treating it as unsigned, or returns `None` if it's too
+    /// large to fit.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(1234, 16).to_u64(), Some(1234));
+    /// assert_eq!(FlexInt::max_value(64, false).to_u64(), Some(u64::MAX));
+    /// assert_eq!(FlexInt::from_int(1234, 100).to_u64(), Some(1234));
+    /// assert_eq!(FlexInt::max_value(65, false).to_u64(), None);
+    /// ```
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.bits.iter().skip(64).any(|b| *b) {
+            return None;
+        }
+
+        Some(self.bits.iter()
+            .take(64)
+            .enumerate()
+            .fold(0, |value, (i, bit)| value | ((*bit as u64) << i)))
+    }
+
+    /// Converts this integer to an `i64`, treating it as signed, or returns `None` if it's too
+    /// large or small to fit.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(1234, 16).to_i64(), Some(1234));
+    /// 
+    /// let (i, _) = FlexInt::from_signed_decimal_string("-1234", 16).unwrap();
+    /// assert_eq!(i.to_i64(), Some(-1234));
+    /// 
+    /// // Unlike `to_u64`, the top bit is the sign
+    /// assert_eq!(FlexInt::from_int(0xFF, 8).to_i64(), Some(-1));
+    /// assert_eq!(FlexInt::from_int(0xFF, 8).to_u64(), Some(255));
+    /// 
+    /// assert_eq!(FlexInt::min_value(64, true).to_i64(), Some(i64::MIN));
+    /// assert_eq!(FlexInt::min_value(65, true).to_i64(), None);
+    /// ```
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.size() <= 64 {
+            let value = self.sign_extend(64).to_u64()?;
+            return Some(value as i64);
+        }
+
+        // Any bits beyond those which fit must just be extending the sign
+        let sign = self.is_negative();
+        if self.bits[63..].iter().any(|b| *b != sign) {
+            return None;
+        }
+        let (low, _, _) = self.shrink(64);
+        Some(low.to_u64()? as i64)
+    }
+
+    /// Packs the bits of this integer into bytes, least-significant byte first. If the size isn't a
+    /// multiple of 8, the final byte is padded with zeroes.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0x1234, 16).to_le_bytes(), vec![0x34, 0x12]);
+    /// assert_eq!(FlexInt::from_int(0x1FF, 9).to_le_bytes(), vec![0xFF, 0x01]);
+    /// assert_eq!(FlexInt::new(0).to_le_bytes(), vec![]);
+    /// ```
+    pub fn to_le_bytes(&self) -> Vec<u8> {
+        self.bits.chunks(8)
+            .map(|chunk| chunk.iter()
+                .enumerate()
+                .fold(0, |byte, (i, bit)| byte | ((*bit as u8) << i)))
+            .collect()
+    }
+
+    /// Creates an integer of the given size from bytes, least-significant byte first, as produced
+    /// by [Self::to_le_bytes]. Only the `size` least-significant bits are used - any others are
+    /// ignored, and if there aren't enough bytes, the rest of the bits are zero.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_le_bytes(&[0x34, 0x12], 16), FlexInt::from_int(0x1234, 16));
+    /// assert_eq!(FlexInt::from_le_bytes(&[0x34, 0x12], 12), FlexInt::from_int(0x234, 12));
+    /// assert_eq!(FlexInt::from_le_bytes(&[0x34], 16), FlexInt::from_int(0x34, 16));
+    /// 
+    /// // Round-tripping gives the same integer, whatever the size
+    /// let i = FlexInt::from_int(0b1011011, 7);
+    /// assert_eq!(FlexInt::from_le_bytes(&i.to_le_bytes(), 7), i);
+    /// ```
+    pub fn from_le_bytes(bytes: &[u8], size: usize) -> Self {
+        let bits = (0..size)
+            .map(|i| bytes.get(i / 8).is_some_and(|byte| byte & (1 << (i % 8)) != 0))
+            .collect::<Vec<_>>();
+        Self::from_bits(&bits)
+    }
+
     /// Gets the bits of this number, least-significant first.
     pub fn bits(&self) -> &[bool] {
         &self.bits
@@ -85,6 +268,41 @@ impl FlexInt {
         &mut self.bits[index]
     }
 
+    /// Gets a window of `len` bits of this number, starting from the bit at index `start` (where 0 is
+    /// the least-significant), as a new `len`-bit integer.
+    /// 
+    /// Panics if the window extends beyond the bits of the number.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0xAB, 8);
+    /// assert_eq!(i.bit_range(4, 4), FlexInt::from_int(0xA, 4));
+    /// assert_eq!(i.bit_range(0, 4), FlexInt::from_int(0xB, 4));
+    /// assert_eq!(i.bit_range(3, 3), FlexInt::from_int(0b101, 3));
+    /// assert_eq!(i.bit_range(8, 0), FlexInt::new(0));
+    /// ```
+    pub fn bit_range(&self, start: usize, len: usize) -> FlexInt {
+        Self::from_bits(&self.bits[start..(start + len)])
+    }
+
+    /// Overwrites a window of bits of this number with the bits of `value`, starting from the bit at
+    /// index `start` (where 0 is the least-significant). The window is as wide as `value`.
+    /// 
+    /// Panics if the window extends beyond the bits of the number.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let mut i = FlexInt::from_int(0xAB, 8);
+    /// i.set_bit_range(4, &FlexInt::from_int(0x5, 4));
+    /// assert_eq!(i, FlexInt::from_int(0x5B, 8));
+    /// 
+    /// i.set_bit_range(1, &FlexInt::from_int(0b00, 2));
+    /// assert_eq!(i, FlexInt::from_int(0x59, 8));
+    /// ```
+    pub fn set_bit_range(&mut self, start: usize, value: &FlexInt) {
+        self.bits[start..(start + value.size())].copy_from_slice(&value.bits);
+    }
+
     /// Gets the number of bits which compose this integer.
     /// 
     /// This also includes bits which are unnecessary, e.g. `0001` will have a size of 4 bits.
@@ -95,23 +313,13 @@ impl FlexInt {
     /// Gets the bits of this number, with any leading zeroes (i.e. those at the most-significant
     /// bit) removed.
     pub(crate) fn bits_without_leading_zeroes(&self) -> Vec<bool> {
-        // Remember our bit representation goes from LSB to MSB, so in our representation they're
-        // actually trailing - handle this by reversing first
-        self.bits.iter()
-            .rev()
-            .copied()
-            .skip_while(|x| !*x)
-            .collect::<Vec<_>>()
-            .iter()
-            .rev()
-            .copied()
-            .collect::<Vec<_>>()
+        self.bits[..(self.size() - self.leading_zeros())].to_vec()
     }
 
     /// Determines whether this number is storing the largest possible negative value for its number
     /// of bits - that is, the most-significant bit is set, and no others are.
     pub(crate) fn is_largest_possible_negative(&self) -> bool {
-        if self.bit(self.size() - 1) {
+        if self.is_negative() {
             for i in 0..(self.size() - 1) {
                 if self.bit(i) {
                     return false
@@ -128,10 +336,130 @@ impl FlexInt {
         self.bits.iter().all(|b| !*b)
     }
 
+    /// Counts the number of bits which are set.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0b1011_0001, 8).count_ones(), 4);
+    /// ```
+    pub fn count_ones(&self) -> usize {
+        self.bits.iter().filter(|b| **b).count()
+    }
+
+    /// Counts the number of bits which are not set.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0b1011_0001, 8).count_zeros(), 4);
+    /// assert_eq!(FlexInt::from_int(0b1011_0001, 12).count_zeros(), 8);
+    /// ```
+    pub fn count_zeros(&self) -> usize {
+        self.size() - self.count_ones()
+    }
+
+    /// Counts the number of unset bits before the first set bit, starting from the
+    /// most-significant bit. If no bits are set, this is the size of the number.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0b0010, 8).leading_zeros(), 6);
+    /// assert_eq!(FlexInt::from_int(0b1000_0000, 8).leading_zeros(), 0);
+    /// assert_eq!(FlexInt::new(8).leading_zeros(), 8);
+    /// ```
+    pub fn leading_zeros(&self) -> usize {
+        // Remember our bit representation goes from LSB to MSB, so in our representation they're
+        // actually trailing
+        self.bits.iter().rev().take_while(|b| !**b).count()
+    }
+
+    /// Counts the number of unset bits before the first set bit, starting from the
+    /// least-significant bit. If no bits are set, this is the size of the number.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0b0010, 8).trailing_zeros(), 1);
+    /// assert_eq!(FlexInt::from_int(0b1000_0000, 8).trailing_zeros(), 7);
+    /// assert_eq!(FlexInt::new(8).trailing_zeros(), 8);
+    /// ```
+    pub fn trailing_zeros(&self) -> usize {
+        self.bits.iter().take_while(|b| !**b).count()
+    }
+
+    /// Gets the smallest number of bits which can represent the value of this number, so that
+    /// shrinking it to any fewer bits would change its value. Even zero needs 1 bit.
+    /// 
+    /// If `signed` is true, this includes the sign bit, so a positive number needs one more bit
+    /// than it would if unsigned.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(128, 16).minimum_bits(false), 8);
+    /// assert_eq!(FlexInt::from_int(128, 16).minimum_bits(true), 9);
+    /// 
+    /// let (i, _) = FlexInt::from_signed_decimal_string("-128", 16).unwrap();
+    /// assert_eq!(i.minimum_bits(true), 8);
+    /// let (i, _) = FlexInt::from_signed_decimal_string("-129", 16).unwrap();
+    /// assert_eq!(i.minimum_bits(true), 9);
+    /// 
+    /// assert_eq!(FlexInt::new(16).minimum_bits(false), 1);
+    /// assert_eq!(FlexInt::new(16).minimum_bits(true), 1);
+    /// ```
+    pub fn minimum_bits(&self, signed: bool) -> usize {
+        if signed {
+            // Every bit matching the sign bit can be cut, except the sign bit itself
+            let sign = self.is_negative();
+            let sign_bits = self.bits.iter().rev().take_while(|b| **b == sign).count();
+            self.size() - sign_bits + 1
+        } else {
+            (self.size() - self.leading_zeros()).max(1)
+        }
+    }
+
+    /// Gets the parity of this number - that is, the XOR of all of its bits. This is true if an odd
+    /// number of bits are set.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0b1011_0001, 8).parity(), false);
+    /// assert_eq!(FlexInt::from_int(0b1011_0011, 8).parity(), true);
+    /// ```
+    pub fn parity(&self) -> bool {
+        self.count_ones() % 2 == 1
+    }
+
     /// Whether this number is negative, assuming it is being treated as signed.
+    /// 
+    /// A zero-width integer has no sign bit, so is never negative.
     pub fn is_negative(&self) -> bool {
         // Most-significant bit is sign
-        self.bit(self.size() - 1)
+        self.bits.last().copied().unwrap_or(false)
+    }
+
+    /// Gets the sign of this number: -1 if it's negative, 0 if it's zero, or 1 if it's positive.
+    /// 
+    /// If `signed` is false, the number can't be negative, so this is only ever 0 or 1.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0xFF, 8);
+    /// assert_eq!(i.signum(false), 1);
+    /// assert_eq!(i.signum(true), -1);
+    /// 
+    /// let i = FlexInt::from_int(0x7F, 8);
+    /// assert_eq!(i.signum(false), 1);
+    /// assert_eq!(i.signum(true), 1);
+    /// 
+    /// assert_eq!(FlexInt::new(8).signum(false), 0);
+    /// assert_eq!(FlexInt::new(8).signum(true), 0);
+    /// ```
+    pub fn signum(&self, signed: bool) -> i8 {
+        if self.is_zero() {
+            0
+        } else if signed && self.is_negative() {
+            -1
+        } else {
+            1
+        }
     }
 
     /// Whether this number is strictly greater than other, assuming that both numbers are unsigned.
@@ -146,19 +474,93 @@ impl FlexInt {
     /// assert_eq!(b.is_greater_than_unsigned(&a), false);
     /// ```
     pub fn is_greater_than_unsigned(&self, other: &FlexInt) -> bool {
+        self.compare(other, false) == Ordering::Greater
+    }
+
+    /// Whether this number is strictly greater than other, assuming that both numbers are signed.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (minus_one, _) = FlexInt::from_signed_decimal_string("-1", 8).unwrap();
+    /// let one = FlexInt::from_int(1, 8);
+    /// assert_eq!(one.is_greater_than_signed(&minus_one), true);
+    /// assert_eq!(minus_one.is_greater_than_signed(&one), false);
+    /// 
+    /// // Unsigned, -1 has the same bits as 255
+    /// assert_eq!(minus_one.is_greater_than_unsigned(&one), true);
+    /// ```
+    pub fn is_greater_than_signed(&self, other: &FlexInt) -> bool {
+        self.compare(other, true) == Ordering::Greater
+    }
+
+    /// Whether this number is strictly less than other, assuming that both numbers are unsigned.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(12, 8);
+    /// let b = FlexInt::from_int(3, 8);
+    /// assert_eq!(b.is_less_than_unsigned(&a), true);
+    /// assert_eq!(a.is_less_than_unsigned(&b), false);
+    /// assert_eq!(a.is_less_than_unsigned(&a), false);
+    /// ```
+    pub fn is_less_than_unsigned(&self, other: &FlexInt) -> bool {
+        self.compare(other, false) == Ordering::Less
+    }
+
+    /// Whether this number is strictly less than other, assuming that both numbers are signed.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (minus_one, _) = FlexInt::from_signed_decimal_string("-1", 8).unwrap();
+    /// let one = FlexInt::from_int(1, 8);
+    /// assert_eq!(minus_one.is_less_than_signed(&one), true);
+    /// assert_eq!(one.is_less_than_signed(&minus_one), false);
+    /// ```
+    pub fn is_less_than_signed(&self, other: &FlexInt) -> bool {
+        self.compare(other, true) == Ordering::Less
+    }
+
+    /// Compares this number with another, treating both as either signed or unsigned.
+    /// 
+    /// Panics unless the two integers are the same size.
+    /// 
+    /// ```rust
+    /// # use core::cmp::Ordering;
+    /// # use flex_int::FlexInt;
+    /// let (minus_one, _) = FlexInt::from_signed_decimal_string("-1", 8).unwrap();
+    /// let (minus_two, _) = FlexInt::from_signed_decimal_string("-2", 8).unwrap();
+    /// let one = FlexInt::from_int(1, 8);
+    /// assert_eq!(minus_one.compare(&one, true), Ordering::Less);
+    /// assert_eq!(minus_one.compare(&one, false), Ordering::Greater);
+    /// assert_eq!(minus_one.compare(&minus_two, true), Ordering::Greater);
+    /// assert_eq!(one.compare(&one, true), Ordering::Equal);
+    /// ```
+    pub fn compare(&self, other: &FlexInt, signed: bool) -> Ordering {
         self.validate_size(other);
 
+        // A negative number is less than any non-negative one. If the signs are the same, then two's
+        // complement means the bits compare just like unsigned numbers
+        if signed && self.is_negative() != other.is_negative() {
+            return if self.is_negative() { Ordering::Less } else { Ordering::Greater };
+        }
+
         // Iterate over bits from most- to least-significant
         for (self_bit, other_bit) in self.bits().iter().zip(other.bits().iter()).rev() {
             match (*self_bit, *other_bit) {
-                (true, false) => return true,
-                (false, true) => return false,
+                (true, false) => return Ordering::Greater,
+                (false, true) => return Ordering::Less,
                 _ => (),
             }
         }
 
         // They're equal!
-        false
+        Ordering::Equal
     }
 
     /// Whether this number equals another.
@@ -6,6 +6,8 @@ impl FlexInt {
     /// Creates a clone of this number which has been sign-extended to a particular number of bits.
     /// This involves repeating the most-significant bit until the number is the required size.
     /// 
+    /// A zero-width integer has no sign bit, so is extended with 0s.
+    /// 
     /// Panics if the new size is less than the current size.
     ///
     /// ```rust
@@ -24,7 +26,7 @@ impl FlexInt {
         }
 
         let mut bits = self.bits.clone();
-        let sign = *bits.last().unwrap();
+        let sign = self.is_negative();
         while bits.len() < new_size {
             bits.push(sign);
         }
@@ -106,11 +108,122 @@ impl FlexInt {
         (Self::from_bits(&bits), zero_count, one_count)
     }
 
+    /// Shrinks this integer to a given size, like [FlexInt::shrink], and returns whether the value
+    /// no longer fits - so the shrunk integer represents a different number.
+    /// 
+    /// Panics if the new size is greater than the current size.
+    pub(crate) fn shrink_checked(&self, new_size: usize, signed: bool) -> (Self, bool) {
+        let (result, cut_zeroes, cut_ones) = self.shrink(new_size);
+        let overflow = if signed {
+            // In a signed number, overflow has only occurred if a mixture of zeroes and ones were
+            // cut. If just ones were cut, then we've shrunk a negative number, and just zeroes a
+            // positive number
+            //
+            // The cut bits must also match the new sign bit, otherwise the sign has changed
+            // e.g.
+            //      \/ cut point
+            //   0b1110000 -> 0b10000    = valid, same signed number
+            //
+            //      \/ cut point
+            //   0b1100000 -> 0b00000    = invalid, different number
+            (cut_zeroes > 0 && cut_ones > 0)
+                || (cut_ones > 0 && !result.is_negative())
+                || (cut_zeroes > 0 && result.is_negative())
+        } else {
+            // In an unsigned number, overflow has occurred if any ones were cut
+            cut_ones > 0
+        };
+
+        (result, overflow)
+    }
+
+    /// Changes the size of this integer, by extending it if the new size is larger (see
+    /// [FlexInt::extend]) or shrinking it if the new size is smaller.
+    /// 
+    /// Also returns a boolean indicating whether shrinking lost significant bits, so that the
+    /// integer no longer represents the same number. Extending never overflows.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// // Extending
+    /// let i = FlexInt::from_int(0b1101, 4);
+    /// assert_eq!(i.resize(8, false), (FlexInt::from_int(0b00001101, 8), false));
+    /// assert_eq!(i.resize(8, true), (FlexInt::from_int(0b11111101, 8), false));
+    /// 
+    /// // Shrinking, without overflow
+    /// let i = FlexInt::from_int(0b11111101, 8);
+    /// assert_eq!(i.resize(4, true), (FlexInt::from_int(0b1101, 4), false));
+    /// let i = FlexInt::from_int(0b00000101, 8);
+    /// assert_eq!(i.resize(4, false), (FlexInt::from_int(0b0101, 4), false));
+    /// 
+    /// // Shrinking, with overflow
+    /// let i = FlexInt::from_int(0b11111101, 8);
+    /// assert_eq!(i.resize(4, false), (FlexInt::from_int(0b1101, 4), true));
+    /// let i = FlexInt::from_int(0b00001101, 8);
+    /// assert_eq!(i.resize(4, true), (FlexInt::from_int(0b1101, 4), true));
+    /// let i = FlexInt::from_int(0b11110101, 8);
+    /// assert_eq!(i.resize(4, true), (FlexInt::from_int(0b0101, 4), true));
+    /// ```
+    pub fn resize(&self, new_size: usize, signed: bool) -> (Self, bool) {
+        if new_size >= self.size() {
+            (self.extend(new_size, signed), false)
+        } else {
+            self.shrink_checked(new_size, signed)
+        }
+    }
+
     /// Returns a clone of this integer with all of its bits flipped.
     pub fn invert(&self) -> FlexInt {
         Self::from_bits(&self.bits.iter().map(|b| !b).collect::<Vec<_>>())
     }
 
+    /// Converts this integer into its (reflected binary) Gray code, in which consecutive values
+    /// differ by exactly one bit. The size is preserved.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0b0101, 4).to_gray_code(), FlexInt::from_int(0b0111, 4));
+    /// assert_eq!(FlexInt::from_int(0b1000, 4).to_gray_code(), FlexInt::from_int(0b1100, 4));
+    /// 
+    /// for i in 0..15 {
+    ///     let a = FlexInt::from_int(i, 4).to_gray_code();
+    ///     let b = FlexInt::from_int(i + 1, 4).to_gray_code();
+    ///     assert_eq!(a.bitwise_xor(&b).count_ones(), 1);
+    /// }
+    /// ```
+    pub fn to_gray_code(&self) -> FlexInt {
+        // Each bit is XORed with the next most-significant bit, i.e. the number XOR itself shifted
+        // right by one
+        let bits = (0..self.size())
+            .map(|i| self.bit(i) ^ self.bits.get(i + 1).copied().unwrap_or(false))
+            .collect::<Vec<_>>();
+        Self::from_bits(&bits)
+    }
+
+    /// Converts this integer from a Gray code back into the number it represents. This is the
+    /// inverse of [FlexInt::to_gray_code]. The size is preserved.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(0b0111, 4).from_gray_code(), FlexInt::from_int(0b0101, 4));
+    /// 
+    /// for i in 0..16 {
+    ///     let a = FlexInt::from_int(i, 4);
+    ///     assert_eq!(a.to_gray_code().from_gray_code(), a);
+    /// }
+    /// ```
+    pub fn from_gray_code(&self) -> FlexInt {
+        // Working down from the most-significant bit, each bit is XORed with the previous decoded
+        // bit
+        let mut bits = vec![false; self.size()];
+        let mut previous = false;
+        for i in (0..self.size()).rev() {
+            previous ^= self.bit(i);
+            bits[i] = previous;
+        }
+        Self::from_bits(&bits)
+    }
+
     /// Returns a clone of this integer which has been numerically negated, assuming that it is
     /// being treated as signed.
     /// 
@@ -149,6 +262,27 @@ impl FlexInt {
         Some(num)
     }
 
+    /// Like [`negate`], but always succeeds by widening the result by one bit if the number is the
+    /// largest possible negative number, which is the only case where the negation doesn't fit.
+    /// 
+    /// Returns the negated number, and whether it was widened.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let a = FlexInt::from_int(0b0110, 4);
+    /// assert_eq!(a.negate_widening(), (FlexInt::from_int(0b1010, 4), false));
+    /// 
+    /// // -8 becomes +8, which needs 5 bits
+    /// let a = FlexInt::from_int(0b1000, 4);
+    /// assert_eq!(a.negate_widening(), (FlexInt::from_int(0b01000, 5), true));
+    /// ```
+    pub fn negate_widening(&self) -> (FlexInt, bool) {
+        match self.negate() {
+            Some(num) => (num, false),
+            None => (self.sign_extend(self.size() + 1).negate().unwrap(), true),
+        }
+    }
+
     /// Returns a clone of this number which has been numerically negated iff the original number is
     /// negative, assuming that this is being treated as signed.
     /// 
@@ -210,6 +344,310 @@ impl FlexInt {
         (result, intermediate_overflow || over_1 || over_2)
     }
 
+    /// Creates a new integer by joining this number with another, where this number provides the
+    /// most-significant bits and `low` provides the least-significant bits.
+    /// 
+    /// The size of the result is the sum of the sizes of both integers.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let high = FlexInt::from_int(0xCA, 8);
+    /// let low = FlexInt::from_int(0xFE, 8);
+    /// assert_eq!(high.concat(&low), FlexInt::from_int(0xCAFE, 16));
+    /// ```
+    pub fn concat(&self, low: &FlexInt) -> FlexInt {
+        let mut bits = low.bits.clone();
+        bits.extend_from_slice(&self.bits);
+        Self::from_bits(&bits)
+    }
+
+    /// Splits this integer into two at the given bit index, returning the high and low parts.
+    /// 
+    /// The low part is composed of the bits below `index`, so has a size of `index`. The high part
+    /// is composed of the remaining bits, so has a size of `self.size() - index`.
+    /// 
+    /// Panics if the index is greater than the size of this integer.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0xCAFE, 16);
+    /// let (high, low) = i.split_at(8);
+    /// assert_eq!(high, FlexInt::from_int(0xCA, 8));
+    /// assert_eq!(low, FlexInt::from_int(0xFE, 8));
+    /// assert_eq!(high.concat(&low), i);
+    /// ```
+    pub fn split_at(&self, index: usize) -> (FlexInt, FlexInt) {
+        if index > self.size() {
+            panic!("cannot split at an index greater than the size");
+        }
+
+        let (low, high) = self.bits.split_at(index);
+        (Self::from_bits(high), Self::from_bits(low))
+    }
+
+    /// Shifts the bits of this integer towards the most-significant end by `amount`, filling the
+    /// least-significant bits with zeroes. The size stays the same.
+    /// 
+    /// Returns the shifted integer, and whether overflow occurred because any set bits were shifted
+    /// out of the top.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0b00101101, 8);
+    /// assert_eq!(i.shift_left(2), (FlexInt::from_int(0b10110100, 8), false));
+    /// assert_eq!(i.shift_left(3), (FlexInt::from_int(0b01101000, 8), true));
+    /// assert_eq!(i.shift_left(100), (FlexInt::new(8), true));
+    /// ```
+    pub fn shift_left(&self, amount: usize) -> (Self, bool) {
+        // Shifting any further only pops zeroes which were shifted in
+        let (result, popped) = self.pop_shift_left(amount.min(self.size()));
+        (result, popped.contains(&true))
+    }
+
+    /// Shifts the bits of this integer towards the least-significant end by `amount`, filling the
+    /// most-significant bits with zeroes. The size stays the same.
+    /// 
+    /// This is equivalent to unsigned division by a power of two. If the amount is at least the
+    /// size of the integer, the result is zero.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0b10110100, 8);
+    /// assert_eq!(i.shift_right_logical(2), FlexInt::from_int(0b00101101, 8));
+    /// assert_eq!(i.shift_right_logical(8), FlexInt::new(8));
+    /// ```
+    pub fn shift_right_logical(&self, amount: usize) -> Self {
+        self.shift_right_filling(amount, false)
+    }
+
+    /// Shifts the bits of this integer towards the least-significant end by `amount`, filling the
+    /// most-significant bits with copies of the sign bit. The size stays the same.
+    /// 
+    /// This is equivalent to signed division by a power of two, rounding towards negative infinity.
+    /// If the amount is at least the size of the integer, every bit of the result is the sign bit.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// // Positive numbers are filled with zeroes...
+    /// let i = FlexInt::from_int(0b00110100, 8);
+    /// assert_eq!(i.shift_right_arithmetic(2), FlexInt::from_int(0b00001101, 8));
+    /// 
+    /// // ...but negative numbers are filled with ones, so -76 >> 2 is -19
+    /// let i = FlexInt::from_int(0b10110100, 8);
+    /// assert_eq!(i.shift_right_arithmetic(2), FlexInt::from_int(0b11101101, 8));
+    /// assert_eq!(i.shift_right_arithmetic(2).to_signed_decimal_string(), "-19");
+    /// assert_eq!(i.shift_right_arithmetic(100), FlexInt::from_int(0b11111111, 8));
+    /// ```
+    pub fn shift_right_arithmetic(&self, amount: usize) -> Self {
+        self.shift_right_filling(amount, self.is_negative())
+    }
+
+    fn shift_right_filling(&self, amount: usize, fill: bool) -> Self {
+        let amount = amount.min(self.size());
+        let mut bits = self.bits[amount..].to_vec();
+        bits.resize(self.size(), fill);
+        Self::from_bits(&bits)
+    }
+
+    /// Calculates the square root of this integer, rounded down. The result is the same size.
+    /// 
+    /// Returns `None` if the integer is signed and negative, since it then has no real square root.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert_eq!(FlexInt::from_int(144, 16).isqrt(false), Some(FlexInt::from_int(12, 16)));
+    /// assert_eq!(FlexInt::from_int(145, 16).isqrt(false), Some(FlexInt::from_int(12, 16)));
+    /// assert_eq!(FlexInt::from_int(168, 16).isqrt(false), Some(FlexInt::from_int(12, 16)));
+    /// assert_eq!(FlexInt::from_int(169, 16).isqrt(false), Some(FlexInt::from_int(13, 16)));
+    /// assert_eq!(FlexInt::new(16).isqrt(false), Some(FlexInt::new(16)));
+    /// 
+    /// // 255 unsigned, or -1 signed
+    /// let i = FlexInt::from_int(255, 8);
+    /// assert_eq!(i.isqrt(false), Some(FlexInt::from_int(15, 8)));
+    /// assert_eq!(i.isqrt(true), None);
+    /// ```
+    pub fn isqrt(&self, signed: bool) -> Option<Self> {
+        if signed && self.is_negative() {
+            return None;
+        }
+
+        // Uses the digit-by-digit method, finding one bit of the root for each pair of bits in this
+        // number: https://en.wikipedia.org/wiki/Methods_of_computing_square_roots#Binary_numeral_system_(base_2)
+        // Work with an extra bit, so that adding to the root can never overflow
+        let size = self.size() + 1;
+        let mut remaining = self.zero_extend(size);
+        let mut root = Self::new(size);
+        for i in (0..self.size()).rev().filter(|i| i % 2 == 0) {
+            let mut bit = Self::new(size);
+            *bit.bit_mut(i) = true;
+
+            let (candidate, _) = root.add(&bit, false);
+            root = root.shift_right_logical(1);
+            if !remaining.is_less_than_unsigned(&candidate) {
+                (remaining, _) = remaining.subtract_unsigned(&candidate);
+                (root, _) = root.add(&bit, false);
+            }
+        }
+
+        let (root, _, _) = root.shrink(self.size());
+        Some(root)
+    }
+
+    /// Rotates the bits of this integer towards the most-significant end by `amount`, so that bits
+    /// shifted out of the top reappear at the bottom. The size stays the same.
+    /// 
+    /// The amount is taken modulo the size, so rotating by the size leaves the integer unchanged.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0b1000, 4);
+    /// assert_eq!(i.rotate_left(1), FlexInt::from_int(0b0001, 4));
+    /// 
+    /// let i = FlexInt::from_int(0b10110100, 8);
+    /// assert_eq!(i.rotate_left(3), FlexInt::from_int(0b10100101, 8));
+    /// assert_eq!(i.rotate_left(11), FlexInt::from_int(0b10100101, 8));
+    /// assert_eq!(i.rotate_left(8), i);
+    /// ```
+    pub fn rotate_left(&self, amount: usize) -> Self {
+        let mut bits = self.bits.clone();
+        if !bits.is_empty() {
+            // Bits are stored least-significant first, so the vector rotates the other way
+            bits.rotate_right(amount % self.size());
+        }
+        Self::from_bits(&bits)
+    }
+
+    /// Rotates the bits of this integer towards the least-significant end by `amount`, so that bits
+    /// shifted out of the bottom reappear at the top. The size stays the same.
+    /// 
+    /// The amount is taken modulo the size, so rotating by the size leaves the integer unchanged.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0b0001, 4);
+    /// assert_eq!(i.rotate_right(1), FlexInt::from_int(0b1000, 4));
+    /// 
+    /// let i = FlexInt::from_int(0b10110100, 8);
+    /// assert_eq!(i.rotate_right(3), FlexInt::from_int(0b10010110, 8));
+    /// assert_eq!(i.rotate_right(3).rotate_left(3), i);
+    /// ```
+    pub fn rotate_right(&self, amount: usize) -> Self {
+        let mut bits = self.bits.clone();
+        if !bits.is_empty() {
+            bits.rotate_left(amount % self.size());
+        }
+        Self::from_bits(&bits)
+    }
+
+    /// Rotates the bits of this integer one place towards the most-significant end, through a carry
+    /// bit, like a processor's rotate-with-carry instruction. The carry is shifted into the bottom,
+    /// and the bit shifted out of the top is returned as the new carry. The size stays the same.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0b10110100, 8);
+    /// assert_eq!(i.rotate_left_through_carry(false), (FlexInt::from_int(0b01101000, 8), true));
+    /// assert_eq!(i.rotate_left_through_carry(true), (FlexInt::from_int(0b01101001, 8), true));
+    /// 
+    /// let i = FlexInt::from_int(0b00110100, 8);
+    /// assert_eq!(i.rotate_left_through_carry(true), (FlexInt::from_int(0b01101001, 8), false));
+    /// ```
+    pub fn rotate_left_through_carry(&self, carry: bool) -> (Self, bool) {
+        let mut bits = self.bits.clone();
+        bits.insert(0, carry);
+        let carry = bits.pop().unwrap();
+        (Self::from_bits(&bits), carry)
+    }
+
+    /// Rotates the bits of this integer one place towards the least-significant end, through a
+    /// carry bit. The carry is shifted into the top, and the bit shifted out of the bottom is
+    /// returned as the new carry. The size stays the same.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0b10110101, 8);
+    /// assert_eq!(i.rotate_right_through_carry(false), (FlexInt::from_int(0b01011010, 8), true));
+    /// assert_eq!(i.rotate_right_through_carry(true), (FlexInt::from_int(0b11011010, 8), true));
+    /// 
+    /// // Rotating back restores the original value and carry
+    /// let (r, carry) = i.rotate_right_through_carry(false);
+    /// assert_eq!(r.rotate_left_through_carry(carry), (i, false));
+    /// ```
+    pub fn rotate_right_through_carry(&self, carry: bool) -> (Self, bool) {
+        let mut bits = self.bits.clone();
+        bits.push(carry);
+        let carry = bits.remove(0);
+        (Self::from_bits(&bits), carry)
+    }
+
+    /// Reverses the order of the bits of this integer, so that the least-significant bit becomes the
+    /// most-significant. The size stays the same.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0b0001, 4);
+    /// assert_eq!(i.reverse_bits(), FlexInt::from_int(0b1000, 4));
+    /// 
+    /// let i = FlexInt::from_int(0b1101000, 7);
+    /// assert_eq!(i.reverse_bits(), FlexInt::from_int(0b0001011, 7));
+    /// assert_eq!(i.reverse_bits().reverse_bits(), i);
+    /// ```
+    pub fn reverse_bits(&self) -> Self {
+        let mut bits = self.bits.clone();
+        bits.reverse();
+        Self::from_bits(&bits)
+    }
+
+    /// Whether this integer is a power of two, treating it as unsigned - that is, whether exactly
+    /// one bit is set.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// assert!(FlexInt::from_int(1, 8).is_power_of_two());
+    /// assert!(FlexInt::from_int(128, 8).is_power_of_two());
+    /// assert!(!FlexInt::from_int(6, 8).is_power_of_two());
+    /// assert!(!FlexInt::new(8).is_power_of_two());
+    /// ```
+    pub fn is_power_of_two(&self) -> bool {
+        self.count_ones() == 1
+    }
+
+    /// Rounds this integer up to the nearest power of two, treating it as unsigned. Zero rounds up
+    /// to one.
+    /// 
+    /// Also returns a boolean indicating whether overflow occurred, because the power of two
+    /// doesn't fit in the size. If it does overflow, the result is zero.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(5, 8);
+    /// assert_eq!(i.next_power_of_two(), (FlexInt::from_int(8, 8), false));
+    /// 
+    /// let i = FlexInt::from_int(64, 8);
+    /// assert_eq!(i.next_power_of_two(), (i, false));
+    /// 
+    /// let i = FlexInt::new(8);
+    /// assert_eq!(i.next_power_of_two(), (FlexInt::from_int(1, 8), false));
+    /// 
+    /// let i = FlexInt::from_int(129, 8);
+    /// assert_eq!(i.next_power_of_two(), (FlexInt::new(8), true));
+    /// ```
+    pub fn next_power_of_two(&self) -> (Self, bool) {
+        if self.is_power_of_two() {
+            return (self.clone(), false);
+        }
+
+        // Otherwise, this is the power of two above the highest set bit
+        let exponent = if self.is_zero() { 0 } else { self.size() - self.leading_zeros() };
+        let mut result = Self::new(self.size());
+        if exponent < self.size() {
+            *result.bit_mut(exponent) = true;
+            (result, false)
+        } else {
+            (result, true)
+        }
+    }
+
     pub(crate) fn pop_shift_left(&self, amount: usize) -> (Self, Vec<bool>) {
         let mut bits = self.bits.clone();
         let mut popped = vec![];
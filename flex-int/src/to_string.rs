@@ -1,4 +1,5 @@
-use alloc::{string::{String, ToString}, vec, vec::Vec};
+use core::fmt;
+use alloc::{string::{String, ToString}, vec, vec::Vec, format};
 
 use crate::FlexInt;
 
@@ -14,6 +15,20 @@ impl FlexInt {
     /// assert_eq!(zero.to_unsigned_decimal_string(), "0");
     /// ```
     pub fn to_unsigned_decimal_string(&self) -> String {
+        let result: String = self.unsigned_decimal_digits().into_iter()
+            .map(|d| char::from_digit(d as u32, 10).unwrap())
+            .collect();
+
+        if result.is_empty() {
+            "0".to_string()
+        } else {
+            result
+        }
+    }
+
+    /// Gets the decimal digits of this number, treating it as unsigned, with the most-significant
+    /// digit first. There are no leading zeroes, so this is empty if the number is zero.
+    pub(crate) fn unsigned_decimal_digits(&self) -> Vec<u8> {
         // Algorithm translated from: https://stackoverflow.com/a/5247217/2626000
         
         // TODO: allocate smarter! len(bits) * ln(2) / ln(10)
@@ -45,22 +60,10 @@ impl FlexInt {
             }
         }
 
-        let mut result = "".to_string();
-        let mut encountered_nonzero_digit = false;
-        for digit in digits.iter().rev() {
-            if !encountered_nonzero_digit && *digit != 0 {
-                encountered_nonzero_digit = true;
-            }
-
-            if encountered_nonzero_digit {
-                result.push(char::from_digit(*digit as u32, 10).unwrap());
-            }
-        }
-
-        if result.is_empty() {
-            result = "0".to_string()
-        }
-        result
+        digits.into_iter()
+            .rev()
+            .skip_while(|d| *d == 0)
+            .collect()
     }
 
     /// Converts this number into a string of hexadecimal digits, treating it as unsigned.
@@ -117,7 +120,39 @@ impl FlexInt {
         result
     }
 
-    /// Converts this number into a string of hexadecimal digits, treating it as unsigned.
+    /// Converts this number into a string of octal digits, treating it as unsigned.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0o1234, 32);
+    /// assert_eq!(i.to_unsigned_octal_string(), "1234");
+    /// 
+    /// // The top digit may have fewer than three bits
+    /// let i = FlexInt::from_int(0xFF, 8);
+    /// assert_eq!(i.to_unsigned_octal_string(), "377");
+    /// 
+    /// let zero = FlexInt::new(16);
+    /// assert_eq!(zero.to_unsigned_octal_string(), "0");
+    /// ```
+    pub fn to_unsigned_octal_string(&self) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+
+        // Iterate through the bits of this number, in chunks of 3, from LSB to MSB
+        self.bits_without_leading_zeroes()
+            .chunks(3)
+            .rev()
+            .map(|chunk| {
+                let digit = chunk.iter()
+                    .rev()
+                    .fold(0, |acc, bit| (acc << 1) | (*bit as u32));
+                char::from_digit(digit, 8).unwrap()
+            })
+            .collect()
+    }
+
+    /// Converts this number into a string of binary digits, treating it as unsigned.
     /// 
     /// ```rust
     /// # use flex_int::FlexInt;
@@ -171,7 +206,24 @@ impl FlexInt {
         self.to_signed_string(Self::to_unsigned_hex_string)
     }
 
-    /// Converts this number into a string of hexadecimal digits, treating it as signed.
+    /// Converts this number into a string of octal digits, treating it as signed.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (i, _) = FlexInt::from_signed_octal_string("1234", 32).unwrap();
+    /// assert_eq!(i.to_signed_octal_string(), "1234");
+    /// 
+    /// let (i, _) = FlexInt::from_signed_octal_string("-1234", 32).unwrap();
+    /// assert_eq!(i.to_signed_octal_string(), "-1234");
+    /// 
+    /// let i = FlexInt::from_int(0x80, 8);
+    /// assert_eq!(i.to_signed_octal_string(), "-200");
+    /// ```
+    pub fn to_signed_octal_string(&self) -> String {
+        self.to_signed_string(Self::to_unsigned_octal_string)
+    }
+
+    /// Converts this number into a string of binary digits, treating it as signed.
     /// 
     /// ```rust
     /// # use flex_int::FlexInt;
@@ -180,11 +232,105 @@ impl FlexInt {
     /// 
     /// let (i, _) = FlexInt::from_signed_binary_string("-110111001", 32).unwrap();
     /// assert_eq!(i.to_signed_binary_string(), "-110111001");
+    /// 
+    /// // The top bit is the sign, rather than a digit
+    /// let i = FlexInt::from_int(0b11111110, 8);
+    /// assert_eq!(i.to_signed_binary_string(), "-10");
+    /// 
+    /// let zero = FlexInt::new(16);
+    /// assert_eq!(zero.to_signed_binary_string(), "0");
     /// ```
     pub fn to_signed_binary_string(&self) -> String {
         self.to_signed_string(Self::to_unsigned_binary_string)
     }
 
+    /// Converts this number into a string of decimal digits, padded with leading zeroes so that
+    /// there are at least `min_digits` digits. If the number is signed and negative, the sign is
+    /// placed before the padding, and doesn't count as one of the digits.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(42, 16);
+    /// assert_eq!(i.to_decimal_string_min_width(5, false), "00042");
+    /// 
+    /// let (i, _) = FlexInt::from_signed_decimal_string("-42", 16).unwrap();
+    /// assert_eq!(i.to_decimal_string_min_width(5, true), "-00042");
+    /// 
+    /// let i = FlexInt::from_int(123456, 32);
+    /// assert_eq!(i.to_decimal_string_min_width(5, false), "123456");
+    /// ```
+    pub fn to_decimal_string_min_width(&self, min_digits: usize, signed: bool) -> String {
+        let str = if signed {
+            self.to_signed_decimal_string()
+        } else {
+            self.to_unsigned_decimal_string()
+        };
+
+        let (sign, digits) = match str.strip_prefix('-') {
+            Some(digits) => ("-", digits),
+            None => ("", str.as_str()),
+        };
+        format!("{sign}{digits:0>min_digits$}")
+    }
+
+    /// Converts this number into a string of hexadecimal digits showing its raw two's complement
+    /// bit pattern, with no sign. The string is padded with leading zeroes to the full width of the
+    /// number, which is `ceil(size / 4)` digits.
+    /// 
+    /// Unlike [`to_signed_hex_string`], negative numbers aren't shown with a `-`.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let (i, _) = FlexInt::from_signed_decimal_string("-1", 16).unwrap();
+    /// assert_eq!(i.to_twos_complement_hex_string(), "FFFF");
+    /// 
+    /// let (i, _) = FlexInt::from_signed_decimal_string("-16", 8).unwrap();
+    /// assert_eq!(i.to_twos_complement_hex_string(), "F0");
+    /// 
+    /// let i = FlexInt::from_int(0x2A, 12);
+    /// assert_eq!(i.to_twos_complement_hex_string(), "02A");
+    /// ```
+    pub fn to_twos_complement_hex_string(&self) -> String {
+        let digits = self.size().div_ceil(4);
+        let str = self.to_unsigned_hex_string();
+        format!("{str:0>digits$}")
+    }
+
+    /// Splits the raw bit pattern of this number into bytes, returning each as a two-digit
+    /// hexadecimal string, with the most-significant byte first.
+    /// 
+    /// If the size isn't a multiple of 8, the most-significant byte is padded with zero bits, so
+    /// has fewer significant bits than the rest. A zero-sized number has no bytes.
+    /// 
+    /// ```rust
+    /// # use flex_int::FlexInt;
+    /// let i = FlexInt::from_int(0xCAFE, 16);
+    /// assert_eq!(i.to_byte_hex_groups(), ["CA", "FE"]);
+    /// 
+    /// let (i, _) = FlexInt::from_signed_decimal_string("-1", 16).unwrap();
+    /// assert_eq!(i.to_byte_hex_groups(), ["FF", "FF"]);
+    /// 
+    /// // The top byte only has 4 bits
+    /// let i = FlexInt::from_int(0xABC, 12);
+    /// assert_eq!(i.to_byte_hex_groups(), ["0A", "BC"]);
+    /// let i = FlexInt::from_int(0x00C, 12);
+    /// assert_eq!(i.to_byte_hex_groups(), ["00", "0C"]);
+    /// ```
+    pub fn to_byte_hex_groups(&self) -> Vec<String> {
+        let digits = self.size().div_ceil(8) * 2;
+        if digits == 0 {
+            return vec![];
+        }
+
+        let str = self.to_unsigned_hex_string();
+        format!("{str:0>digits$}")
+            .chars()
+            .collect::<Vec<_>>()
+            .chunks(2)
+            .map(|byte| byte.iter().collect())
+            .collect()
+    }
+
     /// A convenience method which performs a signed number-to-string conversion by using an
     /// existing implementation of an unsigned conversion.
     fn to_signed_string(&self, unsigned_string_fn: impl FnOnce(&Self) -> String) -> String {
@@ -195,4 +341,70 @@ impl FlexInt {
         }
         str
     }
-}
\ No newline at end of file
+}
+
+// These all treat the number as unsigned, like Rust's own integers do for everything but `Display`.
+// The usual flags, like width and `#` for a `0b`/`0x` prefix, are supported
+
+/// Formats the number as unsigned decimal.
+/// 
+/// ```rust
+/// # use flex_int::FlexInt;
+/// let i = FlexInt::from_int(1234, 16);
+/// assert_eq!(format!("{}", i), "1234");
+/// assert_eq!(format!("{:>6}", i), "  1234");
+/// ```
+impl fmt::Display for FlexInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "", &self.to_unsigned_decimal_string())
+    }
+}
+
+/// ```rust
+/// # use flex_int::FlexInt;
+/// let i = FlexInt::from_int(0b1101, 8);
+/// assert_eq!(format!("{:b}", i), "1101");
+/// assert_eq!(format!("{:#b}", i), "0b1101");
+/// assert_eq!(format!("{:08b}", i), "00001101");
+/// ```
+impl fmt::Binary for FlexInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0b", &self.to_unsigned_binary_string())
+    }
+}
+
+/// ```rust
+/// # use flex_int::FlexInt;
+/// let i = FlexInt::from_int(0o755, 16);
+/// assert_eq!(format!("{:o}", i), "755");
+/// assert_eq!(format!("{:#o}", i), "0o755");
+/// ```
+impl fmt::Octal for FlexInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0o", &self.to_unsigned_octal_string())
+    }
+}
+
+/// ```rust
+/// # use flex_int::FlexInt;
+/// let i = FlexInt::from_int(0xCAFE, 16);
+/// assert_eq!(format!("{:x}", i), "cafe");
+/// assert_eq!(format!("{:#x}", i), "0xcafe");
+/// ```
+impl fmt::LowerHex for FlexInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_unsigned_hex_string().to_ascii_lowercase())
+    }
+}
+
+/// ```rust
+/// # use flex_int::FlexInt;
+/// let i = FlexInt::from_int(0xCAFE, 16);
+/// assert_eq!(format!("{:X}", i), "CAFE");
+/// assert_eq!(format!("{:#X}", i), "0xCAFE");
+/// ```
+impl fmt::UpperHex for FlexInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad_integral(true, "0x", &self.to_unsigned_hex_string())
+    }
+}
@@ -0,0 +1,107 @@
+use flex_int::FlexInt;
+
+type StringParser = fn(&str, usize) -> Option<(FlexInt, bool)>;
+
+fn digits_of(value: u64, radix: u32) -> Vec<u8> {
+    let s = match radix {
+        2 => format!("{value:b}"),
+        8 => format!("{value:o}"),
+        10 => format!("{value}"),
+        16 => format!("{value:X}"),
+        _ => unreachable!(),
+    };
+    s.chars().map(|c| c.to_digit(radix).unwrap() as u8).collect()
+}
+
+fn string_of(value: u64, radix: u32, negative: bool) -> String {
+    let s = match radix {
+        2 => format!("{value:b}"),
+        8 => format!("{value:o}"),
+        10 => format!("{value}"),
+        16 => format!("{value:X}"),
+        _ => unreachable!(),
+    };
+    if negative { format!("-{s}") } else { s }
+}
+
+fn check_matches_string_parsers(radix: u32, unsigned_parser: StringParser, signed_parser: StringParser) {
+    // Include sizes narrower than a single digit, except for decimal, where the string parser
+    // needs to be able to represent 10
+    let narrow: &[usize] = if radix == 10 { &[] } else { &[1, 2, 3] };
+    for size in narrow.iter().copied().chain([4, 5, 8, 10]) {
+        for value in 0..(1u64 << (size + 2)) {
+            let digits = digits_of(value, radix);
+
+            assert_eq!(
+                FlexInt::from_ascii_digits(&digits, radix, false, false, size),
+                unsigned_parser(&string_of(value, radix, false), size),
+                "unsigned {value} in {size} bits, radix {radix}",
+            );
+            assert_eq!(
+                FlexInt::from_ascii_digits(&digits, radix, true, false, size),
+                signed_parser(&string_of(value, radix, false), size),
+                "signed {value} in {size} bits, radix {radix}",
+            );
+            assert_eq!(
+                FlexInt::from_ascii_digits(&digits, radix, true, true, size),
+                signed_parser(&string_of(value, radix, true), size),
+                "signed -{value} in {size} bits, radix {radix}",
+            );
+        }
+    }
+}
+
+#[test]
+fn ascii_digits_decimal() {
+    check_matches_string_parsers(10, FlexInt::from_unsigned_decimal_string, FlexInt::from_signed_decimal_string);
+}
+
+#[test]
+fn ascii_digits_hex() {
+    check_matches_string_parsers(16, FlexInt::from_unsigned_hex_string, FlexInt::from_signed_hex_string);
+}
+
+#[test]
+fn ascii_digits_binary() {
+    check_matches_string_parsers(2, FlexInt::from_unsigned_binary_string, FlexInt::from_signed_binary_string);
+}
+
+#[test]
+fn ascii_digits_octal() {
+    check_matches_string_parsers(8, FlexInt::from_unsigned_octal_string, FlexInt::from_signed_octal_string);
+}
+
+#[test]
+fn ascii_digits_leading_zeros() {
+    // Shifted-out zeros aren't overflow
+    let (i, over) = FlexInt::from_ascii_digits(&[0; 20], 16, false, false, 8).unwrap();
+    assert_eq!(i, FlexInt::new(8));
+    assert!(!over);
+
+    let digits = [[0; 20].as_slice(), &[1, 0, 1]].concat();
+    let (i, over) = FlexInt::from_ascii_digits(&digits, 2, false, false, 3).unwrap();
+    assert_eq!(i, FlexInt::from_int(0b101, 3));
+    assert!(!over);
+}
+
+#[test]
+fn ascii_digits_narrower_than_radix() {
+    // 9 doesn't fit into 3 bits, even though each step of the conversion is done in the radix
+    let (i, over) = FlexInt::from_ascii_digits(&[9], 10, false, false, 3).unwrap();
+    assert_eq!(i, FlexInt::from_int(1, 3));
+    assert!(over);
+
+    let (i, over) = FlexInt::from_ascii_digits(&[7], 10, false, false, 3).unwrap();
+    assert_eq!(i, FlexInt::from_int(7, 3));
+    assert!(!over);
+}
+
+#[test]
+fn ascii_digits_invalid() {
+    assert_eq!(FlexInt::from_ascii_digits(&[0xA], 10, false, false, 8), None);
+    assert_eq!(FlexInt::from_ascii_digits(&[2], 2, false, false, 8), None);
+
+    // Only radices up to 16 are supported
+    assert_eq!(FlexInt::from_ascii_digits(&[1], 1, false, false, 8), None);
+    assert_eq!(FlexInt::from_ascii_digits(&[1], 32, false, false, 8), None);
+}
@@ -1,12 +1,12 @@
 use std::fmt::Display;
 
 use flex_int::FlexInt;
-use num_traits::{ops::overflowing::{OverflowingAdd, OverflowingSub, OverflowingMul}, CheckedDiv, Zero};
+use num_traits::{ops::overflowing::{OverflowingAdd, OverflowingSub, OverflowingMul}, CheckedDiv, CheckedRem, Zero};
 use rand::{prelude::Distribution, distributions::Standard, seq::SliceRandom};
 
 trait TestCaseInt
 where
-    Self: Sized + OverflowingAdd + OverflowingSub + OverflowingMul + CheckedDiv + Zero + Display,
+    Self: Sized + OverflowingAdd + OverflowingSub + OverflowingMul + CheckedDiv + CheckedRem + Zero + Display,
 {
     fn bits() -> usize;
     fn is_signed() -> bool;
@@ -49,10 +49,11 @@ enum Operation {
     Subtract,
     Multiply,
     Divide,
+    Remainder,
 }
 
 impl Operation {
-    const ALL: [Operation; 4] = [Operation::Add, Operation::Subtract, Operation::Multiply, Operation::Divide];
+    const ALL: [Operation; 5] = [Operation::Add, Operation::Subtract, Operation::Multiply, Operation::Divide, Operation::Remainder];
 
     fn random() -> Self {
         *Self::ALL.choose(&mut rand::thread_rng()).unwrap()
@@ -64,6 +65,12 @@ impl Operation {
             Operation::Subtract => a.overflowing_sub(b),
             Operation::Multiply => a.overflowing_mul(b),
             Operation::Divide => if let Some(r) = a.checked_div(b) { (r, false) } else { (I::zero(), true) },
+            Operation::Remainder => match a.checked_rem(b) {
+                Some(r) => (r, false),
+                // The only other failure is the lowest negative divided by -1, which is exactly 0
+                None if b.is_zero() => (I::zero(), true),
+                None => (I::zero(), false),
+            },
         }
     }
 
@@ -73,6 +80,7 @@ impl Operation {
             Operation::Subtract => a.subtract(&b, I::is_signed()),
             Operation::Multiply => a.multiply(&b, I::is_signed()),
             Operation::Divide => a.divide(&b, I::is_signed()),
+            Operation::Remainder => a.remainder(&b, I::is_signed()),
         }
     }
 
@@ -82,6 +90,7 @@ impl Operation {
             Operation::Subtract => "-",
             Operation::Multiply => "*",
             Operation::Divide => "/",
+            Operation::Remainder => "%",
         }
     }
 }
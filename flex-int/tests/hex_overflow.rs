@@ -0,0 +1,44 @@
+use flex_int::FlexInt;
+
+/// Checks that parsing every value up to a few bits larger than `size` as hex gives the value
+/// truncated to `size`, and reports overflow exactly when the value doesn't fit.
+fn check_hex_overflow(size: usize) {
+    for value in 0..(1u64 << (size + 3)) {
+        let (result, overflow) = FlexInt::from_unsigned_hex_string(&format!("{value:X}"), size).unwrap();
+        let fits = value < (1 << size);
+        assert_eq!(overflow, !fits, "{value:X} in {size} bits");
+        assert_eq!(result, FlexInt::from_int(value & ((1 << size) - 1), size), "{value:X} in {size} bits");
+    }
+}
+
+#[test]
+fn hex_overflow_sub_nibble_widths() {
+    for size in 1..=3 {
+        check_hex_overflow(size);
+    }
+}
+
+#[test]
+fn hex_overflow_odd_widths() {
+    for size in [5, 6, 7, 10] {
+        check_hex_overflow(size);
+    }
+}
+
+#[test]
+fn hex_overflow_nibble_widths() {
+    for size in [4, 8, 12] {
+        check_hex_overflow(size);
+    }
+}
+
+#[test]
+fn hex_overflow_leading_zeroes() {
+    let (result, overflow) = FlexInt::from_unsigned_hex_string("000F", 5).unwrap();
+    assert_eq!(result, FlexInt::from_int(0xF, 5));
+    assert!(!overflow);
+
+    let (result, overflow) = FlexInt::from_unsigned_hex_string("0007", 3).unwrap();
+    assert_eq!(result, FlexInt::from_int(0x7, 3));
+    assert!(!overflow);
+}
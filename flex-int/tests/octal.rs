@@ -0,0 +1,29 @@
+use flex_int::FlexInt;
+
+/// Checks that parsing every value up to a few bits larger than `size` as octal gives the value
+/// truncated to `size`, reports overflow exactly when the value doesn't fit, and converts back to
+/// the same digits.
+fn check_octal(size: usize) {
+    for value in 0..(1u64 << (size + 3)) {
+        let (result, overflow) = FlexInt::from_unsigned_octal_string(&format!("{value:o}"), size).unwrap();
+        let fits = value < (1 << size);
+        let truncated = value & ((1 << size) - 1);
+        assert_eq!(overflow, !fits, "{value:o} in {size} bits");
+        assert_eq!(result, FlexInt::from_int(truncated, size), "{value:o} in {size} bits");
+        assert_eq!(result.to_unsigned_octal_string(), format!("{truncated:o}"), "{value:o} in {size} bits");
+    }
+}
+
+#[test]
+fn octal_sub_digit_widths() {
+    for size in 1..=2 {
+        check_octal(size);
+    }
+}
+
+#[test]
+fn octal_widths() {
+    for size in [3, 4, 5, 6, 8, 9, 10] {
+        check_octal(size);
+    }
+}
@@ -0,0 +1,29 @@
+use flex_int::FlexInt;
+
+fn unsigned(i: u8) -> FlexInt {
+    FlexInt::from_int(i as u64, 8)
+}
+
+fn signed(i: i8) -> FlexInt {
+    FlexInt::from_int(i as u8 as u64, 8)
+}
+
+#[test]
+fn saturating_unsigned_exhaustive() {
+    for a in u8::MIN..=u8::MAX {
+        for b in u8::MIN..=u8::MAX {
+            assert_eq!(unsigned(a).saturating_add(&unsigned(b), false), unsigned(a.saturating_add(b)), "{} + {}", a, b);
+            assert_eq!(unsigned(a).saturating_sub(&unsigned(b), false), unsigned(a.saturating_sub(b)), "{} - {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn saturating_signed_exhaustive() {
+    for a in i8::MIN..=i8::MAX {
+        for b in i8::MIN..=i8::MAX {
+            assert_eq!(signed(a).saturating_add(&signed(b), true), signed(a.saturating_add(b)), "{} + {}", a, b);
+            assert_eq!(signed(a).saturating_sub(&signed(b), true), signed(a.saturating_sub(b)), "{} - {}", a, b);
+        }
+    }
+}
@@ -0,0 +1,113 @@
+use flex_int::FlexInt;
+
+// Signed multiplication decides whether it overflowed using the counts of cut bits returned by
+// `shrink`, so these must be exactly right
+
+#[test]
+fn shrink_cuts_zeroes() {
+    let i = FlexInt::from_int(0b0000_1011, 8);
+    for new_size in 4..=8 {
+        let (result, zeroes, ones) = i.shrink(new_size);
+        assert_eq!(result, FlexInt::from_int(0b1011, new_size));
+        assert_eq!((zeroes, ones), (8 - new_size, 0), "shrinking to {}", new_size);
+    }
+}
+
+#[test]
+fn shrink_cuts_ones() {
+    let i = FlexInt::from_int(0b1111_0100, 8);
+    for new_size in 4..=8 {
+        let (result, zeroes, ones) = i.shrink(new_size);
+        assert_eq!(result.bits(), &i.bits()[..new_size]);
+        assert_eq!((zeroes, ones), (0, 8 - new_size), "shrinking to {}", new_size);
+    }
+}
+
+#[test]
+fn shrink_cuts_mixed() {
+    // Every 8-bit pattern, cut to every size
+    for value in 0..=u8::MAX {
+        let i = FlexInt::from_int(value as u64, 8);
+        for new_size in 0..=8 {
+            let cut = if new_size == 8 { 0 } else { value >> new_size };
+            let expected_ones = cut.count_ones() as usize;
+            let expected_zeroes = (8 - new_size) - expected_ones;
+
+            let (result, zeroes, ones) = i.shrink(new_size);
+            assert_eq!(result.bits(), &i.bits()[..new_size]);
+            assert_eq!(
+                (zeroes, ones), (expected_zeroes, expected_ones),
+                "shrinking {:#010b} to {}", value, new_size,
+            );
+        }
+    }
+}
+
+#[test]
+fn shrink_to_same_size() {
+    let i = FlexInt::from_int(0b1010_0101, 8);
+    assert_eq!(i.shrink(8), (i.clone(), 0, 0));
+}
+
+#[test]
+#[should_panic]
+fn shrink_to_larger_size() {
+    FlexInt::new(8).shrink(9);
+}
+
+fn signed(value: i8) -> FlexInt {
+    FlexInt::from_signed_decimal_string(&value.to_string(), 8).unwrap().0
+}
+
+#[test]
+fn multiply_signed_edge_cases() {
+    // Results which only just fit cut a mixture of bits from the double-width product, or none at
+    // all, so are easy to get wrong
+    for (a, b) in [
+        (-128, 1), (1, -128), (-64, 2), (2, -64), (16, -8), (-16, 8), (-1, -127),
+        (64, 2), (-128, -1), (-1, -128), (-128, 2), (-128, -128), (127, 127), (-16, -8),
+    ] as [(i8, i8); 14] {
+        let (expected_result, expected_overflow) = a.overflowing_mul(b);
+        let (result, overflow) = signed(a).multiply(&signed(b), true);
+
+        assert_eq!(result.to_signed_decimal_string(), expected_result.to_string(), "{} * {}", a, b);
+        assert_eq!(overflow, expected_overflow, "{} * {}", a, b);
+    }
+}
+
+#[test]
+fn multiply_signed_exhaustive() {
+    for a in i8::MIN..=i8::MAX {
+        for b in i8::MIN..=i8::MAX {
+            let (expected_result, expected_overflow) = a.overflowing_mul(b);
+            let (result, overflow) = signed(a).multiply(&signed(b), true);
+
+            assert_eq!(result.to_signed_decimal_string(), expected_result.to_string(), "{} * {}", a, b);
+            assert_eq!(overflow, expected_overflow, "{} * {}", a, b);
+        }
+    }
+}
+
+#[test]
+fn resize_exhaustive() {
+    // Every 8-bit pattern, resized to every size up to 12 bits, overflows exactly when its value
+    // doesn't fit in the new size
+    for value in 0..=u8::MAX {
+        let i = FlexInt::from_int(value as u64, 8);
+        for new_size in 0..=12 {
+            let unsigned_fits = (value as u64) < (1 << new_size);
+            let (result, overflow) = i.resize(new_size, false);
+            assert_eq!(overflow, !unsigned_fits, "resizing {} to U{}", value, new_size);
+            assert_eq!(result, FlexInt::from_int(value as u64, new_size));
+
+            let signed_value = value as i8 as i64;
+            let signed_fits = match new_size {
+                0 => signed_value == 0,
+                _ => (-(1 << (new_size - 1))..(1 << (new_size - 1))).contains(&signed_value),
+            };
+            let (result, overflow) = i.resize(new_size, true);
+            assert_eq!(overflow, !signed_fits, "resizing {} to S{}", signed_value, new_size);
+            assert_eq!(result, FlexInt::from_int(signed_value as u64, new_size));
+        }
+    }
+}
@@ -0,0 +1,20 @@
+#![cfg(feature = "std")]
+
+use std::error::Error;
+
+use flex_int::{FlexInt, ParseFlexIntError};
+
+fn parse_u8(s: &str) -> Result<FlexInt, Box<dyn Error>> {
+    let (i, _) = FlexInt::parse_str_radix(s, 10, false, 8)?;
+    Ok(i)
+}
+
+#[test]
+fn parse_error_is_error() {
+    assert_eq!(parse_u8("200").unwrap(), FlexInt::from_int(200, 8));
+
+    let error = parse_u8("2x0").unwrap_err();
+    assert_eq!(error.to_string(), "invalid digit");
+    assert_eq!(error.downcast_ref::<ParseFlexIntError>(), Some(&ParseFlexIntError::InvalidDigit));
+    assert!(error.source().is_none());
+}
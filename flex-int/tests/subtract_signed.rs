@@ -0,0 +1,34 @@
+use flex_int::FlexInt;
+
+// Subtracting the largest possible negative number takes a special path in `subtract_signed`,
+// because the subtrahend can't be negated
+
+#[test]
+fn subtract_min_i8() {
+    let min = FlexInt::from_signed_decimal_string(&i8::MIN.to_string(), 8).unwrap().0;
+
+    for a in i8::MIN..=i8::MAX {
+        let (expected_result, expected_overflow) = a.overflowing_sub(i8::MIN);
+
+        let a_flex = FlexInt::from_signed_decimal_string(&a.to_string(), 8).unwrap().0;
+        let (result, overflow) = a_flex.subtract_signed(&min);
+
+        assert_eq!(result.to_signed_decimal_string(), expected_result.to_string(), "{} - {}", a, i8::MIN);
+        assert_eq!(overflow, expected_overflow, "{} - {}", a, i8::MIN);
+    }
+}
+
+#[test]
+fn subtract_min_i16() {
+    let min = FlexInt::from_signed_decimal_string(&i16::MIN.to_string(), 16).unwrap().0;
+
+    for a in (i16::MIN..=i16::MAX).step_by(7).chain([-1, 0, 1, i16::MAX]) {
+        let (expected_result, expected_overflow) = a.overflowing_sub(i16::MIN);
+
+        let a_flex = FlexInt::from_signed_decimal_string(&a.to_string(), 16).unwrap().0;
+        let (result, overflow) = a_flex.subtract_signed(&min);
+
+        assert_eq!(result.to_signed_decimal_string(), expected_result.to_string(), "{} - {}", a, i16::MIN);
+        assert_eq!(overflow, expected_overflow, "{} - {}", a, i16::MIN);
+    }
+}
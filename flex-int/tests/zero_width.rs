@@ -0,0 +1,40 @@
+use flex_int::FlexInt;
+
+#[test]
+fn construct_zero_width() {
+    let i = FlexInt::new(0);
+    assert_eq!(i.size(), 0);
+    assert_eq!(i.bits(), &[]);
+    assert_eq!(i, FlexInt::from_int(123, 0));
+    assert_eq!(i, FlexInt::from_bits(&[]));
+    assert_eq!(i, FlexInt::max_value(0, true));
+    assert_eq!(i, FlexInt::min_value(0, true));
+}
+
+#[test]
+fn inspect_zero_width() {
+    let i = FlexInt::new(0);
+    assert!(i.is_zero());
+    assert!(!i.is_negative());
+    assert_eq!(i.count_ones(), 0);
+    assert_eq!(i.abs(), Some(i.clone()));
+    assert_eq!(i.negate(), Some(i.clone()));
+
+    assert_eq!(i.to_unsigned_decimal_string(), "0");
+    assert_eq!(i.to_signed_decimal_string(), "0");
+    assert_eq!(i.to_unsigned_hex_string(), "0");
+    assert_eq!(i.to_signed_binary_string(), "0");
+}
+
+#[test]
+fn extend_zero_width() {
+    let i = FlexInt::new(0);
+    assert_eq!(i.sign_extend(4), FlexInt::new(4));
+    assert_eq!(i.zero_extend(4), FlexInt::new(4));
+}
+
+#[test]
+#[should_panic(expected = "cannot create a one with zero bits")]
+fn one_zero_width() {
+    FlexInt::new_one(0);
+}